@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn field_table(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.open_field_table();
+    EventResult::Consumed(None)
+}
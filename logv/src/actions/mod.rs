@@ -30,9 +30,23 @@ pub mod search_prev;
 mod help;
 mod go_to_line;
 mod go_to_date;
+mod date_format;
 mod open_file;
 mod application_metrics;
+mod show_config;
 mod open_filter_dialog;
 mod action_impl;
 mod action_impl_registry;
 mod switch_theme;
+mod select_theme;
+mod toggle_bookmark;
+mod toggle_soft_wrap;
+mod toggle_hex_view;
+mod switch_display_zone;
+mod next_time_gap;
+mod set_time_reference;
+mod toggle_time_deltas;
+mod pattern_clustering;
+mod field_table;
+mod record_details;
+mod line_details;
@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn next_time_gap(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.jump_to_next_gap();
+    EventResult::Consumed(None)
+}
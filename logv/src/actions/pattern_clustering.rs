@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn pattern_clustering(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.open_clustering();
+    EventResult::Consumed(None)
+}
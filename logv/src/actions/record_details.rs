@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn record_details(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.show_record_details();
+    EventResult::Consumed(None)
+}
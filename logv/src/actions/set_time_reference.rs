@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn set_time_reference(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.toggle_time_reference();
+    EventResult::Consumed(None)
+}
@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn switch_display_zone(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.switch_display_zone();
+    EventResult::Consumed(None)
+}
@@ -0,0 +1,13 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn toggle_bookmark(model: &mut RootModel, _event: &Event) -> EventResult {
+    if model.toggle_bookmark() {
+        EventResult::Consumed(None)
+    } else {
+        EventResult::Ignored
+    }
+}
@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn toggle_hex_view(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.toggle_hex_view();
+    EventResult::Consumed(None)
+}
@@ -0,0 +1,10 @@
+use cursive::event::{Event, EventResult};
+use logv_macro::define_action;
+
+use crate::model::model::RootModel;
+
+#[define_action]
+fn toggle_time_deltas(model: &mut RootModel, _event: &Event) -> EventResult {
+    model.toggle_time_deltas();
+    EventResult::Consumed(None)
+}
@@ -1,18 +1,28 @@
+use crate::config::{config_dir, HighlightRule};
 use crate::highlight::style_with_priority::{StylePriority, StyleWithPriority};
 use crate::ui::palette_utils::PaletteAdditions;
+use anyhow::{anyhow, bail, Context};
 use cursive::reexports::enumset::EnumSet;
 use cursive::theme;
 use cursive::theme::PaletteColor::{Background, Highlight, Primary, Tertiary};
-use cursive::theme::{ColorStyle, ColorType, Effect, Palette, Style};
+use cursive::theme::{Color, ColorStyle, ColorType, Effect, Palette, PaletteColor, Style};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::ops::Index;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const THEMES_DIR_NAME: &str = "themes";
+const THEME_FILE_EXTENSION: &str = "toml";
 
 pub struct AppTheme {
     pub name: AppThemeName,
     styles: HashMap<AppThemeKey, StyleWithPriority>,
+    palette: Palette,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum AppThemeKey {
     Regular,
     Cursor,
@@ -22,6 +32,74 @@ pub enum AppThemeKey {
     SearchCurrentOccurrence,
     SearchAnotherOccurrence,
     Filter,
+    MinimapViewport,
+    MinimapSearch,
+    MinimapFilter,
+    MinimapBookmark,
+    LogfmtKey,
+    LogfmtValue,
+    SyntaxKey,
+    SyntaxString,
+    SyntaxLiteral,
+    SyntaxTag,
+}
+
+impl AppThemeKey {
+    /// Keys in the order they are listed in theme files
+    pub const ALL: [AppThemeKey; 18] = [
+        AppThemeKey::Regular,
+        AppThemeKey::Cursor,
+        AppThemeKey::Selection,
+        AppThemeKey::LineNumber,
+        AppThemeKey::Date,
+        AppThemeKey::SearchCurrentOccurrence,
+        AppThemeKey::SearchAnotherOccurrence,
+        AppThemeKey::Filter,
+        AppThemeKey::MinimapViewport,
+        AppThemeKey::MinimapSearch,
+        AppThemeKey::MinimapFilter,
+        AppThemeKey::MinimapBookmark,
+        AppThemeKey::LogfmtKey,
+        AppThemeKey::LogfmtValue,
+        AppThemeKey::SyntaxKey,
+        AppThemeKey::SyntaxString,
+        AppThemeKey::SyntaxLiteral,
+        AppThemeKey::SyntaxTag,
+    ];
+
+    /// Name of the key in `[styles]` table of theme files
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppThemeKey::Regular => "regular",
+            AppThemeKey::Cursor => "cursor",
+            AppThemeKey::Selection => "selection",
+            AppThemeKey::LineNumber => "line_number",
+            AppThemeKey::Date => "date",
+            AppThemeKey::SearchCurrentOccurrence => "search_current_occurrence",
+            AppThemeKey::SearchAnotherOccurrence => "search_another_occurrence",
+            AppThemeKey::Filter => "filter",
+            AppThemeKey::MinimapViewport => "minimap_viewport",
+            AppThemeKey::MinimapSearch => "minimap_search",
+            AppThemeKey::MinimapFilter => "minimap_filter",
+            AppThemeKey::MinimapBookmark => "minimap_bookmark",
+            AppThemeKey::LogfmtKey => "logfmt_key",
+            AppThemeKey::LogfmtValue => "logfmt_value",
+            AppThemeKey::SyntaxKey => "syntax_key",
+            AppThemeKey::SyntaxString => "syntax_string",
+            AppThemeKey::SyntaxLiteral => "syntax_literal",
+            AppThemeKey::SyntaxTag => "syntax_tag",
+        }
+    }
+}
+
+impl FromStr for AppThemeKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AppThemeKey::ALL.into_iter()
+            .find(|key| key.name() == s)
+            .ok_or_else(|| format!("Unknown style {}", s))
+    }
 }
 
 impl Index<AppThemeKey> for AppTheme {
@@ -32,33 +110,127 @@ impl Index<AppThemeKey> for AppTheme {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Built-in theme or the one loaded from `<name>.toml` in `themes` subdirectory of [config_dir]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum AppThemeName {
     SolarizedLight,
     SolarizedDark,
+    Custom(String),
+}
+
+impl AppThemeName {
+    /// Built-in themes followed by the ones found in themes directory
+    pub fn available() -> Vec<AppThemeName> {
+        let mut custom: Vec<AppThemeName> = themes_dir()
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .map(|entries| entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == THEME_FILE_EXTENSION))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+                .map(AppThemeName::Custom)
+                .collect())
+            .unwrap_or_default();
+        custom.sort_by(|a, b| a.to_string().cmp(&b.to_string()));
+        let mut result = vec![AppThemeName::SolarizedLight, AppThemeName::SolarizedDark];
+        result.append(&mut custom);
+        result
+    }
+}
+
+impl Display for AppThemeName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppThemeName::SolarizedLight => write!(f, "solarized-light"),
+            AppThemeName::SolarizedDark => write!(f, "solarized-dark"),
+            AppThemeName::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl FromStr for AppThemeName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err(String::from("Theme name is empty")),
+            "solarized-light" => Ok(AppThemeName::SolarizedLight),
+            "solarized-dark" => Ok(AppThemeName::SolarizedDark),
+            name => Ok(AppThemeName::Custom(name.to_string())),
+        }
+    }
 }
 
 impl AppTheme {
-    pub fn load(name: AppThemeName) -> Result<(Self, theme::Theme), theme::Error> {
-        let asset = match name {
-            AppThemeName::SolarizedLight => include_str!("../assets/solarized-light.toml"),
-            AppThemeName::SolarizedDark => include_str!("../assets/solarized-dark.toml"),
+    /// Loads palette and styles of the theme. Theme files are cursive themes with optional
+    /// `[styles]` table that overrides styles by [AppThemeKey::name]:
+    /// ```toml
+    /// [styles]
+    /// line_number = { fg = "secondary", effects = ["italic"] }
+    /// date = { fg = "#268bd2", bg = "inherit" }
+    /// ```
+    /// Colors are palette colors (`primary`, `blue`, ...), `inherit` or anything
+    /// [Color::parse] accepts. Omitted attributes keep their default values.
+    pub fn load(name: &AppThemeName) -> anyhow::Result<(Self, theme::Theme)> {
+        let content = match name {
+            AppThemeName::SolarizedLight => Cow::Borrowed(include_str!("../assets/solarized-light.toml")),
+            AppThemeName::SolarizedDark => Cow::Borrowed(include_str!("../assets/solarized-dark.toml")),
+            AppThemeName::Custom(name) => {
+                let path = theme_path(name).ok_or(anyhow!("Config directory is unknown"))?;
+                let content = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read theme {:?}", path))?;
+                Cow::Owned(content)
+            }
         };
-        let theme = theme::load_toml(asset)?;
-        Ok((Self::new(name, &theme.palette), theme))
+        let theme = theme::load_toml(&content)
+            .map_err(|err| anyhow!("Failed to parse theme {}: {:?}", name, err))?;
+        let mut app_theme = Self::new(name.clone(), &theme.palette);
+        app_theme.apply_styles(&content, &theme.palette)
+            .with_context(|| format!("Failed to parse styles of theme {}", name))?;
+        Ok((app_theme, theme))
     }
 
     fn new(name: AppThemeName, palette: &Palette) -> Self {
         match name {
             AppThemeName::SolarizedLight => Self::light(palette),
             AppThemeName::SolarizedDark => Self::dark(palette),
+            AppThemeName::Custom(_) => Self {
+                name,
+                styles: Self::common(palette),
+                palette: palette.clone(),
+            },
         }
     }
 
+    /// Style of matches of highlight rule. Unlike theme styles, attributes omitted by the rule
+    /// are inherited.
+    pub fn rule_style(&self, rule: &HighlightRule) -> anyhow::Result<StyleWithPriority> {
+        let effects: Vec<&str> = rule.effects.iter().map(String::as_str).collect();
+        let style = apply_attributes(Style::from(ColorStyle::inherit_parent()), rule.fg.as_deref(),
+                                     rule.bg.as_deref(), Some(&effects[..]), &self.palette)?;
+        Ok(StyleWithPriority::new(style, StylePriority::Rule))
+    }
+
+    fn apply_styles(&mut self, content: &str, palette: &Palette) -> anyhow::Result<()> {
+        let value: toml::Value = content.parse()?;
+        let Some(table) = value.get("styles").and_then(|styles| styles.as_table()) else {
+            return Ok(());
+        };
+        for (key, spec) in table {
+            let key = AppThemeKey::from_str(key).map_err(|err| anyhow!(err))?;
+            let style = self.styles[&key];
+            let overridden = parse_style(spec, style.get_style(), palette)
+                .with_context(|| format!("Invalid style {}", key.name()))?;
+            self.styles.insert(key, style.with_style(overridden));
+        }
+        Ok(())
+    }
+
     fn light(palette: &Palette) -> Self {
         Self {
             name: AppThemeName::SolarizedLight,
             styles: Self::common(palette),
+            palette: palette.clone(),
         }
     }
 
@@ -66,6 +238,7 @@ impl AppTheme {
         Self {
             name: AppThemeName::SolarizedDark,
             styles: Self::common(palette),
+            palette: palette.clone(),
         }
     }
 
@@ -130,6 +303,239 @@ impl AppTheme {
                 StylePriority::Filter,
             ),
         );
+        styles.insert(
+            AppThemeKey::MinimapViewport,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette[Tertiary], palette[Highlight])),
+                StylePriority::Selection,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::MinimapSearch,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.yellow(), ColorType::InheritParent)),
+                StylePriority::Search,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::MinimapFilter,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.green(), ColorType::InheritParent)),
+                StylePriority::Filter,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::MinimapBookmark,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.magenta(), ColorType::InheritParent)),
+                StylePriority::Bookmark,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::LogfmtKey,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.violet(), ColorType::InheritParent)),
+                StylePriority::Field,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::LogfmtValue,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.cyan(), ColorType::InheritParent)),
+                StylePriority::Field,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::SyntaxKey,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.violet(), ColorType::InheritParent)),
+                StylePriority::Field,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::SyntaxString,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.cyan(), ColorType::InheritParent)),
+                StylePriority::Field,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::SyntaxLiteral,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.orange(), ColorType::InheritParent)),
+                StylePriority::Field,
+            ),
+        );
+        styles.insert(
+            AppThemeKey::SyntaxTag,
+            StyleWithPriority::new(
+                Style::from(ColorStyle::new(palette.blue(), ColorType::InheritParent)),
+                StylePriority::Field,
+            ),
+        );
         styles
     }
 }
+
+/// `style` with attributes set by `spec`, e.g. `{ fg = "blue", bg = "inherit", effects = ["bold"] }`
+fn parse_style(spec: &toml::Value, style: Style, palette: &Palette) -> anyhow::Result<Style> {
+    let Some(spec) = spec.as_table() else {
+        bail!("Style must be a table");
+    };
+    let string = |key: &str| spec.get(key)
+        .map(|value| value.as_str().ok_or(anyhow!("{} must be a string", key)))
+        .transpose();
+    let effects = spec.get("effects")
+        .map(|effects| effects.as_array()
+            .ok_or(anyhow!("Effects must be an array"))?
+            .iter()
+            .map(|effect| effect.as_str().ok_or(anyhow!("Effect must be a string")))
+            .collect::<anyhow::Result<Vec<&str>>>())
+        .transpose()?;
+    apply_attributes(style, string("fg")?, string("bg")?, effects.as_deref(), palette)
+}
+
+/// `style` with the given attributes replaced
+fn apply_attributes(
+    style: Style,
+    fg: Option<&str>,
+    bg: Option<&str>,
+    effects: Option<&[&str]>,
+    palette: &Palette,
+) -> anyhow::Result<Style> {
+    let mut result = style;
+    if let Some(fg) = fg {
+        result.color.front = parse_color(fg, palette)?;
+    }
+    if let Some(bg) = bg {
+        result.color.back = parse_color(bg, palette)?;
+    }
+    if let Some(effects) = effects {
+        result.effects = EnumSet::new();
+        for effect in effects {
+            result.effects.insert(parse_effect(effect)?);
+        }
+    }
+    Ok(result)
+}
+
+fn parse_color(s: &str, palette: &Palette) -> anyhow::Result<ColorType> {
+    if s == "inherit" {
+        return Ok(ColorType::InheritParent);
+    }
+    if let Ok(palette_color) = PaletteColor::from_str(s) {
+        return Ok(palette[palette_color].into());
+    }
+    palette.custom(s).copied()
+        .or_else(|| Color::parse(s))
+        .map(ColorType::from)
+        .ok_or(anyhow!("Unknown color {}", s))
+}
+
+fn parse_effect(s: &str) -> anyhow::Result<Effect> {
+    match s {
+        "simple" => Ok(Effect::Simple),
+        "reverse" => Ok(Effect::Reverse),
+        "dim" => Ok(Effect::Dim),
+        "bold" => Ok(Effect::Bold),
+        "italic" => Ok(Effect::Italic),
+        "strikethrough" => Ok(Effect::Strikethrough),
+        "underline" => Ok(Effect::Underline),
+        "blink" => Ok(Effect::Blink),
+        _ => bail!("Unknown effect {}", s),
+    }
+}
+
+fn themes_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(THEMES_DIR_NAME))
+}
+
+fn theme_path(name: &str) -> Option<PathBuf> {
+    themes_dir().map(|dir| dir.join(format!("{name}.{THEME_FILE_EXTENSION}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use cursive::theme::{BaseColor, Color, ColorType, Effect, Palette};
+    use spectral::prelude::*;
+
+    use crate::app_theme::app_theme::{AppTheme, AppThemeKey, AppThemeName};
+    use crate::config::HighlightRule;
+
+    #[test]
+    fn test_all_keys_are_styled() {
+        let (app_theme, _) = AppTheme::load(&AppThemeName::SolarizedDark).unwrap();
+        for key in AppThemeKey::ALL {
+            assert_that!(app_theme.styles.contains_key(&key)).is_true();
+            assert_that!(AppThemeKey::from_str(key.name())).is_equal_to(Ok(key));
+        }
+    }
+
+    /// Key that follows `key` in [AppThemeKey::ALL]. The match has no wildcard, so a new key does
+    /// not compile until it is chained here.
+    fn next_key(key: AppThemeKey) -> Option<AppThemeKey> {
+        match key {
+            AppThemeKey::Regular => Some(AppThemeKey::Cursor),
+            AppThemeKey::Cursor => Some(AppThemeKey::Selection),
+            AppThemeKey::Selection => Some(AppThemeKey::LineNumber),
+            AppThemeKey::LineNumber => Some(AppThemeKey::Date),
+            AppThemeKey::Date => Some(AppThemeKey::SearchCurrentOccurrence),
+            AppThemeKey::SearchCurrentOccurrence => Some(AppThemeKey::SearchAnotherOccurrence),
+            AppThemeKey::SearchAnotherOccurrence => Some(AppThemeKey::Filter),
+            AppThemeKey::Filter => Some(AppThemeKey::MinimapViewport),
+            AppThemeKey::MinimapViewport => Some(AppThemeKey::MinimapSearch),
+            AppThemeKey::MinimapSearch => Some(AppThemeKey::MinimapFilter),
+            AppThemeKey::MinimapFilter => Some(AppThemeKey::MinimapBookmark),
+            AppThemeKey::MinimapBookmark => Some(AppThemeKey::LogfmtKey),
+            AppThemeKey::LogfmtKey => Some(AppThemeKey::LogfmtValue),
+            AppThemeKey::LogfmtValue => Some(AppThemeKey::SyntaxKey),
+            AppThemeKey::SyntaxKey => Some(AppThemeKey::SyntaxString),
+            AppThemeKey::SyntaxString => Some(AppThemeKey::SyntaxLiteral),
+            AppThemeKey::SyntaxLiteral => Some(AppThemeKey::SyntaxTag),
+            AppThemeKey::SyntaxTag => None,
+        }
+    }
+
+    #[test]
+    fn test_all_keys_are_listed() {
+        let chained: Vec<AppThemeKey> = std::iter::successors(Some(AppThemeKey::Regular), |key| next_key(*key))
+            .collect();
+        assert_that!(chained).is_equal_to(AppThemeKey::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_styles_override() {
+        let mut app_theme = AppTheme::new(AppThemeName::Custom(String::from("test")), &Palette::default());
+        let content = "[styles]\n\
+            line_number = { fg = \"red\", effects = [\"bold\", \"underline\"] }\n\
+            date = { bg = \"inherit\" }\n";
+        app_theme.apply_styles(content, &Palette::default()).unwrap();
+
+        let line_number = app_theme[AppThemeKey::LineNumber].get_style();
+        assert_that!(line_number.color.front).is_equal_to(ColorType::from(Color::Dark(BaseColor::Red)));
+        assert_that!(line_number.effects.contains(Effect::Bold)).is_true();
+        assert_that!(line_number.effects.contains(Effect::Italic)).is_false();
+        assert_that!(app_theme[AppThemeKey::Date].get_style().color.back).is_equal_to(ColorType::InheritParent);
+
+        assert_that!(app_theme.apply_styles("[styles]\nunknown = { fg = \"red\" }", &Palette::default()).is_err()).is_true();
+        assert_that!(app_theme.apply_styles("[styles]\ndate = { fg = \"no such color\" }", &Palette::default()).is_err()).is_true();
+    }
+
+    #[test]
+    fn test_rule_style() {
+        let app_theme = AppTheme::new(AppThemeName::Custom(String::from("test")), &Palette::default());
+        let rule = |fg: &str| HighlightRule {
+            pattern: String::from("ERROR"),
+            fg: Some(fg.to_string()),
+            bg: None,
+            effects: vec![String::from("bold")],
+        };
+        let style = app_theme.rule_style(&rule("red")).unwrap().get_style();
+        assert_that!(style.color.front).is_equal_to(ColorType::from(Color::Dark(BaseColor::Red)));
+        assert_that!(style.color.back).is_equal_to(ColorType::InheritParent);
+        assert_that!(style.effects.contains(Effect::Bold)).is_true();
+        assert_that!(app_theme.rule_style(&rule("no such color")).is_err()).is_true();
+    }
+}
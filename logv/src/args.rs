@@ -1,11 +1,17 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
 use log::LevelFilter;
+use crate::app_theme::app_theme::AppThemeName;
+use crate::data_source::delimiter::Delimiter;
+use crate::data_source::encoding::Encoding;
+use crate::model::time_zone::AssumedZone;
 
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 pub struct Args {
-    #[arg(short, long)]
+    #[arg(short, long, help = "File to open. A directory or a glob like 'app.log*' opens the matching files one after another, rotated ones first. Compressed rotations (gzip, bzip2, xz, zstd) are not supported and are skipped.")]
     pub file: Option<String>,
 
     #[arg(short='L', long="loglevel", help="One of \"OFF\", \"ERROR\", \"WARN\", \"INFO\", \"DEBUG\", \"TRACE\". Case-insensitive.")]
@@ -13,4 +19,59 @@ pub struct Args {
 
     #[arg(short, long, help = "Port for puffin profiler to connect to. Puffin viewer expects 8585 by default. Profiler is disabled, if not specified.")]
     pub profiler_port: Option<u16>,
+
+    #[arg(long, help = "Map regular files into memory instead of reading them. Other files (e.g. pipes) are read as usual.")]
+    pub mmap: bool,
+
+    #[arg(short, long, help = "Encoding of the file: utf-8, latin1, utf-16le or utf-16be. Detected automatically, if not specified.")]
+    pub encoding: Option<Encoding>,
+
+    #[arg(short, long, help = "Record delimiter: lf, nul, a single ASCII char, a byte like 0x1e or /regex/. Line breaks are used, unless the file looks NUL-separated.")]
+    pub delimiter: Option<Delimiter>,
+
+    #[arg(short, long, help = "Time zone of dates that have no zone in the file: local, UTC, an offset like +02:00 or an abbreviation like CET. Local, if not specified.")]
+    pub timezone: Option<AssumedZone>,
+
+    #[arg(long, default_value_t = 60, help = "Gap between dates of adjacent lines, in seconds, that is long enough to stop at when going to the next time gap.")]
+    pub gap_threshold: u32,
+
+    #[arg(long, help = "Color theme: solarized-light, solarized-dark or name of a .toml file in themes subdirectory of config directory. Solarized light, if not specified.")]
+    pub theme: Option<AppThemeName>,
+
+    #[arg(long, help = "Key profile applied on top of the default one, e.g. macos. The one of current OS, if not specified.")]
+    pub profile: Option<String>,
+
+    #[arg(long, help = "Configuration file to use instead of config.yaml in config directory.")]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands that print results of the file given by `--file` to stdout instead of showing the UI
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Prints lines that contain the pattern
+    Grep {
+        pattern: String,
+
+        #[arg(short, long, help = "Treat the pattern as a regular expression.")]
+        regex: bool,
+
+        #[arg(short = 'C', long, default_value_t = 0, help = "Number of lines printed around each matching line.")]
+        context: u8,
+
+        #[arg(short, long, help = "Print number of matching lines instead of the lines. Context lines are not counted.")]
+        count: bool,
+    },
+    /// Prints lines with dates in the given range, along with lines without dates that follow them
+    Slice {
+        #[arg(long, allow_hyphen_values = true, help = "First date to print, as in go-to-date dialog. Relative dates like -1h are counted from the last date of the file. From the beginning, if not specified.")]
+        from: Option<String>,
+
+        #[arg(long, allow_hyphen_values = true, help = "Last date to print, inclusive. Up to the end, if not specified.")]
+        to: Option<String>,
+    },
+    /// Prints size, number of lines, date format and the time span of the file
+    Stats,
 }
\ No newline at end of file
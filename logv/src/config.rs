@@ -0,0 +1,308 @@
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Context;
+use log::LevelFilter;
+use yaml_rust2::{Yaml, YamlLoader};
+
+use crate::app_theme::app_theme::AppThemeName;
+use crate::args::Args;
+
+const CONFIG_FILE_NAME: &str = "config.yaml";
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+const TAB_WIDTHS: RangeInclusive<i64> = 1..=16;
+
+/// User settings read from `config.yaml` in [config_dir] or from the file given by `--config`.
+/// Command line arguments take precedence over them.
+///
+/// Sample:
+/// ```yaml
+/// theme: solarized-dark
+/// profile: macos
+/// log_level: debug
+/// soft_wrap: true
+/// line_numbers: true
+/// tab_width: 8
+/// highlights:
+///   - pattern: "ERROR|FATAL"
+///     fg: red
+///     effects: [bold]
+/// filter:
+///   field: false
+///   neighbourhood: 2
+/// date_formats:
+///   - "%d/%m/%Y %H:%M:%S"
+///   - "[%Y-%m-%d %T %z]"
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AppConfig {
+    /// File the settings are loaded from, if any
+    pub path: Option<PathBuf>,
+    pub theme: Option<AppThemeName>,
+    /// Key profile applied on top of the default one, e.g. `macos`
+    pub profile: Option<String>,
+    pub log_level: Option<LevelFilter>,
+    pub soft_wrap: bool,
+    pub show_line_numbers: bool,
+    /// Number of spaces a tab is shown as
+    pub tab_width: usize,
+    pub highlights: Vec<HighlightRule>,
+    pub filter: FilterDefaults,
+    /// Chrono formats of dates in addition to the built-in ones
+    pub date_formats: Vec<String>,
+    /// Settings that are out of range, the nearest valid values are used instead
+    pub errors: Vec<String>,
+}
+
+/// Regular expression whose matches are styled with `fg`, `bg` and `effects`. Colors and effects
+/// are named the same way as in theme files.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HighlightRule {
+    pub pattern: String,
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub effects: Vec<String>,
+}
+
+/// Initial state of filter dialog
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct FilterDefaults {
+    pub field: bool,
+    pub neighbourhood: u8,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            path: None,
+            theme: None,
+            profile: None,
+            log_level: None,
+            soft_wrap: false,
+            show_line_numbers: true,
+            tab_width: DEFAULT_TAB_WIDTH,
+            highlights: vec![],
+            filter: FilterDefaults::default(),
+            date_formats: vec![],
+            errors: vec![],
+        }
+    }
+}
+
+impl AppConfig {
+    /// Loads settings from the default location. No file means default settings.
+    pub fn load() -> anyhow::Result<AppConfig> {
+        match config_dir().map(|dir| dir.join(CONFIG_FILE_NAME)) {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(AppConfig::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> anyhow::Result<AppConfig> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let config = Self::parse(&content)
+            .with_context(|| format!("Failed to parse {:?}", path))?;
+        Ok(AppConfig {
+            path: Some(path.to_path_buf()),
+            ..config
+        })
+    }
+
+    /// Settings overridden by command line arguments
+    pub fn merge(self, args: &Args) -> AppConfig {
+        AppConfig {
+            theme: args.theme.clone().or(self.theme),
+            profile: args.profile.clone().or(self.profile),
+            log_level: args.log_level.or(self.log_level),
+            ..self
+        }
+    }
+
+    /// Human-readable settings, as they are in effect
+    pub fn describe(&self) -> Vec<(String, String)> {
+        let or_default = |value: Option<String>| value.unwrap_or_else(|| String::from("(default)"));
+        let on_off = |value: bool| String::from(if value { "on" } else { "off" });
+        let mut result = vec![
+            (String::from("Config file"), or_default(self.path.as_ref().map(|path| path.display().to_string()))),
+            (String::from("Theme"), or_default(self.theme.as_ref().map(|theme| theme.to_string()))),
+            (String::from("Key profile"), or_default(self.profile.clone())),
+            (String::from("Log level"), or_default(self.log_level.map(|level| level.to_string()))),
+            (String::from("Soft wrap"), on_off(self.soft_wrap)),
+            (String::from("Line numbers"), on_off(self.show_line_numbers)),
+            (String::from("Tab width"), self.tab_width.to_string()),
+            (String::from("Filter by logfmt field"), on_off(self.filter.field)),
+            (String::from("Filter neighbourhood"), self.filter.neighbourhood.to_string()),
+        ];
+        for rule in &self.highlights {
+            result.push((String::from("Highlight"), rule.to_string()));
+        }
+        for date_format in &self.date_formats {
+            result.push((String::from("Date format"), date_format.clone()));
+        }
+        result
+    }
+
+    fn parse(content: &str) -> anyhow::Result<AppConfig> {
+        let docs = YamlLoader::load_from_str(content)?;
+        Ok(docs.first().map(AppConfig::from).unwrap_or_default())
+    }
+}
+
+impl From<&Yaml> for AppConfig {
+    fn from(value: &Yaml) -> Self {
+        let strings = |value: &Yaml| value
+            .as_vec()
+            .map(|arr| arr.iter()
+                .filter_map(|item| item.as_str())
+                .map(String::from)
+                .collect())
+            .unwrap_or_default();
+        let defaults = AppConfig::default();
+        let mut errors = vec![];
+        let tab_width = value["tab_width"].as_i64()
+            .map(|width| {
+                let clamped = width.clamp(*TAB_WIDTHS.start(), *TAB_WIDTHS.end());
+                if clamped != width {
+                    errors.push(format!("Tab width {} is out of range {:?}, {} is used", width, TAB_WIDTHS, clamped));
+                }
+                clamped as usize
+            })
+            .unwrap_or(defaults.tab_width);
+        AppConfig {
+            path: None,
+            theme: value["theme"].as_str().and_then(|theme| AppThemeName::from_str(theme).ok()),
+            profile: value["profile"].as_str().map(String::from),
+            log_level: value["log_level"].as_str().and_then(|level| LevelFilter::from_str(level).ok()),
+            soft_wrap: value["soft_wrap"].as_bool().unwrap_or(defaults.soft_wrap),
+            show_line_numbers: value["line_numbers"].as_bool().unwrap_or(defaults.show_line_numbers),
+            tab_width,
+            highlights: value["highlights"]
+                .as_vec()
+                .map(|arr| arr.iter()
+                    .filter_map(|rule| HighlightRule::try_from(rule)
+                        .map_err(|err| log::warn!("{}", err))
+                        .ok())
+                    .collect())
+                .unwrap_or_default(),
+            filter: FilterDefaults {
+                field: value["filter"]["field"].as_bool().unwrap_or_default(),
+                neighbourhood: value["filter"]["neighbourhood"].as_i64()
+                    .and_then(|n| u8::try_from(n).ok())
+                    .unwrap_or_default(),
+            },
+            date_formats: strings(&value["date_formats"]),
+            errors,
+        }
+    }
+}
+
+impl TryFrom<&Yaml> for HighlightRule {
+    type Error = String;
+
+    fn try_from(value: &Yaml) -> Result<Self, Self::Error> {
+        let pattern = value["pattern"].as_str()
+            .ok_or_else(|| format!("Highlight rule has no pattern: {:?}", value))?;
+        let effects = value["effects"]
+            .as_vec()
+            .map(|arr| arr.iter()
+                .filter_map(|effect| effect.as_str())
+                .map(String::from)
+                .collect())
+            .unwrap_or_default();
+        Ok(HighlightRule {
+            pattern: pattern.to_string(),
+            fg: value["fg"].as_str().map(String::from),
+            bg: value["bg"].as_str().map(String::from),
+            effects,
+        })
+    }
+}
+
+impl std::fmt::Display for HighlightRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "/{}/", self.pattern)?;
+        if let Some(fg) = &self.fg {
+            write!(f, " fg={}", fg)?;
+        }
+        if let Some(bg) = &self.bg {
+            write!(f, " bg={}", bg)?;
+        }
+        if !self.effects.is_empty() {
+            write!(f, " {}", self.effects.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Directory of user settings: `$XDG_CONFIG_HOME/logv`, `~/.config/logv` or `%APPDATA%\logv`
+pub fn config_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let base = non_empty("XDG_CONFIG_HOME")
+        .or_else(|| non_empty("HOME").map(|home| home.join(".config")))
+        .or_else(|| non_empty("APPDATA"))?;
+    Some(base.join("logv"))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use log::LevelFilter;
+    use spectral::prelude::*;
+
+    use crate::app_theme::app_theme::AppThemeName;
+    use crate::args::Args;
+    use crate::config::{AppConfig, FilterDefaults, HighlightRule};
+
+    #[test]
+    fn test_parse() {
+        let config = AppConfig::parse("date_formats:\n  - \"%d/%m/%Y %H:%M:%S\"\n  - \"[%d/%m %T]\"\n").unwrap();
+        assert_that!(config.date_formats).is_equal_to(vec![String::from("%d/%m/%Y %H:%M:%S"), String::from("[%d/%m %T]")]);
+        assert_that!(AppConfig::parse("").unwrap()).is_equal_to(AppConfig::default());
+        assert_that!(AppConfig::parse("date_formats: [").is_err()).is_true();
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let config = AppConfig::parse("theme: solarized-dark\n\
+            profile: macos\n\
+            log_level: debug\n\
+            soft_wrap: true\n\
+            line_numbers: false\n\
+            tab_width: 8\n\
+            highlights:\n  - pattern: ERROR\n    fg: red\n    effects: [bold]\n  - fg: blue\n\
+            filter:\n  field: true\n  neighbourhood: 3\n").unwrap();
+        assert_that!(config.theme).is_equal_to(Some(AppThemeName::SolarizedDark));
+        assert_that!(config.profile).is_equal_to(Some(String::from("macos")));
+        assert_that!(config.log_level).is_equal_to(Some(LevelFilter::Debug));
+        assert_that!(config.soft_wrap).is_true();
+        assert_that!(config.show_line_numbers).is_false();
+        assert_that!(config.tab_width).is_equal_to(8);
+        assert_that!(config.highlights).is_equal_to(vec![HighlightRule {
+            pattern: String::from("ERROR"),
+            fg: Some(String::from("red")),
+            bg: None,
+            effects: vec![String::from("bold")],
+        }]);
+        assert_that!(config.filter).is_equal_to(FilterDefaults { field: true, neighbourhood: 3 });
+    }
+
+    #[test]
+    fn test_tab_width_is_clamped() {
+        for (yaml, expected) in [("tab_width: 0", 1), ("tab_width: -2", 1), ("tab_width: 100", 16)] {
+            let config = AppConfig::parse(yaml).unwrap();
+            assert_that!(config.tab_width).is_equal_to(expected);
+            assert_that!(config.errors).has_length(1);
+        }
+        assert_that!(AppConfig::parse("tab_width: 16").unwrap().errors).is_empty();
+    }
+
+    #[test]
+    fn test_merge() {
+        let config = AppConfig::parse("theme: solarized-dark\nprofile: macos\n").unwrap();
+        let merged = config.clone().merge(&Args::parse_from(["logv", "--theme", "mine"]));
+        assert_that!(merged.theme).is_equal_to(Some(AppThemeName::Custom(String::from("mine"))));
+        assert_that!(merged.profile).is_equal_to(config.profile);
+    }
+}
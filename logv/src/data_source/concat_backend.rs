@@ -0,0 +1,222 @@
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::data_source::reader_factory::concat::ConcatReaderFactory;
+use crate::data_source::reader_factory::ReaderFactory;
+use crate::data_source::LineSourceBackend;
+
+/// File that is a part of concatenated content
+#[derive(Debug)]
+pub struct Part {
+    pub path: PathBuf,
+    /// Offset of the file content in concatenated content
+    pub start: u64,
+    length: u64,
+    // a line break is added after files that do not end with one, so that their last line is
+    // not glued to the first line of the next file
+    adds_line_break: bool,
+}
+
+impl Part {
+    /// Offset right after the part, including the added line break
+    pub fn end(&self) -> u64 {
+        self.start + self.length + u64::from(self.adds_line_break)
+    }
+}
+
+/// Several files shown one after another as if they were a single one. Lengths of the files are
+/// fixed when the backend is created. Cloning is cheap.
+#[derive(Clone, Debug)]
+pub struct ConcatBackend {
+    parts: Arc<Vec<Part>>,
+}
+
+impl ConcatBackend {
+    /// Empty files are skipped
+    pub fn new(paths: &[PathBuf]) -> std::io::Result<Self> {
+        let mut parts = vec![];
+        let mut start = 0;
+        for path in paths {
+            let length = std::fs::metadata(path)?.len();
+            if length == 0 {
+                continue;
+            }
+            let adds_line_break = !ends_with_line_break(path, length)?;
+            let part = Part { path: path.clone(), start, length, adds_line_break };
+            start = part.end();
+            parts.push(part);
+        }
+        Ok(ConcatBackend { parts: Arc::new(parts) })
+    }
+
+    pub fn parts(&self) -> &[Part] {
+        &self.parts[..]
+    }
+
+    /// Index of the part that contains `offset`. Offsets past the end belong to the last part.
+    pub fn part_index(&self, offset: u64) -> Option<usize> {
+        part_index(&self.parts, offset)
+    }
+}
+
+fn ends_with_line_break(path: &Path, length: u64) -> std::io::Result<bool> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(length - 1))?;
+    let mut last = [0u8];
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+fn part_index(parts: &[Part], offset: u64) -> Option<usize> {
+    parts.partition_point(|part| part.start <= offset).checked_sub(1)
+}
+
+impl LineSourceBackend<ConcatReader> for ConcatBackend {
+    fn get_length(&self) -> u64 {
+        self.parts.last().map(Part::end).unwrap_or_default()
+    }
+
+    fn new_reader(&self) -> BufReader<ConcatReader> {
+        BufReader::new(ConcatReader::new(self.parts.clone()))
+    }
+
+    fn reader_factory(&self) -> Box<dyn ReaderFactory> {
+        Box::new(ConcatReaderFactory::new(self.parts.clone()))
+    }
+}
+
+/// Reads parts one after another. Files are opened on demand.
+pub struct ConcatReader {
+    parts: Arc<Vec<Part>>,
+    position: u64,
+    // index of the open part, the file and position in it
+    current: Option<(usize, File, u64)>,
+}
+
+impl ConcatReader {
+    pub fn new(parts: Arc<Vec<Part>>) -> Self {
+        ConcatReader {
+            parts,
+            position: 0,
+            current: None,
+        }
+    }
+
+    fn length(&self) -> u64 {
+        self.parts.last().map(Part::end).unwrap_or_default()
+    }
+}
+
+impl Read for ConcatReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.position >= self.length() {
+            return Ok(0);
+        }
+        let i = part_index(&self.parts, self.position).unwrap();
+        let part = &self.parts[i];
+        let position_in_part = self.position - part.start;
+        if position_in_part >= part.length {
+            // added line break
+            buf[0] = b'\n';
+            self.position += 1;
+            return Ok(1);
+        }
+        let file = match &mut self.current {
+            Some((j, file, file_position)) if *j == i => {
+                if *file_position != position_in_part {
+                    file.seek(SeekFrom::Start(position_in_part))?;
+                }
+                file
+            }
+            current => {
+                let mut file = File::open(&part.path)?;
+                file.seek(SeekFrom::Start(position_in_part))?;
+                &mut current.insert((i, file, position_in_part)).1
+            }
+        };
+        let max = buf.len().min((part.length - position_in_part) as usize);
+        let n = file.read(&mut buf[..max])?;
+        if n == 0 {
+            return Err(std::io::Error::new(ErrorKind::UnexpectedEof, format!("{:?} has been truncated", part.path)));
+        }
+        self.position += n as u64;
+        if let Some((_, _, file_position)) = &mut self.current {
+            *file_position = position_in_part + n as u64;
+        }
+        Ok(n)
+    }
+}
+
+impl Seek for ConcatReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.length().checked_add_signed(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(std::io::Error::new(ErrorKind::InvalidInput, "Seek to a negative offset")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+    use std::path::PathBuf;
+
+    use spectral::prelude::*;
+
+    use crate::data_source::LineSourceBackend;
+    use crate::test_extensions::TempDir;
+    use super::ConcatBackend;
+
+    fn write_files(dir: &TempDir, contents: &[&str]) -> Vec<PathBuf> {
+        contents.iter().enumerate()
+            .map(|(i, content)| dir.file(&format!("part{}.log", i), content))
+            .collect()
+    }
+
+    #[test]
+    fn test_read_concatenated() {
+        let dir = TempDir::new("concat-read");
+        let paths = write_files(&dir, &["a1\na2\n", "", "b1\nb2", "c1\n"]);
+        let backend = ConcatBackend::new(&paths).unwrap();
+        assert_that!(backend.parts().len()).is_equal_to(3);
+        assert_that!(backend.get_length()).is_equal_to(15);
+
+        let mut content = String::new();
+        backend.new_reader().read_to_string(&mut content).unwrap();
+        assert_that!(content.as_str()).is_equal_to("a1\na2\nb1\nb2\nc1\n");
+
+        assert_that!(backend.part_index(0)).is_equal_to(Some(0));
+        assert_that!(backend.part_index(6)).is_equal_to(Some(1));
+        assert_that!(backend.part_index(11)).is_equal_to(Some(1));
+        assert_that!(backend.part_index(12)).is_equal_to(Some(2));
+        assert_that!(backend.part_index(100)).is_equal_to(Some(2));
+    }
+
+    #[test]
+    fn test_seek() {
+        let dir = TempDir::new("concat-seek");
+        let paths = write_files(&dir, &["a1\na2\n", "b1\nb2\n"]);
+        let backend = ConcatBackend::new(&paths).unwrap();
+        let mut reader = backend.new_reader();
+        let mut buf = [0u8; 4];
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_that!(&buf).is_equal_to(b"2\nb1");
+
+        reader.seek(SeekFrom::End(-3)).unwrap();
+        reader.read_exact(&mut buf[..3]).unwrap();
+        assert_that!(buf[..3].to_vec()).is_equal_to(b"b2\n".to_vec());
+        assert_that!(reader.seek(SeekFrom::Current(-20)).is_err()).is_true();
+    }
+}
@@ -2,7 +2,8 @@ mod test_read_delimited {
     extern crate spectral;
 
     use crate::data_source::read_delimited;
-    use crate::data_source::{Data, Line};
+    use crate::data_source::read_lines_from_slice;
+    use crate::data_source::{CustomHighlight, Data, Line, INVALID_BYTES_CUSTOM_DATA_KEY};
     use crate::test_extensions::*;
     use spectral::prelude::*;
     use std::io::{BufReader, Cursor, Seek};
@@ -458,6 +459,55 @@ mod test_read_delimited {
         Line, Word
     }
 
+    #[test]
+    fn read_lines_from_slice_is_consistent_with_read_delimited() {
+        for s in ["AAA\nBBB\nCCC", "AAA\n\nBBB\r\nCCC\n", "\n\nA\n", ""] {
+            for offset in 0..=s.len() as u64 {
+                for n in [-3, -1, 1, 2, 5] {
+                    let (expected, _) = test(s, offset, n, SegmentType::Line);
+                    let actual = read_lines_from_slice(s.as_bytes(), offset.into(), n.into(), None);
+                    let descr = format!("{:?} from {} by {}", s, offset, n);
+                    asserting!(&descr)
+                        .that(&actual.lines)
+                        .is_equal_to(&expected.unwrap().lines);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn read_lines_from_slice_beyond_end() {
+        for n in [-3, -1, 1, 2] {
+            let data = read_lines_from_slice(b"AAA\nBBB", 8.into(), n.into(), None);
+            let descr = format!("by {}", n);
+            asserting!(&descr).that(&data.lines).is_empty();
+            assert_that!(data.start).is_none();
+            assert_that!(data.end).is_none();
+        }
+        // the end itself is still a valid offset
+        let data = read_lines_from_slice(b"AAA\nBBB", 7.into(), (-1).into(), None);
+        assert_that!(data.lines).is_equal_to(vec![Line::new("BBB", 4, 7)]);
+    }
+
+    #[test]
+    fn invalid_bytes_are_marked() {
+        let bytes: &[u8] = b"a\x1a\xff\nb";
+        let expected = vec![CustomHighlight::new(2, 3)];
+        let data = read_lines_from_slice(bytes, 0.into(), 1.into(), None);
+        assert_that!(data.lines[0].custom_highlights.get(INVALID_BYTES_CUSTOM_DATA_KEY)).is_equal_to(Some(&expected));
+        let mut reader = BufReader::new(Cursor::new(bytes));
+        for (offset, n) in [(0, 1), (2, -1)] {
+            let data = read_delimited(&mut reader, offset.into(), n.into(), true, None, |&ch| ch == '\n').unwrap();
+            let descr = format!("from {} by {}", offset, n);
+            asserting!(&descr)
+                .that(&data.lines[0].custom_highlights.get(INVALID_BYTES_CUSTOM_DATA_KEY))
+                .is_equal_to(Some(&expected));
+        }
+        // no highlights in lines without invalid bytes
+        let data = read_lines_from_slice(bytes, 4.into(), 1.into(), None);
+        assert_that!(data.lines).is_equal_to(vec![Line::new("b", 4, 5)]);
+    }
+
     fn test(s: &str, offset: u64, n: i32, segment_type: SegmentType) -> (std::io::Result<Data>, BufReader<Cursor<&str>>) {
         let mut reader = BufReader::new(Cursor::new(s));
         let (allow_empty_segments, delimiter): (bool, fn(&char) -> bool) = match segment_type {
@@ -0,0 +1,219 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::data_source::encoding::Encoding;
+
+/// Number of bytes at the beginning of a file used to guess its delimiter
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// Shown instead of line breaks that are a part of a record, when records are not delimited by
+/// line breaks
+const EMBEDDED_NEW_LINE: &str = "\u{2424}";
+
+/// Separates records of a file
+#[derive(Debug, Clone)]
+pub enum Delimiter {
+    /// `\n`, optionally preceded by `\r`
+    NewLine,
+    Byte(u8),
+    Regex(Regex),
+}
+
+impl Delimiter {
+    /// Records are considered NUL-separated (e.g. output of `find -print0`), if the sample contains
+    /// NUL bytes, but no line breaks
+    pub fn detect(sample: &[u8]) -> Delimiter {
+        if memchr::memchr(0, sample).is_some() && memchr::memchr(b'\n', sample).is_none() {
+            Delimiter::Byte(0)
+        } else {
+            Delimiter::NewLine
+        }
+    }
+
+    /// Guesses delimiter by the beginning of the file. Files other than regular ones are not
+    /// sampled.
+    pub fn detect_file(path: &Path) -> std::io::Result<Delimiter> {
+        let file = File::open(path)?;
+        if !file.metadata()?.is_file() {
+            return Ok(Delimiter::NewLine);
+        }
+        let mut sample = vec![];
+        file.take(SAMPLE_SIZE).read_to_end(&mut sample)?;
+        Ok(Delimiter::detect(&sample))
+    }
+
+    pub fn is_new_line(&self) -> bool {
+        matches!(self, Delimiter::NewLine)
+    }
+
+    /// Converts `bytes` in `encoding` to UTF-8 text with records delimited by line breaks, which
+    /// is the only form line sources work with. Calls `f` with the offset of every char in
+    /// `bytes` and the text it is converted to: a delimiter is converted to a line break, a line
+    /// break inside a record to a visible glyph, the rest of chars of a delimiter to nothing.
+    pub fn convert<F: FnMut(usize, &str)>(&self, encoding: Encoding, bytes: &[u8], mut f: F) {
+        let mut buf = [0u8; 4];
+        match self {
+            Delimiter::NewLine => encoding.for_each_char(bytes, |i, ch| f(i, ch.encode_utf8(&mut buf))),
+            Delimiter::Byte(b) => encoding.for_each_char(bytes, |i, ch| match ch {
+                '\n' => f(i, EMBEDDED_NEW_LINE),
+                ch if ch == char::from(*b) => f(i, "\n"),
+                ch => f(i, ch.encode_utf8(&mut buf)),
+            }),
+            Delimiter::Regex(regex) => {
+                let (text, offsets) = decode_with_offsets(encoding, bytes);
+                let mut matches = regex.find_iter(&text).filter(|m| !m.is_empty()).peekable();
+                for ((position, ch), offset) in text.char_indices().zip(offsets) {
+                    while matches.next_if(|m| m.end() <= position).is_some() {}
+                    match matches.peek() {
+                        Some(m) if m.start() == position => f(offset, "\n"),
+                        Some(m) if m.start() < position => f(offset, ""),
+                        _ if ch == '\n' => f(offset, EMBEDDED_NEW_LINE),
+                        _ => f(offset, ch.encode_utf8(&mut buf)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves the end of the first `len` bytes of `bytes`, so that it does not cut a delimiter
+    /// found in `bytes`. Content is converted by parts that end there, see [Self::convert].
+    pub fn align_to_records(&self, encoding: Encoding, bytes: &[u8], len: usize) -> usize {
+        // single chars cannot be cut
+        let Delimiter::Regex(regex) = self else { return len; };
+        let (text, offsets) = decode_with_offsets(encoding, bytes);
+        // positions of chars in the text along with their offsets in bytes
+        let chars: Vec<(usize, usize)> = text.char_indices().map(|(position, _)| position).zip(offsets).collect();
+        let offset_at = |position: usize| chars.binary_search_by_key(&position, |(p, _)| *p)
+            .map_or(bytes.len(), |i| chars[i].1);
+        let Some(&(cut, _)) = chars.get(chars.partition_point(|(_, offset)| *offset < len)) else {
+            return len;
+        };
+        regex.find_iter(&text)
+            .take_while(|m| m.start() < cut)
+            .find(|m| m.end() > cut)
+            .map_or(len, |m| offset_at(m.end()))
+    }
+}
+
+/// Decodes `bytes` along with offsets of chars in them
+fn decode_with_offsets(encoding: Encoding, bytes: &[u8]) -> (String, Vec<usize>) {
+    let mut text = String::with_capacity(bytes.len());
+    let mut offsets = Vec::with_capacity(bytes.len());
+    encoding.for_each_char(bytes, |i, ch| {
+        text.push(ch);
+        offsets.push(i);
+    });
+    (text, offsets)
+}
+
+impl FromStr for Delimiter {
+    type Err = String;
+
+    /// Accepts `lf`, `nul`, a single ASCII char, a byte in hex (e.g. `0x1e`) or a regex
+    /// surrounded by slashes (e.g. `/\n-{3,}\n/`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lf" | "crlf" | "\\n" => return Ok(Delimiter::NewLine),
+            "nul" | "\\0" => return Ok(Delimiter::Byte(0)),
+            _ => {},
+        }
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            return match u8::from_str_radix(hex, 16) {
+                Ok(b) if b.is_ascii() => Ok(Delimiter::Byte(b)),
+                Ok(_) => Err(format!("Invalid delimiter byte {}: only ASCII is supported", s)),
+                Err(e) => Err(format!("Invalid delimiter byte {}: {}", s, e)),
+            };
+        }
+        if let Some(pattern) = s.strip_prefix('/').and_then(|s| s.strip_suffix('/')).filter(|p| !p.is_empty()) {
+            return Regex::new(pattern)
+                .map(Delimiter::Regex)
+                .map_err(|e| format!("Invalid delimiter regex {}: {}", pattern, e));
+        }
+        match s.as_bytes() {
+            [b] if b.is_ascii() => Ok(Delimiter::Byte(*b)),
+            _ => Err(format!("Unsupported delimiter: {}. Expected lf, nul, a single ASCII char, a byte like 0x1e or /regex/", s)),
+        }
+    }
+}
+
+impl Display for Delimiter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Delimiter::NewLine => write!(f, "line breaks"),
+            Delimiter::Byte(0) => write!(f, "NUL"),
+            Delimiter::Byte(b) if b.is_ascii_graphic() => write!(f, "'{}'", char::from(*b)),
+            Delimiter::Byte(b) => write!(f, "0x{:02x}", b),
+            Delimiter::Regex(regex) => write!(f, "/{}/", regex),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use crate::data_source::encoding::Encoding;
+    use super::Delimiter;
+
+    #[test]
+    fn test_detect() {
+        assert_that!(Delimiter::detect(b"a\0b\0").is_new_line()).is_false();
+        assert_that!(Delimiter::detect(b"a\nb\0").is_new_line()).is_true();
+        assert_that!(Delimiter::detect(b"a\nb\n").is_new_line()).is_true();
+    }
+
+    fn convert(delimiter: &Delimiter, bytes: &[u8]) -> (String, Vec<usize>) {
+        let mut text = String::new();
+        let mut offsets = vec![];
+        delimiter.convert(Encoding::Utf8, bytes, |i, converted| {
+            offsets.extend(std::iter::repeat_n(i, converted.len()));
+            text.push_str(converted);
+        });
+        (text, offsets)
+    }
+
+    #[test]
+    fn test_convert() {
+        let (text, offsets) = convert(&Delimiter::Byte(0), b"first\nrecord\0second\0");
+        assert_that!(text.as_str()).is_equal_to("first\u{2424}record\nsecond\n");
+        assert_that!(offsets[5..9].to_vec()).is_equal_to(vec![5, 5, 5, 6]);
+        // a substitute is a record delimiter, an undecodable byte is not
+        let (text, _) = convert(&Delimiter::Byte(0x1A), b"a\x1Ab\xFFc");
+        assert_that!(text.as_str()).is_equal_to("a\nb\u{FFFD}c");
+
+        let delimiter: Delimiter = "/\\n-{3,}\\n/".parse().unwrap();
+        let (text, offsets) = convert(&delimiter, b"a\nb\n---\nc\n-----\nd");
+        assert_that!(text.as_str()).is_equal_to("a\u{2424}b\nc\nd");
+        assert_that!(offsets[5..].to_vec()).is_equal_to(vec![3, 8, 9, 16]);
+        let (text, _) = convert(&Delimiter::NewLine, b"a\r\nb");
+        assert_that!(text.as_str()).is_equal_to("a\r\nb");
+    }
+
+    #[test]
+    fn test_align_to_records() {
+        let delimiter: Delimiter = "/\\n-{3,}\\n/".parse().unwrap();
+        let bytes = b"a\n---\nb\n---\nc";
+        assert_that!(delimiter.align_to_records(Encoding::Utf8, bytes, 3)).is_equal_to(6);
+        assert_that!(delimiter.align_to_records(Encoding::Utf8, bytes, 6)).is_equal_to(6);
+        assert_that!(delimiter.align_to_records(Encoding::Utf8, bytes, 1)).is_equal_to(1);
+        // the delimiter is cut by the end of bytes
+        assert_that!(delimiter.align_to_records(Encoding::Utf8, &bytes[..11], 9)).is_equal_to(9);
+        assert_that!(Delimiter::Byte(0).align_to_records(Encoding::Utf8, b"a\0b", 1)).is_equal_to(1);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_that!(matches!("nul".parse::<Delimiter>(), Ok(Delimiter::Byte(0)))).is_true();
+        assert_that!(matches!("0x1e".parse::<Delimiter>(), Ok(Delimiter::Byte(0x1e)))).is_true();
+        assert_that!(matches!(";".parse::<Delimiter>(), Ok(Delimiter::Byte(b';')))).is_true();
+        assert_that!(matches!("LF".parse::<Delimiter>(), Ok(Delimiter::NewLine))).is_true();
+        assert_that!(matches!("/\\n\\n/".parse::<Delimiter>(), Ok(Delimiter::Regex(_)))).is_true();
+        assert_that!("/(/".parse::<Delimiter>()).is_err();
+        assert_that!("ab".parse::<Delimiter>()).is_err();
+    }
+}
@@ -0,0 +1,258 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::utils::utf8::{utf_byte_type, UnicodeByteType};
+
+/// Number of bytes at the beginning of a file used to guess its encoding
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Guesses encoding by byte order mark, or by distribution of bytes if there is none.
+    ///
+    /// Content that is neither valid UTF-8 nor text in other supported encoding (e.g. binary
+    /// data) is treated as UTF-8, so that invalid bytes are shown as they are.
+    pub fn detect(sample: &[u8]) -> Encoding {
+        if sample.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            return Encoding::Utf8;
+        }
+        if sample.starts_with(&[0xFF, 0xFE]) {
+            return Encoding::Utf16Le;
+        }
+        if sample.starts_with(&[0xFE, 0xFF]) {
+            return Encoding::Utf16Be;
+        }
+
+        // ASCII text in UTF-16 has zero in every other byte
+        let pairs = sample.len() / 2;
+        let zeros_at = |parity: usize| sample.iter().skip(parity).step_by(2).filter(|b| **b == 0).count();
+        let (even_zeros, odd_zeros) = (zeros_at(0), zeros_at(1));
+        if pairs > 0 && odd_zeros > pairs / 4 && even_zeros <= pairs / 64 {
+            return Encoding::Utf16Le;
+        }
+        if pairs > 0 && even_zeros > pairs / 4 && odd_zeros <= pairs / 64 {
+            return Encoding::Utf16Be;
+        }
+
+        match std::str::from_utf8(sample) {
+            Ok(_) => Encoding::Utf8,
+            // the sample may end in the middle of a char
+            Err(e) if e.error_len().is_none() => Encoding::Utf8,
+            Err(_) if even_zeros + odd_zeros > 0 => Encoding::Utf8,
+            Err(_) => Encoding::Latin1,
+        }
+    }
+
+    /// Guesses encoding by the beginning of the file. Files other than regular ones (e.g. pipes)
+    /// are not sampled, as that would consume their content, and are considered UTF-8.
+    pub fn detect_file(path: &Path) -> std::io::Result<Encoding> {
+        let file = File::open(path)?;
+        if !file.metadata()?.is_file() {
+            return Ok(Encoding::Utf8);
+        }
+        let mut sample = vec![];
+        file.take(SAMPLE_SIZE).read_to_end(&mut sample)?;
+        Ok(Encoding::detect(&sample))
+    }
+
+    /// Whether content has to be converted before it can be read as UTF-8
+    pub fn needs_decoding(&self) -> bool {
+        *self != Encoding::Utf8
+    }
+
+    /// Converts content to UTF-8. Malformed sequences are replaced with U+FFFD.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let bytes = &bytes[self.bom_len(bytes)..];
+        let mut result = String::with_capacity(bytes.len());
+        self.for_each_char(bytes, |_, ch| result.push(ch));
+        result
+    }
+
+    /// Calls `f` with every char of `bytes` and its offset in `bytes`. Every byte of a malformed
+    /// sequence is a U+FFFD, so that it is not confused with the chars of the text.
+    pub fn for_each_char<F: FnMut(usize, char)>(&self, bytes: &[u8], mut f: F) {
+        match self {
+            Encoding::Utf8 => {
+                let mut offset = 0;
+                for chunk in bytes.utf8_chunks() {
+                    for (i, ch) in chunk.valid().char_indices() {
+                        f(offset + i, ch);
+                    }
+                    offset += chunk.valid().len();
+                    for i in 0..chunk.invalid().len() {
+                        f(offset + i, char::REPLACEMENT_CHARACTER);
+                    }
+                    offset += chunk.invalid().len();
+                }
+            }
+            Encoding::Latin1 => bytes.iter().enumerate().for_each(|(i, b)| f(i, char::from(*b))),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let mut i = 0;
+                while i < bytes.len() {
+                    let Some(unit) = self.utf16_unit(bytes, i) else {
+                        // odd trailing byte
+                        f(i, char::REPLACEMENT_CHARACTER);
+                        break;
+                    };
+                    let pair = self.utf16_unit(bytes, i + 2)
+                        .and_then(|next| char::decode_utf16([unit, next]).next())
+                        .and_then(Result::ok)
+                        .filter(|ch| ch.len_utf16() == 2);
+                    match pair {
+                        Some(ch) => {
+                            f(i, ch);
+                            i += 4;
+                        }
+                        None => {
+                            f(i, char::from_u32(u32::from(unit)).unwrap_or(char::REPLACEMENT_CHARACTER));
+                            i += 2;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Length of the beginning of `bytes` that consists of whole chars, so that it can be decoded
+    /// apart from the rest
+    pub fn whole_chars_len(&self, bytes: &[u8]) -> usize {
+        let len = bytes.len();
+        match self {
+            Encoding::Latin1 => len,
+            Encoding::Utf8 => {
+                // a char starts at most 3 bytes before the end
+                let first = (len.saturating_sub(4)..len).rev()
+                    .find(|i| !matches!(utf_byte_type(bytes[*i]), Ok(UnicodeByteType::Continuation)));
+                let Some(first) = first else { return len; };
+                let char_len = match utf_byte_type(bytes[first]) {
+                    Ok(UnicodeByteType::FirstOf2) => 2,
+                    Ok(UnicodeByteType::FirstOf3) => 3,
+                    Ok(UnicodeByteType::FirstOf4) => 4,
+                    _ => 1,
+                };
+                if len - first < char_len { first } else { len }
+            }
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let len = len & !1;
+                match len.checked_sub(2).and_then(|i| self.utf16_unit(bytes, i)) {
+                    // the other half of the surrogate pair is not there
+                    Some(unit) if (0xD800..0xDC00).contains(&unit) => len - 2,
+                    _ => len,
+                }
+            }
+        }
+    }
+
+    /// Length of byte order mark at the beginning of `bytes`. Byte order mark of UTF-8 is a part
+    /// of the text.
+    pub fn bom_len(&self, bytes: &[u8]) -> usize {
+        let bom: &[u8] = match self {
+            Encoding::Utf16Le => &[0xFF, 0xFE],
+            Encoding::Utf16Be => &[0xFE, 0xFF],
+            Encoding::Utf8 | Encoding::Latin1 => &[],
+        };
+        if bytes.starts_with(bom) { bom.len() } else { 0 }
+    }
+
+    fn utf16_unit(&self, bytes: &[u8], i: usize) -> Option<u16> {
+        let pair = [*bytes.get(i)?, *bytes.get(i + 1)?];
+        match self {
+            Encoding::Utf16Be => Some(u16::from_be_bytes(pair)),
+            _ => Some(u16::from_le_bytes(pair)),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            "utf-16le" | "utf16le" => Ok(Encoding::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(Encoding::Utf16Be),
+            _ => Err(format!("Unsupported encoding: {}. Expected one of utf-8, latin1, utf-16le, utf-16be", s)),
+        }
+    }
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Latin1 => "ISO-8859-1",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::Encoding;
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    fn utf16be(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(|u| u.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_that!(Encoding::detect("2024-01-01 Grüße\n".as_bytes())).is_equal_to(Encoding::Utf8);
+        assert_that!(Encoding::detect(b"2024-01-01 Gr\xfc\xdfe\n")).is_equal_to(Encoding::Latin1);
+        assert_that!(Encoding::detect(&utf16le("2024-01-01 line\n"))).is_equal_to(Encoding::Utf16Le);
+        assert_that!(Encoding::detect(&utf16be("2024-01-01 line\n"))).is_equal_to(Encoding::Utf16Be);
+        assert_that!(Encoding::detect(&[0xFF, 0xFE, 0x41, 0x04])).is_equal_to(Encoding::Utf16Le);
+        // truncated multibyte char at the end of the sample
+        assert_that!(Encoding::detect(&"ab€".as_bytes()[..4])).is_equal_to(Encoding::Utf8);
+        // binary
+        assert_that!(Encoding::detect(&[0x00, 0x00, 0x13, 0xFF, 0x00])).is_equal_to(Encoding::Utf8);
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_that!(Encoding::Latin1.decode(b"Gr\xfc\xdfe")).is_equal_to(String::from("Grüße"));
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(utf16le("Grüße 😀"));
+        assert_that!(Encoding::Utf16Le.decode(&bytes)).is_equal_to(String::from("Grüße 😀"));
+        assert_that!(Encoding::Utf16Be.decode(&utf16be("line\n"))).is_equal_to(String::from("line\n"));
+        assert_that!(Encoding::Utf16Le.decode(&[0x41, 0x00, 0x00, 0xD8])).is_equal_to(String::from("A\u{FFFD}"));
+        assert_that!(Encoding::Utf8.decode(b"a\x1a\xffb")).is_equal_to(String::from("a\u{1A}\u{FFFD}b"));
+    }
+
+    #[test]
+    fn test_whole_chars_len() {
+        let bytes = utf16le("a😀");
+        assert_that!(Encoding::Utf16Le.whole_chars_len(&bytes)).is_equal_to(6);
+        assert_that!(Encoding::Utf16Le.whole_chars_len(&bytes[..5])).is_equal_to(2);
+        assert_that!(Encoding::Utf16Le.whole_chars_len(&bytes[..4])).is_equal_to(2);
+        let bytes = "a€".as_bytes();
+        assert_that!(Encoding::Utf8.whole_chars_len(bytes)).is_equal_to(4);
+        assert_that!(Encoding::Utf8.whole_chars_len(&bytes[..3])).is_equal_to(1);
+        assert_that!(Encoding::Latin1.whole_chars_len(b"ab")).is_equal_to(2);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_that!("UTF-16LE".parse::<Encoding>()).is_ok().is_equal_to(Encoding::Utf16Le);
+        assert_that!("iso-8859-1".parse::<Encoding>()).is_ok().is_equal_to(Encoding::Latin1);
+        assert_that!("koi8-r".parse::<Encoding>()).is_err();
+    }
+}
@@ -0,0 +1,220 @@
+use std::cmp::Reverse;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+
+use crate::model::file_preview::FileKind;
+
+const COMPRESSION_EXTENSIONS: [&str; 4] = ["gz", "bz2", "xz", "zst"];
+
+/// Files matched by a directory or a glob, oldest first
+#[derive(Debug, Default)]
+pub struct FileSet {
+    pub files: Vec<PathBuf>,
+    /// Compressed files, which are not supported and are skipped
+    pub skipped: Vec<PathBuf>,
+}
+
+impl FileSet {
+    /// Names the skipped files, if any
+    pub fn skipped_warning(&self) -> Option<String> {
+        if self.skipped.is_empty() {
+            return None;
+        }
+        let names = self.skipped.iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(format!("Compressed files are skipped: {}", names))
+    }
+}
+
+/// Whether `path` names several files: it is a directory, or its file name is a glob with `*`
+/// or `?`
+pub fn is_file_set(path: &Path) -> bool {
+    if path.is_dir() {
+        return true;
+    }
+    !path.exists() && path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(is_glob)
+}
+
+pub fn is_glob(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+/// Lists regular files in the directory `path`, or the ones matching its file name in its parent
+/// directory. Hidden files are not listed, unless the glob starts with a dot. Rotated files go
+/// first from the oldest (`app.log.2`, `app.log.1`, `app.log`), other files are ordered by
+/// modification time. Compressed files are not decompressed, but listed as skipped.
+pub fn list_files(path: &Path) -> anyhow::Result<FileSet> {
+    let (dir, pattern) = if path.is_dir() {
+        (path, None)
+    } else {
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        (dir, path.file_name().and_then(|name| name.to_str()))
+    };
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir)?.filter_map(Result::ok) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_visible = !name.starts_with('.') || pattern.is_some_and(|pattern| pattern.starts_with('.'));
+        let is_match = is_visible && pattern.is_none_or(|pattern| glob_match(pattern, &name));
+        if is_match && entry.file_type().is_ok_and(|t| t.is_file()) {
+            let modified_at = entry.metadata().ok().and_then(|m| m.modified().ok());
+            files.push((entry.path(), modified_at));
+        }
+    }
+    if files.is_empty() {
+        return Err(anyhow!("No files match {:?}", path));
+    }
+    let mut result = FileSet::default();
+    for path in order(files) {
+        if is_compressed(&path) {
+            log::warn!("{:?} is compressed and is skipped", path);
+            result.skipped.push(path);
+        } else {
+            result.files.push(path);
+        }
+    }
+    if result.files.is_empty() {
+        return Err(anyhow!("All files matching {:?} are compressed", path));
+    }
+    Ok(result)
+}
+
+fn is_compressed(path: &Path) -> bool {
+    let mut sample = vec![];
+    File::open(path)
+        .and_then(|file| file.take(8).read_to_end(&mut sample))
+        .map(|_| FileKind::detect(&sample).is_compressed())
+        .unwrap_or(false)
+}
+
+fn order(mut files: Vec<(PathBuf, Option<SystemTime>)>) -> Vec<PathBuf> {
+    let rotations: Option<Vec<(&str, u32)>> = files.iter()
+        .map(|(path, _)| path.file_name().and_then(|name| name.to_str()).map(rotation))
+        .collect();
+    let same_base = rotations.as_ref()
+        .is_some_and(|rotations| rotations.iter().all(|(base, _)| *base == rotations[0].0));
+    if same_base {
+        let indexes: Vec<u32> = rotations.unwrap().into_iter().map(|(_, index)| index).collect();
+        let mut indexed: Vec<(u32, PathBuf)> = indexes.into_iter()
+            .zip(files.into_iter().map(|(path, _)| path))
+            .collect();
+        indexed.sort_by_key(|(index, _)| Reverse(*index));
+        indexed.into_iter().map(|(_, path)| path).collect()
+    } else {
+        files.sort_by(|(a, a_time), (b, b_time)| a_time.cmp(b_time).then_with(|| a.cmp(b)));
+        files.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+/// Splits name of a rotated file into the name of the current file and rotation index:
+/// `app.log.2.gz` is `("app.log", 2)`, `app.log` is `("app.log", 0)`
+fn rotation(name: &str) -> (&str, u32) {
+    let name = COMPRESSION_EXTENSIONS.iter()
+        .find_map(|ext| name.strip_suffix(ext).and_then(|name| name.strip_suffix('.')))
+        .unwrap_or(name);
+    match name.rsplit_once('.') {
+        Some((base, index)) if !base.is_empty() => match index.parse() {
+            Ok(index) => (base, index),
+            Err(_) => (name, 0),
+        },
+        _ => (name, 0),
+    }
+}
+
+/// Matches whole `name` against `pattern`, where `*` stands for any characters and `?` for one
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` and of the name char it is matched up to
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    use spectral::prelude::*;
+
+    use super::{glob_match, order, rotation, FileSet};
+
+    #[test]
+    fn test_glob_match() {
+        assert_that!(glob_match("app.log*", "app.log")).is_true();
+        assert_that!(glob_match("app.log*", "app.log.2.gz")).is_true();
+        assert_that!(glob_match("*.log", "app.log")).is_true();
+        assert_that!(glob_match("*.log", "app.log.1")).is_false();
+        assert_that!(glob_match("a?p*.log", "app-1.log")).is_true();
+        assert_that!(glob_match("*a*b", "xaxxb")).is_true();
+        assert_that!(glob_match("*a*b", "xaxxc")).is_false();
+    }
+
+    #[test]
+    fn test_rotation() {
+        assert_that!(rotation("app.log")).is_equal_to(("app.log", 0));
+        assert_that!(rotation("app.log.1")).is_equal_to(("app.log", 1));
+        assert_that!(rotation("app.log.2.gz")).is_equal_to(("app.log", 2));
+        assert_that!(rotation("app.2024-01-01.log")).is_equal_to(("app.2024-01-01.log", 0));
+    }
+
+    #[test]
+    fn test_order() {
+        let time = |s: u64| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(s));
+        let rotated = order(vec![
+            (PathBuf::from("app.log"), time(1)),
+            (PathBuf::from("app.log.10"), time(2)),
+            (PathBuf::from("app.log.2"), time(3)),
+        ]);
+        assert_that!(rotated).is_equal_to(vec![
+            PathBuf::from("app.log.10"), PathBuf::from("app.log.2"), PathBuf::from("app.log"),
+        ]);
+
+        let by_time = order(vec![
+            (PathBuf::from("b.log"), time(2)),
+            (PathBuf::from("a.log"), time(3)),
+            (PathBuf::from("c.log"), time(1)),
+        ]);
+        assert_that!(by_time).is_equal_to(vec![
+            PathBuf::from("c.log"), PathBuf::from("b.log"), PathBuf::from("a.log"),
+        ]);
+    }
+
+    #[test]
+    fn test_skipped_warning() {
+        let mut file_set = FileSet { files: vec![PathBuf::from("logs/app.log")], skipped: vec![] };
+        assert_that!(file_set.skipped_warning()).is_none();
+        file_set.skipped = vec![PathBuf::from("logs/app.log.2.gz"), PathBuf::from("logs/app.log.1.gz")];
+        assert_that!(file_set.skipped_warning())
+            .is_equal_to(Some(String::from("Compressed files are skipped: app.log.2.gz, app.log.1.gz")));
+    }
+}
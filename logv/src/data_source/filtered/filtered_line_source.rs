@@ -6,6 +6,7 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Error};
 use mucow::MuCow;
+use regex::Regex;
 use uuid::Uuid;
 
 use crate::background_process::background_process_handler::BackgroundProcessHandler;
@@ -167,6 +168,20 @@ impl FilteredLineSource {
         Self::new(original, mapper, neighbourhood)
     }
 
+    pub fn with_regex(
+        original: ConcreteLineSourceHolder,
+        pattern: &Regex,
+        neighbourhood: u8,
+    ) -> Self {
+        let pattern = pattern.clone();
+        let mapper = Arc::new(move |s: &str|
+            pattern.find_iter(s)
+                .map(|m| CustomHighlight::new(m.start(), m.end()))
+                .collect()
+        );
+        Self::new(original, mapper, neighbourhood)
+    }
+
     pub fn destroy(mut self) -> ConcreteLineSourceHolder {
         if let Some(handler) = &self.handler.as_mut() {
             handler.interrupt();
@@ -193,6 +208,23 @@ impl FilteredLineSource {
         }
     }
 
+    pub fn get_original(&self) -> &ConcreteLineSourceHolder {
+        &self.original
+    }
+
+    /// Filter lines of the original source are checked with, regardless of neighbourhood
+    pub fn get_filter(&self) -> LineFilter {
+        Arc::clone(&self.original_filter)
+    }
+
+    /// Maps `offset` to the original source, if it has been evaluated already
+    pub fn to_original_offset(&self, offset: Integer) -> Option<Integer> {
+        match self.offset_mapper.eval(ProxyOffset::from(offset)) {
+            OffsetEvaluationResult::Exact(original_offset) => Some(*original_offset),
+            _ => None,
+        }
+    }
+
     pub fn build_offset_mapper<T: RunInBackground>(&mut self, runner: &mut T, on_finish: Callback) {
         match &self.original {
             ConcreteLineSourceHolder::FileBased(ls) => {
@@ -201,6 +233,15 @@ impl FilteredLineSource {
             ConcreteLineSourceHolder::ConstantBased(ls) => {
                 self.build_offset_mapper_0(runner, ls.backend().clone(), on_finish);
             }
+            ConcreteLineSourceHolder::MmapBased(ls) => {
+                self.build_offset_mapper_0(runner, ls.backend().clone(), on_finish);
+            }
+            ConcreteLineSourceHolder::ConcatBased(ls) => {
+                self.build_offset_mapper_0(runner, ls.backend().clone(), on_finish);
+            }
+            ConcreteLineSourceHolder::TranscodedBased(ls) => {
+                self.build_offset_mapper_0(runner, ls.backend().clone(), on_finish);
+            }
         }
     }
 
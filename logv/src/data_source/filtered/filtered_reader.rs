@@ -9,6 +9,8 @@ use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::sync::Arc;
 use thiserror::Error;
 use crate::tout;
+use crate::utils;
+use crate::utils::utf8::decode_substituting;
 
 pub struct FilteredReader<R: Read + Seek> {
     cache: Cache<R>,
@@ -293,7 +295,8 @@ impl<R: Read + Seek> Cache<R> {
     ) -> Result<CacheItem, FilterError> {
         let (offset, buf) = line_reader(reader)?;
         let is_ok = {
-            let line = String::from_utf8_lossy(&buf);
+            let mut line = decode_substituting(&buf);
+            utils::trim_newline(&mut line);
             !filter(&line).is_empty()
         };
         Ok(CacheItem::new(offset, buf, is_ok))
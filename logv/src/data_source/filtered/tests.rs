@@ -1,6 +1,7 @@
 use super::*;
 use crate::data_source::filtered::filtered_line_source::FilteredLineSource;
 use crate::data_source::{Line, LineSourceImpl};
+use crate::model::log_template::Template;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use num_traits::Euclid;
@@ -179,6 +180,23 @@ fn test_none_match() {
         .is_empty();
 }
 
+#[test]
+fn test_template_filter() {
+    // templates match whole lines, so lines are filtered without line breaks
+    let original = "took 12 ms\nstarted\r\ntook 345 ms\r\ntook 5 s\ntook 7 ms\n";
+    let regex = Regex::new(&Template::from_line("took 1 ms").to_regex()).unwrap();
+    let original = ConcreteLineSourceHolder::from(LineSourceImpl::from_str(original));
+    let mut proxy = FilteredLineSource::with_regex(original, &regex, 0);
+
+    let data = proxy.read_lines(0.into(), 10.into());
+    let contents = data.lines.iter().map(|line| line.content.as_str()).collect_vec();
+    assert_that!(contents).is_equal_to(vec!["took 12 ms", "took 345 ms", "took 7 ms"]);
+
+    let mut filtered = String::new();
+    proxy.reader_factory().new_reader().unwrap().read_to_string(&mut filtered).unwrap();
+    assert_that!(filtered.as_str()).is_equal_to("took 12 ms\ntook 345 ms\r\ntook 7 ms\n");
+}
+
 #[test]
 fn test_line_registry() {
     let mut proxy = create_filtered();
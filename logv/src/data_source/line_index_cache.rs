@@ -0,0 +1,246 @@
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::data_source::line_registry::LineRegistryImpl;
+
+const MAGIC: &[u8; 8] = b"LOGVIDX1";
+/// Number of bytes at the beginning and at the end of indexed part used to detect changes
+const CHECKSUM_BLOCK: u64 = 4096;
+const HEADER_SIZE: usize = MAGIC.len() + 40;
+/// Least recently used indexes are removed when the cache directory grows beyond these limits
+const MAX_CACHED_INDEXES: usize = 100;
+const MAX_CACHE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Identifies state of a file at the moment it was indexed
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Fingerprint {
+    size: u64,
+    mtime: u64,
+    head: u64,
+    tail: u64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ContentChecksum {
+    pub head: u64,
+    pub tail: u64,
+}
+
+/// On-disk cache of line breaks. The index is stored per file path in user's cache directory
+/// and is considered valid while the indexed part of the file stays unchanged, so a file that
+/// has only grown since is re-indexed incrementally. Least recently used indexes are removed
+/// once the directory exceeds [MAX_CACHED_INDEXES] files or [MAX_CACHE_SIZE] bytes.
+pub struct LineIndexCache {
+    file_path: PathBuf,
+    index_path: PathBuf,
+}
+
+impl LineIndexCache {
+    /// The same file opened by different paths, e.g. relative ones or via symlinks, shares the index
+    pub fn for_file(file_path: &Path) -> Option<Self> {
+        let dir = cache_dir()?;
+        let file_path = fs::canonicalize(file_path).ok()?;
+        let name = format!("{:016x}.idx", fnv1a(file_path.to_string_lossy().as_bytes()));
+        Some(LineIndexCache {
+            file_path,
+            index_path: dir.join(name),
+        })
+    }
+
+    /// Returns line breaks and length of the indexed part of the file, if cache is still valid
+    pub fn load(&self) -> Option<(Vec<u64>, u64)> {
+        let data = fs::read(&self.index_path).ok()?;
+        let (stored, line_breaks) = decode(&data)?;
+        let mut file = File::open(&self.file_path).ok()?;
+        let metadata = file.metadata().ok()?;
+        let size = metadata.len();
+        let mtime = modification_time(&metadata);
+        if size < stored.size || (size == stored.size && mtime != stored.mtime) {
+            return None;
+        }
+        let actual = fingerprint(&mut file, stored.size, stored.mtime).ok()?;
+        if actual != stored {
+            return None;
+        }
+        Some((line_breaks, stored.size))
+    }
+
+    /// Stores line breaks of the first `length` bytes of the file unless the stored index
+    /// already covers exactly them
+    pub fn save(&self, line_registry: &LineRegistryImpl, length: u64) -> io::Result<()> {
+        let mut file = File::open(&self.file_path)?;
+        let mtime = modification_time(&file.metadata()?);
+        let fingerprint = fingerprint(&mut file, length, mtime)?;
+        if self.stored_fingerprint() == Some(fingerprint) {
+            // keeps the index from being pruned as unused
+            return File::options().write(true).open(&self.index_path)?.set_modified(SystemTime::now());
+        }
+        let data = line_registry.inspect(|line_breaks, _| encode(&fingerprint, line_breaks));
+
+        if let Some(dir) = self.index_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // write to a temporary file first, so that concurrent readers never see partial index
+        let tmp_path = self.index_path.with_extension("tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(&data)?;
+        tmp.sync_all()?;
+        fs::rename(tmp_path, &self.index_path)?;
+        if let Some(dir) = self.index_path.parent() {
+            prune(dir)?;
+        }
+        Ok(())
+    }
+
+    fn stored_fingerprint(&self) -> Option<Fingerprint> {
+        let mut header = [0; HEADER_SIZE];
+        File::open(&self.index_path).ok()?.read_exact(&mut header).ok()?;
+        decode_header(&header).map(|(fingerprint, _)| fingerprint)
+    }
+}
+
+/// Removes least recently written indexes beyond [MAX_CACHED_INDEXES] and [MAX_CACHE_SIZE]
+fn prune(dir: &Path) -> io::Result<()> {
+    let mut indexes: Vec<(SystemTime, u64, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "idx"))
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            Some((metadata.modified().ok()?, metadata.len(), e.path()))
+        })
+        .collect();
+    indexes.sort_by_key(|(modified_at, ..)| Reverse(*modified_at));
+    let mut total_size = 0;
+    for (i, (_, size, path)) in indexes.into_iter().enumerate() {
+        total_size += size;
+        if i >= MAX_CACHED_INDEXES || total_size > MAX_CACHE_SIZE {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+    Some(base.join("logv").join("index"))
+}
+
+fn modification_time(metadata: &fs::Metadata) -> u64 {
+    metadata.modified().ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+fn fingerprint(file: &mut File, size: u64, mtime: u64) -> io::Result<Fingerprint> {
+    let ContentChecksum { head, tail } = content_checksum(file, size)?;
+    Ok(Fingerprint { size, mtime, head, tail })
+}
+
+/// Checksums of the first and the last [CHECKSUM_BLOCK] bytes of the first `size` bytes of the file.
+/// They stay the same while the file is only appended to.
+pub fn content_checksum(file: &mut File, size: u64) -> io::Result<ContentChecksum> {
+    let mut checksum = |start: u64| -> io::Result<u64> {
+        let mut buffer = vec![0; (size - start).min(CHECKSUM_BLOCK) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut buffer)?;
+        Ok(fnv1a(&buffer))
+    };
+    let head = checksum(0)?;
+    let tail = checksum(size.saturating_sub(CHECKSUM_BLOCK))?;
+    Ok(ContentChecksum { head, tail })
+}
+
+/// FNV-1a: stable across builds unlike [std::collections::hash_map::DefaultHasher]
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Header is followed by line breaks stored as LEB128-encoded deltas
+fn encode(fingerprint: &Fingerprint, line_breaks: &[u64]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(HEADER_SIZE + line_breaks.len() * 2);
+    data.extend_from_slice(MAGIC);
+    for value in [fingerprint.size, fingerprint.mtime, fingerprint.head, fingerprint.tail, line_breaks.len() as u64] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    let mut prev = 0;
+    for &value in line_breaks {
+        let mut delta = value - prev;
+        prev = value;
+        loop {
+            let byte = (delta & 0x7f) as u8;
+            delta >>= 7;
+            if delta == 0 {
+                data.push(byte);
+                break;
+            }
+            data.push(byte | 0x80);
+        }
+    }
+    data
+}
+
+/// Returns fingerprint and number of line breaks
+fn decode_header(data: &[u8]) -> Option<(Fingerprint, usize)> {
+    let header = data.strip_prefix(MAGIC)?;
+    let field = |i: usize| header.get(i * 8..(i + 1) * 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+    let fingerprint = Fingerprint {
+        size: field(0)?,
+        mtime: field(1)?,
+        head: field(2)?,
+        tail: field(3)?,
+    };
+    Some((fingerprint, field(4)? as usize))
+}
+
+fn decode(data: &[u8]) -> Option<(Fingerprint, Vec<u64>)> {
+    let (fingerprint, count) = decode_header(data)?;
+    let mut line_breaks = Vec::with_capacity(count);
+    let mut bytes = data[HEADER_SIZE..].iter();
+    let mut prev = 0_u64;
+    for _ in 0..count {
+        let mut delta = 0_u64;
+        let mut shift = 0;
+        loop {
+            let byte = *bytes.next()?;
+            delta |= ((byte & 0x7f) as u64).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        prev += delta;
+        line_breaks.push(prev);
+    }
+    Some((fingerprint, line_breaks))
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::{decode, encode, Fingerprint};
+
+    #[test]
+    fn test_encode_decode() {
+        let fingerprint = Fingerprint { size: 1 << 40, mtime: 12345, head: 1, tail: 2 };
+        let line_breaks = vec![0_u64, 1, 200, 70_000, 1 << 39];
+        let data = encode(&fingerprint, &line_breaks);
+        assert_that!(decode(&data)).is_some().is_equal_to((fingerprint, line_breaks));
+    }
+
+    #[test]
+    fn test_decode_corrupted() {
+        let fingerprint = Fingerprint { size: 100, mtime: 0, head: 0, tail: 0 };
+        let data = encode(&fingerprint, &[300]);
+        assert_that!(decode(&data[..data.len() - 1])).is_none();
+        assert_that!(decode(b"garbage")).is_none();
+    }
+}
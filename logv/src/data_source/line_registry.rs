@@ -2,15 +2,24 @@ use crate::data_source::BUFFER_SIZE;
 use crate::interval::{Interval, IntervalBound};
 use crate::utils::ToUnit;
 use fluent_integer::Integer;
+use crossbeam_channel::RecvTimeoutError;
+use memchr::memchr_iter;
 use metrics::{describe_gauge, describe_histogram, gauge, histogram, Unit};
-use std::cmp::Ordering;
-use std::io::{BufReader, Read, Seek};
+use std::cmp::{min, Ordering};
+use std::collections::BTreeMap;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::atomic::Ordering::Relaxed;
 use std::sync::RwLock;
+use std::thread;
 use std::time::{Duration, Instant};
 use std::vec::IntoIter;
 use thiserror::Error;
 
 const PROGRESS_REPORT_PERIOD: Duration = Duration::from_millis(100);
+/// Size of a part of file crawled by a single worker at once
+const CHUNK_SIZE: u64 = 16 * 1024 * 1024;
 
 const METRIC_BUILD: &str = "LineRegistry: build";
 const METRIC_READ: &str = "LineRegistry: read";
@@ -43,6 +52,16 @@ pub trait LineRegistry {
         R: Read + Seek,
         F: Fn() -> bool,
         G: Fn(usize);
+
+    /// Same as [build](LineRegistry::build), but `[start, end)` is split into chunks crawled by
+    /// several threads. Every thread opens its own reader via `new_reader`. Whatever is appended
+    /// after `end` is crawled sequentially afterwards.
+    fn build_parallel<R, N, F, G>(&self, new_reader: N, start: u64, end: u64, is_interrupted: F, bytes_processed: G) -> LineRegistryResult<()>
+    where
+        R: Read + Seek,
+        N: Fn() -> std::io::Result<R> + Sync,
+        F: Fn() -> bool,
+        G: Fn(usize);
 }
 
 #[derive(Error, Debug)]
@@ -107,7 +126,8 @@ pub struct LineRegistryImpl {
 
 #[derive(Default, Debug)]
 struct Internals {
-    line_breaks: Vec<Integer>,
+    // u64 takes half of the memory of Integer, which matters for files with billions of lines
+    line_breaks: Vec<u64>,
     crawled: Integer,
 }
 
@@ -125,10 +145,117 @@ impl LineRegistryImpl {
         }
     }
 
+    /// Replaces content with line breaks crawled earlier, e.g. restored from
+    /// [LineIndexCache](crate::data_source::line_index_cache::LineIndexCache)
+    pub fn restore(&self, line_breaks: Vec<u64>, crawled: Integer) {
+        let mut internals = self.internals.write().unwrap();
+        internals.line_breaks = line_breaks;
+        internals.crawled = crawled;
+    }
+
+    /// Provides read access to line breaks and crawled offset without copying
+    pub fn inspect<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(&[u64], Integer) -> T
+    {
+        let internals = self.internals.read().unwrap();
+        f(&internals.line_breaks, internals.crawled)
+    }
+
+    fn crawl_in_parallel<R, N, F, G>(
+        &self,
+        new_reader: &N,
+        start: u64,
+        end: u64,
+        chunk_size: u64,
+        is_interrupted: &F,
+        bytes_processed: &G,
+    ) -> LineRegistryResult<()>
+    where
+        R: Read + Seek,
+        N: Fn() -> std::io::Result<R> + Sync,
+        F: Fn() -> bool,
+        G: Fn(usize),
+    {
+        let chunks: Vec<(u64, u64)> = (start..end)
+            .step_by(chunk_size as usize)
+            .map(|s| (s, min(s + chunk_size, end)))
+            .collect();
+        let workers = thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1)
+            .min(chunks.len());
+        let next_chunk = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                let sender = sender.clone();
+                let (chunks, next_chunk, stop) = (&chunks, &next_chunk, &stop);
+                scope.spawn(move || {
+                    while !stop.load(Relaxed) {
+                        let i = next_chunk.fetch_add(1, Relaxed);
+                        let Some(&(s, e)) = chunks.get(i) else { break; };
+                        let result = crawl_chunk(new_reader, s, e, stop);
+                        if sender.send((i, result)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            // chunks are merged in order, so that crawled part is always contiguous
+            let mut pending = BTreeMap::new();
+            let mut next = 0;
+            let mut last_report = Instant::now();
+            let result = 'merge: loop {
+                if next == chunks.len() {
+                    break Ok(());
+                }
+                if is_interrupted() {
+                    break Err(LineRegistryError::Cancelled);
+                }
+                match receiver.recv_timeout(PROGRESS_REPORT_PERIOD) {
+                    Ok((i, chunk_result)) => {
+                        pending.insert(i, chunk_result);
+                    },
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break Err(LineRegistryError::Cancelled),
+                }
+                while let Some(chunk_result) = pending.remove(&next) {
+                    let sw = Instant::now();
+                    match chunk_result {
+                        Ok(line_breaks) => {
+                            let mut internals = self.internals.write().unwrap();
+                            internals.line_breaks.extend(line_breaks);
+                            internals.crawled = chunks[next].1.into();
+                        },
+                        Err(err) => break 'merge Err(LineRegistryError::IO(err)),
+                    }
+                    {
+                        let elapsed = sw.elapsed().to_unit(&Unit::Microseconds);
+                        histogram!(METRIC_SUBMIT).record(elapsed);
+                        gauge!(METRIC_SUBMIT_TOTAL).increment(elapsed);
+                    }
+                    next += 1;
+                }
+                if last_report.elapsed() > PROGRESS_REPORT_PERIOD {
+                    let crawled_end = next.checked_sub(1).map(|i| chunks[i].1).unwrap_or(start);
+                    bytes_processed((crawled_end - start) as usize);
+                    last_report = Instant::now();
+                }
+            };
+            stop.store(true, Relaxed);
+            result
+        })
+    }
+
     #[cfg(test)]
     fn with_data<I: Into<Integer> + Copy>(data: Vec<I>) -> Self {
-        let line_breaks: Vec<Integer> = data.iter().map(|i| (*i).into()).collect();
-        let crawled = *line_breaks.iter().max().unwrap_or(&0.into());
+        let line_breaks: Vec<u64> = data.iter().map(|i| (*i).into().as_u64()).collect();
+        let crawled = line_breaks.iter().max().copied().unwrap_or_default().into();
         LineRegistryImpl {
             internals: RwLock::new(Internals {
                 line_breaks,
@@ -145,8 +272,8 @@ impl LineRegistry for LineRegistryImpl {
     {
         let offset = offset.into();
         let mut internals = self.internals.write().unwrap();
-        let Err(p) = internals.line_breaks.binary_search(&offset) else { return; };
-        internals.line_breaks.insert(p, offset);
+        let Err(p) = internals.line_breaks.binary_search(&offset.as_u64()) else { return; };
+        internals.line_breaks.insert(p, offset.as_u64());
         if internals.crawled < offset {
             internals.crawled = offset;
         }
@@ -168,19 +295,15 @@ impl LineRegistry for LineRegistryImpl {
 
         let v = &internals.line_breaks;
 
+        // bounds may be negative, so line breaks are compared as Integer
         let s = match &range.left_bound {
             IntervalBound::PositiveInfinity => None,
             IntervalBound::NegativeInfinity => Some(0),
             IntervalBound::Fixed { value, is_included } => {
-                match v.binary_search(value) {
-                    Ok(p) => {
-                        if *is_included {
-                            Some(p)
-                        } else {
-                            Some(p + 1)
-                        }
-                    }
-                    Err(p) => Some(p),
+                if *is_included {
+                    Some(v.partition_point(|lb| Integer::from(*lb) < *value))
+                } else {
+                    Some(v.partition_point(|lb| Integer::from(*lb) <= *value))
                 }
             }
         };
@@ -188,15 +311,10 @@ impl LineRegistry for LineRegistryImpl {
             IntervalBound::PositiveInfinity => Some(Integer::from(v.len()) - 1),
             IntervalBound::NegativeInfinity => None,
             IntervalBound::Fixed { value, is_included } => {
-                match v.binary_search(&(*value).into()) {
-                    Ok(p) => {
-                        if *is_included {
-                            Some(Integer::from(p))
-                        } else {
-                            Some(Integer::from(p) - 1)
-                        }
-                    }
-                    Err(p) => Some(Integer::from(p) - 1),
+                if *is_included {
+                    Some(Integer::from(v.partition_point(|lb| Integer::from(*lb) <= *value)) - 1)
+                } else {
+                    Some(Integer::from(v.partition_point(|lb| Integer::from(*lb) < *value)) - 1)
                 }
             }
         };
@@ -218,7 +336,7 @@ impl LineRegistry for LineRegistryImpl {
         let internals = self.internals.read()
             .map_err(|_| Integer::new(0))?;
         internals.line_breaks.get(line_no.as_usize() - 1)
-            .map(|p| *p + 1)
+            .map(|p| Integer::from(*p + 1))
             .ok_or(internals.crawled)
     }
 
@@ -229,12 +347,12 @@ impl LineRegistry for LineRegistryImpl {
         G: Fn(usize),
     {
         let sw_total = Instant::now();
-        let mut offset: Integer = reader.stream_position()?.into();
+        let mut offset = reader.stream_position()?;
         let mut bytes_read = 0;
         let mut buffer: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
         let mut last_report = Instant::now();
         let mut sw_read = Instant::now();
-        let mut data: Vec<Integer> = Vec::new();
+        let mut data: Vec<u64> = Vec::new();
         while let Ok(b) = reader.read(&mut buffer) {
             {
                 let elapsed = sw_read.elapsed().to_unit(&Unit::Microseconds);
@@ -250,15 +368,8 @@ impl LineRegistry for LineRegistryImpl {
             }
 
             let sw = Instant::now();
-            let mut p = 0_usize;
             data.clear();
-            #[allow(clippy::explicit_counter_loop)] // using enumerate slows it twice
-            for ch in &buffer[0..b] {
-                if *ch == b'\n' {
-                    data.push(offset + p);
-                }
-                p += 1;
-            }
+            data.extend(memchr_iter(b'\n', &buffer[0..b]).map(|p| offset + p as u64));
             {
                 let elapsed = sw.elapsed().to_unit(&Unit::Microseconds);
                 histogram!(METRIC_COUNT).record(elapsed);
@@ -269,7 +380,7 @@ impl LineRegistry for LineRegistryImpl {
             if !data.is_empty() {
                 let mut internals = self.internals.write().unwrap();
                 data.iter().for_each(|i| internals.line_breaks.push(*i));
-                internals.crawled = offset + b;
+                internals.crawled = (offset + b as u64).into();
             }
             {
                 let elapsed = sw.elapsed().to_unit(&Unit::Microseconds);
@@ -278,7 +389,7 @@ impl LineRegistry for LineRegistryImpl {
             }
 
             bytes_read += b;
-            offset += b;
+            offset += b as u64;
 
             if last_report.elapsed() > PROGRESS_REPORT_PERIOD {
                 bytes_processed(bytes_read);
@@ -291,6 +402,55 @@ impl LineRegistry for LineRegistryImpl {
         histogram!(METRIC_BUILD).record(sw_total.elapsed().to_unit(&Unit::Milliseconds));
         Ok(())
     }
+
+    fn build_parallel<R, N, F, G>(&self, new_reader: N, start: u64, end: u64, is_interrupted: F, bytes_processed: G) -> LineRegistryResult<()>
+    where
+        R: Read + Seek,
+        N: Fn() -> std::io::Result<R> + Sync,
+        F: Fn() -> bool,
+        G: Fn(usize),
+    {
+        let sw_total = Instant::now();
+        self.crawl_in_parallel(&new_reader, start, end, CHUNK_SIZE, &is_interrupted, &bytes_processed)?;
+        histogram!(METRIC_BUILD).record(sw_total.elapsed().to_unit(&Unit::Milliseconds));
+
+        let mut reader = BufReader::new(new_reader()?);
+        reader.seek(SeekFrom::Start(end))?;
+        let parallel_bytes = (end - start) as usize;
+        self.build(&mut reader, is_interrupted, |b| bytes_processed(parallel_bytes + b))
+    }
+}
+
+/// Returns offsets of line breaks within `[start, end)`
+fn crawl_chunk<R, N>(new_reader: &N, start: u64, end: u64, stop: &AtomicBool) -> std::io::Result<Vec<u64>>
+where
+    R: Read + Seek,
+    N: Fn() -> std::io::Result<R>,
+{
+    let mut reader = new_reader()?;
+    reader.seek(SeekFrom::Start(start))?;
+    let mut buffer = vec![0_u8; BUFFER_SIZE];
+    let mut result = vec![];
+    let mut offset = start;
+    while offset < end {
+        if stop.load(Relaxed) {
+            return Err(std::io::Error::from(std::io::ErrorKind::Interrupted));
+        }
+        let len = min(BUFFER_SIZE as u64, end - offset) as usize;
+        let b = reader.read(&mut buffer[..len])?;
+        if b == 0 {
+            break;
+        }
+        let sw = Instant::now();
+        result.extend(memchr_iter(b'\n', &buffer[..b]).map(|p| offset + p as u64));
+        {
+            let elapsed = sw.elapsed().to_unit(&Unit::Microseconds);
+            histogram!(METRIC_COUNT).record(elapsed);
+            gauge!(METRIC_COUNT_TOTAL).increment(elapsed);
+        }
+        offset += b as u64;
+    }
+    Ok(result)
 }
 
 impl IntoIterator for &LineRegistryImpl {
@@ -299,7 +459,7 @@ impl IntoIterator for &LineRegistryImpl {
 
     fn into_iter(self) -> Self::IntoIter {
         let internals = self.internals.read().unwrap();
-        internals.line_breaks.clone().into_iter()
+        internals.line_breaks.iter().copied().map(Integer::from).collect::<Vec<_>>().into_iter()
     }
 }
 
@@ -311,6 +471,7 @@ mod tests {
     use fluent_integer::Integer;
     use paste::paste;
     use spectral::prelude::*;
+    use std::io::Cursor;
 
     const N: usize = 15;
 
@@ -388,9 +549,24 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_crawl_in_parallel() {
+        let text = "a\nbb\n\nccc\ndddd\neeeee\n".repeat(10);
+        let expected: Vec<Integer> = text.match_indices('\n')
+            .map(|(i, _)| Integer::from(i))
+            .collect();
+        let registry = LineRegistryImpl::new();
+        let new_reader = || Ok(Cursor::new(text.as_bytes()));
+        let result = registry.crawl_in_parallel(&new_reader, 0, text.len() as u64, 7, &|| false, &|_| {});
+        assert_that!(result).is_ok();
+        let actual: Vec<Integer> = registry.into_iter().collect();
+        assert_that!(actual).is_equal_to(expected);
+        assert_that!(registry.count(&Interval::closed(0, text.len() as u64 - 1))).is_ok_containing(60);
+    }
+
     test_push!("to_empty", Vec::<i32>::new(), 10, vec![10]);
     test_push!("to_tail", vec![0, 10, 20, 30, 40], 50, vec![0, 10, 20, 30, 40, 50]);
     test_push!("to_middle", vec![0, 10, 20, 30, 40], 25, vec![0, 10, 20, 25, 30, 40]);
-    test_push!("to_head", vec![0, 10, 20, 30, 40], -10, vec![-10, 0, 10, 20, 30, 40]);
+    test_push!("to_head", vec![10, 20, 30, 40], 0, vec![0, 10, 20, 30, 40]);
     test_push!("to_existing", vec![0, 10, 20, 30, 40], 20, vec![0, 10, 20, 30, 40]);
 }
@@ -5,16 +5,25 @@ use std::sync::Arc;
 
 use fluent_integer::Integer;
 
+use crate::data_source::concat_backend::{ConcatBackend, ConcatReader};
 use crate::data_source::filtered::FilteredLineSource;
 use crate::data_source::line_registry::LineRegistryImpl;
+use crate::data_source::mmap_backend::{MappedFile, MmapBackend};
+use crate::data_source::transcoded::{TranscodedBackend, TranscodedReader};
 use crate::data_source::{Data, Direction, FileBackend, Line, LineSource, LineSourceImpl, StrBackend};
 
 #[derive(Clone)]
 pub enum ConcreteLineSourceHolder {
     FileBased(LineSourceImpl<File, FileBackend>),
     ConstantBased(LineSourceImpl<Cursor<&'static [u8]>, StrBackend<'static>>),
+    MmapBased(LineSourceImpl<Cursor<MappedFile>, MmapBackend>),
+    ConcatBased(LineSourceImpl<ConcatReader, ConcatBackend>),
+    TranscodedBased(LineSourceImpl<TranscodedReader, TranscodedBackend>),
 }
 
+/// Creates a copy of a line source in another thread, as line sources cannot be sent
+pub type LineSourceFactory = Box<dyn FnOnce() -> ConcreteLineSourceHolder + Send>;
+
 impl Deref for ConcreteLineSourceHolder {
     type Target = dyn LineSource;
 
@@ -22,6 +31,9 @@ impl Deref for ConcreteLineSourceHolder {
         match self {
             ConcreteLineSourceHolder::FileBased(obj) => obj,
             ConcreteLineSourceHolder::ConstantBased(obj) => obj,
+            ConcreteLineSourceHolder::MmapBased(obj) => obj,
+            ConcreteLineSourceHolder::ConcatBased(obj) => obj,
+            ConcreteLineSourceHolder::TranscodedBased(obj) => obj,
         }
     }
 }
@@ -31,6 +43,9 @@ impl DerefMut for ConcreteLineSourceHolder {
         match self {
             ConcreteLineSourceHolder::FileBased(obj) => obj,
             ConcreteLineSourceHolder::ConstantBased(obj) => obj,
+            ConcreteLineSourceHolder::MmapBased(obj) => obj,
+            ConcreteLineSourceHolder::ConcatBased(obj) => obj,
+            ConcreteLineSourceHolder::TranscodedBased(obj) => obj,
         }
     }
 }
@@ -47,11 +62,69 @@ impl From<LineSourceImpl<File, FileBackend>> for ConcreteLineSourceHolder {
     }
 }
 
+impl From<LineSourceImpl<Cursor<MappedFile>, MmapBackend>> for ConcreteLineSourceHolder {
+    fn from(value: LineSourceImpl<Cursor<MappedFile>, MmapBackend>) -> Self {
+        ConcreteLineSourceHolder::MmapBased(value)
+    }
+}
+
+impl From<LineSourceImpl<ConcatReader, ConcatBackend>> for ConcreteLineSourceHolder {
+    fn from(value: LineSourceImpl<ConcatReader, ConcatBackend>) -> Self {
+        ConcreteLineSourceHolder::ConcatBased(value)
+    }
+}
+
+impl From<LineSourceImpl<TranscodedReader, TranscodedBackend>> for ConcreteLineSourceHolder {
+    fn from(value: LineSourceImpl<TranscodedReader, TranscodedBackend>) -> Self {
+        ConcreteLineSourceHolder::TranscodedBased(value)
+    }
+}
+
 impl ConcreteLineSourceHolder {
     pub fn get_length(&self) -> Integer {
         match &self {
             ConcreteLineSourceHolder::FileBased(h) => h.get_length(),
             ConcreteLineSourceHolder::ConstantBased(h) => h.get_length(),
+            ConcreteLineSourceHolder::MmapBased(h) => h.get_length(),
+            ConcreteLineSourceHolder::ConcatBased(h) => h.get_length(),
+            ConcreteLineSourceHolder::TranscodedBased(h) => h.get_length(),
+        }
+    }
+
+    /// Reads bytes in `[start, end)` as they are
+    pub fn read_bytes(&self, start: Integer, end: Integer) -> std::io::Result<Vec<u8>> {
+        match &self {
+            ConcreteLineSourceHolder::FileBased(h) => h.read_bytes(start, end),
+            ConcreteLineSourceHolder::ConstantBased(h) => h.read_bytes(start, end),
+            ConcreteLineSourceHolder::MmapBased(h) => h.read_bytes(start, end),
+            ConcreteLineSourceHolder::ConcatBased(h) => h.read_bytes(start, end),
+            ConcreteLineSourceHolder::TranscodedBased(h) => h.read_bytes(start, end),
+        }
+    }
+
+    /// Factory of a line source reading the same content
+    pub fn factory(&self) -> LineSourceFactory {
+        match &self {
+            ConcreteLineSourceHolder::FileBased(h) => {
+                let backend = h.backend().clone();
+                Box::new(move || LineSourceImpl::new(backend).into())
+            },
+            ConcreteLineSourceHolder::ConstantBased(h) => {
+                let backend = h.backend().clone();
+                Box::new(move || LineSourceImpl::new(backend).into())
+            },
+            ConcreteLineSourceHolder::MmapBased(h) => {
+                let backend = h.backend().clone();
+                Box::new(move || LineSourceImpl::new(backend).into())
+            },
+            ConcreteLineSourceHolder::ConcatBased(h) => {
+                let backend = h.backend().clone();
+                Box::new(move || LineSourceImpl::new(backend).into())
+            },
+            ConcreteLineSourceHolder::TranscodedBased(h) => {
+                let backend = h.backend().clone();
+                Box::new(move || LineSourceImpl::new(backend).into())
+            },
         }
     }
 }
@@ -94,6 +167,24 @@ impl From<FilteredLineSource> for LineSourceHolder {
     }
 }
 
+impl LineSourceHolder {
+    /// Returns the underlying source, i.e. the one that is not filtered
+    pub fn get_original(&self) -> &ConcreteLineSourceHolder {
+        match self {
+            LineSourceHolder::Concrete(concrete) => concrete,
+            LineSourceHolder::Filtered(filtered) => filtered.get_original(),
+        }
+    }
+
+    /// Maps `offset` to the underlying source
+    pub fn to_original_offset(&self, offset: Integer) -> Option<Integer> {
+        match self {
+            LineSourceHolder::Concrete(_) => Some(offset),
+            LineSourceHolder::Filtered(filtered) => filtered.to_original_offset(offset),
+        }
+    }
+}
+
 impl LineSource for LineSourceHolder {
 
     fn read_lines(&mut self, offset: Integer, number_of_lines: Integer) -> Data {
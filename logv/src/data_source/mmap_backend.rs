@@ -0,0 +1,75 @@
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{BufReader, Cursor, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+
+use crate::data_source::reader_factory::mmap::MmapReaderFactory;
+use crate::data_source::reader_factory::ReaderFactory;
+use crate::data_source::LineSourceBackend;
+
+/// Read-only memory map of a whole file. Cloning is cheap.
+///
+/// Note that the map reflects the length of the file at the moment it was opened. Truncating
+/// the file while it is mapped is not supported by operating systems and may crash the process.
+#[derive(Clone)]
+pub struct MappedFile(Arc<Mmap>);
+
+impl MappedFile {
+    /// Maps regular file at `path`. Fails for other kinds of files (pipes, devices, etc.), so that
+    /// the caller could fall back to [FileBackend](crate::data_source::FileBackend).
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() || metadata.len() == 0 {
+            return Err(std::io::Error::new(ErrorKind::Unsupported, "Only non-empty regular files can be mapped"));
+        }
+        // SAFETY: the map is read-only. Concurrent modification of the file is accepted the same
+        // way as for plain reads.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(MappedFile(Arc::new(mmap)))
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Debug for MappedFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MappedFile({} bytes)", self.0.len())
+    }
+}
+
+#[derive(Clone)]
+pub struct MmapBackend {
+    mapped: MappedFile,
+}
+
+impl MmapBackend {
+    pub fn new(path: &Path) -> std::io::Result<Self> {
+        MappedFile::open(path).map(|mapped| MmapBackend { mapped })
+    }
+}
+
+impl LineSourceBackend<Cursor<MappedFile>> for MmapBackend {
+    fn get_length(&self) -> u64 {
+        self.mapped.as_ref().len() as u64
+    }
+
+    fn new_reader(&self) -> BufReader<Cursor<MappedFile>> {
+        BufReader::new(Cursor::new(self.mapped.clone()))
+    }
+
+    fn reader_factory(&self) -> Box<dyn ReaderFactory> {
+        Box::new(MmapReaderFactory::new(self.mapped.clone()))
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        Some(self.mapped.as_ref())
+    }
+}
@@ -13,7 +13,8 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use crate::data_source::reader_factory::ReaderFactory;
 pub use crate::data_source::line::{Line, LineBuilder};
-use crate::data_source::read_delimited::read_delimited;
+pub use crate::data_source::read_delimited::INVALID_BYTES_CUSTOM_DATA_KEY;
+use crate::data_source::read_delimited::{read_delimited, read_lines_from_slice};
 use crate::data_source::reader_factory::file::FileBasedReaderFactory;
 use crate::data_source::reader_factory::string::StringBasedReaderFactory;
 
@@ -108,6 +109,11 @@ pub trait LineSourceBackend<R: Read + Seek> {
     fn new_reader(&self) -> BufReader<R>;
 
     fn reader_factory(&self) -> Box<dyn ReaderFactory>;
+
+    /// Whole content, if it is available in memory. Allows faster line reading.
+    fn as_bytes(&self) -> Option<&[u8]> {
+        None
+    }
 }
 
 #[derive(Clone)]
@@ -198,6 +204,16 @@ impl<R, B> LineSourceImpl<R, B> where R: Read + Seek, B: LineSourceBackend<R> {
         self.backend.get_length().into()
     }
 
+    /// Reads bytes in `[start, end)` as they are
+    pub fn read_bytes(&self, start: Integer, end: Integer) -> std::io::Result<Vec<u8>> {
+        let mut f = self.backend.new_reader();
+        f.seek(SeekFrom::Start(start.as_u64()))?;
+        let len = (end - start).as_usize();
+        let mut result: Vec<u8> = Vec::with_capacity(len);
+        f.read_fluently(len as i128, |chunk: &[u8]| result.extend_from_slice(chunk))?;
+        Ok(result)
+    }
+
     fn reader(&mut self) -> RefMut<'_, BufReader<R>> {
         if self.file_reader.is_none() {
             let f = self.backend.new_reader();
@@ -253,12 +269,17 @@ impl<R, B> LineSource for LineSourceImpl<R, B> where R: Read + Seek, B: LineSour
         } else {
             offset
         };
-        let result = self.with_reader(|mut f| {
-            log::trace!("read_lines number_of_lines = {}, offset = {}", number_of_lines, offset);
-            stat(METRIC_READ_DELIMITED, &Unit::Microseconds, || {
-                read_delimited(&mut f, offset, number_of_lines, true, line_registry, |c| *c == '\n')
-            })
-        }).unwrap_or_default();
+        log::trace!("read_lines number_of_lines = {}, offset = {}", number_of_lines, offset);
+        let result = match self.backend.as_bytes() {
+            Some(bytes) => stat(METRIC_READ_DELIMITED, &Unit::Microseconds, || {
+                read_lines_from_slice(bytes, offset, number_of_lines, line_registry)
+            }),
+            None => self.with_reader(|mut f| {
+                stat(METRIC_READ_DELIMITED, &Unit::Microseconds, || {
+                    read_delimited(&mut f, offset, number_of_lines, true, line_registry, |c| *c == '\n')
+                })
+            }).unwrap_or_default(),
+        };
 
         log::trace!("Result: {:?}", result);
 
@@ -270,17 +291,8 @@ impl<R, B> LineSource for LineSourceImpl<R, B> where R: Read + Seek, B: LineSour
     }
 
     fn read_raw(&mut self, start: Integer, end: Integer) -> Result<String, ()> {
-        let mut f = self.backend.new_reader();
-        f.seek(SeekFrom::Start(start.as_u64())).map_err(|_| ())?;
-        let len = (end - start).as_usize();
-        let mut result: Vec<u8> = Vec::with_capacity(len);
-        let consumer = |chunk: &[u8]| {
-            for x in chunk {
-                result.push(*x);
-            }
-        };
-        f.read_fluently(len as i128, consumer).map_err(|_| ())?;
-        Ok(String::from_utf8(result).unwrap())
+        let bytes = self.read_bytes(start, end).map_err(|_| ())?;
+        Ok(crate::utils::utf8::decode_substituting(&bytes))
     }
 
     fn skip_token(&mut self, offset: Integer, direction: Direction) -> anyhow::Result<Integer> {
@@ -299,6 +311,14 @@ impl<R, B> LineSource for LineSourceImpl<R, B> where R: Read + Seek, B: LineSour
 mod data_source_tests;
 
 pub mod line_registry;
+pub mod time_index;
+pub mod line_index_cache;
+pub mod mmap_backend;
+pub mod concat_backend;
+pub mod transcoded;
+pub mod file_set;
+pub mod encoding;
+pub mod delimiter;
 pub mod filtered;
 pub mod line_source_holder;
 pub mod reader_factory;
@@ -1,13 +1,19 @@
 use std::cmp::Ordering;
 use std::io::{BufReader, Read, Seek};
+use memchr::{memchr, memrchr};
 use std::sync::Arc;
 use fluent_integer::Integer;
-use crate::data_source::{Data, Direction, Line};
+use crate::data_source::{CustomHighlight, CustomHighlights, Data, Direction, Line};
 use crate::data_source::char_navigation::{next_char, peek_next_char, peek_prev_char, prev_char};
 use crate::data_source::line_registry::{LineRegistry, LineRegistryImpl};
 use crate::interval::Interval;
-use crate::model::rendered::LineNumberMissingReason;
+use crate::model::rendered::{LineNumberMissingReason, LineNumberResult};
 use crate::utils;
+use crate::utils::utf8;
+use crate::utils::utf8::UtfChar;
+
+/// Key of custom highlights that mark bytes of malformed UTF-8 sequences in lines
+pub const INVALID_BYTES_CUSTOM_DATA_KEY: &str = "InvalidBytesCustomData";
 
 /// Reads a collection of at most `abs(n)` segments (lines, words, etc.) that are delimited by chars that
 /// satisfy `is_delimiter` in direction denoted by `sign(n)`.
@@ -42,20 +48,22 @@ where R: Read + Seek, F: Fn(&char) -> bool
     let actual_offset: Integer = f.stream_position()?.into();
     let shift = (offset - actual_offset).as_i64();
     f.seek_relative(shift)?;
-    let mut current_no = line_registry
-        .ok_or(LineNumberMissingReason::LineNumberingTurnedOff)
-        .and_then(|r| {
-            let interval = Interval::closed_open(0.into(), offset);
-            r.count(&interval).map_err(LineNumberMissingReason::Delegate)
-        });
+    let mut current_no = line_number_at(line_registry, offset);
 
     let mut data = vec![];
     let mut stack = vec![];
-    let flush = |s: &mut Vec<char>| -> (String, u64) {
-        let mut content: String = s.iter().collect();
+    let flush = |s: &mut Vec<UtfChar>| -> (String, u64, Vec<CustomHighlight>) {
+        let mut content = String::with_capacity(s.len());
+        let mut invalid = vec![];
+        for ch in s.iter() {
+            if !ch.is_valid() {
+                invalid.push(CustomHighlight::new(content.len(), content.len() + 1));
+            }
+            content.push(ch.get_char());
+        }
         let bytes_trimmed = utils::trim_newline(&mut content);
         s.clear();
-        (content, bytes_trimmed as u64)
+        (content, bytes_trimmed as u64, invalid)
     };
 
     match direction {
@@ -74,15 +82,16 @@ where R: Read + Seek, F: Fn(&char) -> bool
             loop {
                 if let Some(ch) = next_char(f)? {
                     if !is_delimiter(&ch.get_char()) {
-                        stack.push(ch.get_char());
                         start = start.or(Some(ch.get_offset()));
+                        stack.push(ch);
                     } else {
                         let line_no = current_no.clone();
                         current_no = current_no.map(|n| n + 1);
                         if !stack.is_empty() || allow_empty_segments {
-                            let (content, bytes_trimmed) = flush(&mut stack);
+                            let (content, bytes_trimmed, invalid) = flush(&mut stack);
                             let line = Line::builder()
                                 .with_content(content)
+                                .with_custom_highlights(invalid_bytes_highlights(invalid))
                                 .with_start(start.unwrap_or(ch.get_offset()))
                                 .with_end(ch.get_offset() - bytes_trimmed)
                                 .with_line_no(line_no)
@@ -97,9 +106,10 @@ where R: Read + Seek, F: Fn(&char) -> bool
                 } else {
                     // EOF
                     if !stack.is_empty() || (allow_empty_segments && start.is_some()) {
-                        let (content, bytes_trimmed) = flush(&mut stack);
+                        let (content, bytes_trimmed, invalid) = flush(&mut stack);
                         let line = Line::builder()
                             .with_content(content)
+                            .with_custom_highlights(invalid_bytes_highlights(invalid))
                             .with_start(start.unwrap())
                             .with_end(f.stream_position()? - bytes_trimmed)
                             .with_line_no(current_no.clone())
@@ -125,16 +135,17 @@ where R: Read + Seek, F: Fn(&char) -> bool
             loop {
                 if let Some(ch) = prev_char(f)? {
                     if !is_delimiter(&ch.get_char()) {
-                        stack.push(ch.get_char());
                         end = end.or(Some(ch.get_end()));
+                        stack.push(ch);
                     } else {
                         let line_no = current_no.clone();
                         current_no = current_no.map(|n| n.saturating_sub(1));
                         if !stack.is_empty() || allow_empty_segments {
                             stack.reverse();
-                            let (content, bytes_trimmed) = flush(&mut stack);
+                            let (content, bytes_trimmed, invalid) = flush(&mut stack);
                             let line = Line::builder()
                                 .with_content(content)
+                                .with_custom_highlights(invalid_bytes_highlights(invalid))
                                 .with_start(ch.get_offset() + 1)
                                 .with_end(end.unwrap_or(ch.get_end()) - bytes_trimmed)
                                 .with_line_no(line_no)
@@ -150,9 +161,10 @@ where R: Read + Seek, F: Fn(&char) -> bool
                     // BOF
                     if !stack.is_empty() || (allow_empty_segments && end.is_some()) {
                         stack.reverse();
-                        let (content, bytes_trimmed) = flush(&mut stack);
+                        let (content, bytes_trimmed, invalid) = flush(&mut stack);
                         let line = Line::builder()
                             .with_content(content)
+                            .with_custom_highlights(invalid_bytes_highlights(invalid))
                             .with_start(0)
                             .with_end(end.unwrap() - bytes_trimmed)
                             .with_line_no(current_no.clone())
@@ -175,4 +187,112 @@ where R: Read + Seek, F: Fn(&char) -> bool
         start: s,
         end: e,
     })
-}
\ No newline at end of file
+}
+
+/// Same as [read_delimited] with `\n` as delimiter and empty segments allowed, but works on
+/// content that is entirely in memory (e.g. mapped file). Line breaks are looked up by `memchr`.
+pub fn read_lines_from_slice(
+    bytes: &[u8],
+    offset: Integer,
+    n: Integer,
+    line_registry: Option<Arc<LineRegistryImpl>>) -> Data
+{
+    if offset < 0 || n == Integer::from(0) {
+        return Data::default();
+    }
+    let len = bytes.len();
+    if offset > len {
+        return Data::default();
+    }
+    let offset = offset.as_usize();
+    let mut current_no = line_number_at(line_registry, offset.into());
+    let max_lines = n.abs().as_usize();
+    let mut data = vec![];
+    let line = |start: usize, end: usize, line_no: LineNumberResult| {
+        let (mut content, invalid) = utf8::decode_marking_invalid(&bytes[start..end]);
+        let bytes_trimmed = utils::trim_newline(&mut content);
+        let invalid = invalid.into_iter().map(|range| CustomHighlight::new(range.start, range.end)).collect();
+        Line::builder()
+            .with_content(content)
+            .with_custom_highlights(invalid_bytes_highlights(invalid))
+            .with_start(start)
+            .with_end(end - bytes_trimmed)
+            .with_line_no(line_no)
+            .build()
+    };
+
+    if n > Integer::from(0) {
+        let mut start = memrchr(b'\n', &bytes[..offset]).map(|p| p + 1).unwrap_or(0);
+        let mut delimiter_seen = false;
+        loop {
+            match memchr(b'\n', &bytes[start..]) {
+                Some(p) => {
+                    let line_no = current_no.clone();
+                    current_no = current_no.map(|no| no + 1);
+                    data.push(line(start, start + p, line_no));
+                    if data.len() == max_lines {
+                        break;
+                    }
+                    start += p + 1;
+                    delimiter_seen = true;
+                },
+                None => {
+                    if start < len || delimiter_seen {
+                        data.push(line(start, len, current_no.clone()));
+                    }
+                    break;
+                }
+            }
+        }
+    } else {
+        let mut end = memchr(b'\n', &bytes[offset..]).map(|p| offset + p).unwrap_or(len);
+        let mut delimiter_seen = false;
+        loop {
+            match memrchr(b'\n', &bytes[..end]) {
+                Some(p) => {
+                    let line_no = current_no.clone();
+                    current_no = current_no.map(|no| no.saturating_sub(1));
+                    data.push(line(p + 1, end, line_no));
+                    if data.len() == max_lines {
+                        break;
+                    }
+                    end = p;
+                    delimiter_seen = true;
+                },
+                None => {
+                    if end > 0 || delimiter_seen {
+                        data.push(line(0, end, current_no.clone()));
+                    }
+                    break;
+                }
+            }
+        }
+        data.reverse();
+    }
+
+    let s = data.first().map(|segment| segment.start);
+    let e = data.last().map(|segment| segment.end);
+    Data {
+        lines: data,
+        start: s,
+        end: e,
+    }
+}
+
+fn invalid_bytes_highlights(invalid: Vec<CustomHighlight>) -> CustomHighlights {
+    let mut highlights = CustomHighlights::new();
+    if !invalid.is_empty() {
+        highlights.insert(INVALID_BYTES_CUSTOM_DATA_KEY, invalid);
+    }
+    highlights
+}
+
+/// Number of the line that contains `offset`
+fn line_number_at(line_registry: Option<Arc<LineRegistryImpl>>, offset: Integer) -> LineNumberResult {
+    line_registry
+        .ok_or(LineNumberMissingReason::LineNumberingTurnedOff)
+        .and_then(|r| {
+            let interval = Interval::closed_open(0.into(), offset);
+            r.count(&interval).map_err(LineNumberMissingReason::Delegate)
+        })
+}
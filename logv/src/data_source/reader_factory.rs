@@ -1,12 +1,20 @@
+use crate::data_source::concat_backend::ConcatReader;
+use crate::data_source::transcoded::TranscodedReader;
 use crate::data_source::filtered::filtered_reader::FilteredReader;
 use crate::data_source::line_source_holder::{ConcreteLineSourceHolder, LineSourceHolder};
 use crate::data_source::LineSourceBackend;
+use crate::data_source::mmap_backend::MappedFile;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::{Cursor, Read, Seek, SeekFrom};
 
 pub trait ReaderFactory: Send + Sync + Debug {
     fn new_reader(&self) -> std::io::Result<UniversalReadSeek>;
+
+    /// Whole content, if it is available in memory, e.g. mapped
+    fn mapped(&self) -> Option<MappedFile> {
+        None
+    }
 }
 
 pub trait HasReaderFactory {
@@ -16,6 +24,9 @@ pub trait HasReaderFactory {
 pub enum BasicReadSeek {
     FileBased(File),
     CursorBased(Cursor<Vec<u8>>),
+    MmapBased(Cursor<MappedFile>),
+    ConcatBased(ConcatReader),
+    TranscodedBased(TranscodedReader),
 }
 
 impl Read for BasicReadSeek {
@@ -23,6 +34,9 @@ impl Read for BasicReadSeek {
         match self {
             BasicReadSeek::FileBased(inner) => inner.read(buf),
             BasicReadSeek::CursorBased(inner) => inner.read(buf),
+            BasicReadSeek::MmapBased(inner) => inner.read(buf),
+            BasicReadSeek::ConcatBased(inner) => inner.read(buf),
+            BasicReadSeek::TranscodedBased(inner) => inner.read(buf),
         }
     }
 }
@@ -32,6 +46,9 @@ impl Seek for BasicReadSeek {
         match self {
             BasicReadSeek::FileBased(inner) => inner.seek(pos),
             BasicReadSeek::CursorBased(inner) => inner.seek(pos),
+            BasicReadSeek::MmapBased(inner) => inner.seek(pos),
+            BasicReadSeek::ConcatBased(inner) => inner.seek(pos),
+            BasicReadSeek::TranscodedBased(inner) => inner.seek(pos),
         }
     }
 }
@@ -77,6 +94,15 @@ impl HasReaderFactory for ConcreteLineSourceHolder {
             ConcreteLineSourceHolder::ConstantBased(inner) => {
                 inner.backend.reader_factory()
             }
+            ConcreteLineSourceHolder::MmapBased(inner) => {
+                inner.backend.reader_factory()
+            }
+            ConcreteLineSourceHolder::ConcatBased(inner) => {
+                inner.backend.reader_factory()
+            }
+            ConcreteLineSourceHolder::TranscodedBased(inner) => {
+                inner.backend.reader_factory()
+            }
         }
     }
 }
@@ -122,6 +148,73 @@ pub mod file {
     }
 }
 
+pub mod mmap {
+    use crate::data_source::mmap_backend::MappedFile;
+    use crate::data_source::reader_factory::{BasicReadSeek, ReaderFactory, UniversalReadSeek};
+    use std::io::Cursor;
+
+    #[derive(Clone, Debug)]
+    pub struct MmapReaderFactory(MappedFile);
+
+    impl ReaderFactory for MmapReaderFactory {
+        fn new_reader(&self) -> std::io::Result<UniversalReadSeek> {
+            Ok(UniversalReadSeek::Basic(BasicReadSeek::MmapBased(Cursor::new(self.0.clone()))))
+        }
+
+        fn mapped(&self) -> Option<MappedFile> {
+            Some(self.0.clone())
+        }
+    }
+
+    impl MmapReaderFactory {
+        pub fn new(mapped: MappedFile) -> Self {
+            Self(mapped)
+        }
+    }
+}
+
+pub mod concat {
+    use std::sync::Arc;
+    use crate::data_source::concat_backend::{ConcatReader, Part};
+    use crate::data_source::reader_factory::{BasicReadSeek, ReaderFactory, UniversalReadSeek};
+
+    #[derive(Clone, Debug)]
+    pub struct ConcatReaderFactory(Arc<Vec<Part>>);
+
+    impl ReaderFactory for ConcatReaderFactory {
+        fn new_reader(&self) -> std::io::Result<UniversalReadSeek> {
+            Ok(UniversalReadSeek::Basic(BasicReadSeek::ConcatBased(ConcatReader::new(self.0.clone()))))
+        }
+    }
+
+    impl ConcatReaderFactory {
+        pub fn new(parts: Arc<Vec<Part>>) -> Self {
+            Self(parts)
+        }
+    }
+}
+
+pub mod transcoded {
+    use std::sync::Arc;
+    use crate::data_source::reader_factory::{BasicReadSeek, ReaderFactory, UniversalReadSeek};
+    use crate::data_source::transcoded::{TranscodedFile, TranscodedReader};
+
+    #[derive(Clone, Debug)]
+    pub struct TranscodedReaderFactory(Arc<TranscodedFile>);
+
+    impl ReaderFactory for TranscodedReaderFactory {
+        fn new_reader(&self) -> std::io::Result<UniversalReadSeek> {
+            Ok(UniversalReadSeek::Basic(BasicReadSeek::TranscodedBased(TranscodedReader::new(self.0.clone()))))
+        }
+    }
+
+    impl TranscodedReaderFactory {
+        pub fn new(file: Arc<TranscodedFile>) -> Self {
+            Self(file)
+        }
+    }
+}
+
 pub mod filtered {
     use std::fmt::{Debug, Formatter};
     use crate::data_source::filtered::filtered_reader::FilteredReader;
@@ -0,0 +1,216 @@
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::RwLock;
+
+use chrono::NaiveDateTime;
+
+use crate::data_source::line_registry::{LineRegistryError, LineRegistryResult};
+use crate::utils;
+use crate::utils::utf8::decode_substituting;
+
+/// Distance between indexed lines, in bytes
+const INDEX_STEP: u64 = 64 * 1024;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Sample {
+    /// Start of the line
+    offset: u64,
+    date: NaiveDateTime,
+    /// Latest date among this and previous samples
+    max_date: NaiveDateTime,
+}
+
+/// Sparse index of dates: the first line with a date after every [INDEX_STEP] bytes. Lookups
+/// narrow the search for a date down to a couple of steps, which are looked through
+/// line by line.
+///
+/// Dates are not required to be sorted. Lookups rely on the latest date seen so far, which never
+/// decreases, so lines logged out of order only make the looked through region longer.
+#[derive(Debug)]
+pub struct TimeIndex {
+    samples: RwLock<Vec<Sample>>,
+    length: u64,
+    step: u64,
+    crawled: AtomicU64,
+    is_complete: AtomicBool,
+}
+
+impl TimeIndex {
+    pub fn new(length: u64) -> Self {
+        Self::with_step(length, INDEX_STEP)
+    }
+
+    fn with_step(length: u64, step: u64) -> Self {
+        TimeIndex {
+            samples: RwLock::new(vec![]),
+            length,
+            step,
+            crawled: AtomicU64::new(0),
+            is_complete: AtomicBool::new(false),
+        }
+    }
+
+    /// Reads dates of lines at every step of the first `length` bytes of `reader`. Dates are
+    /// taken from lines by `parse`. `bytes_processed` is called with number of bytes indexed.
+    pub fn build<R, P, F, G>(&self, reader: &mut BufReader<R>, parse: P, is_interrupted: F, bytes_processed: G) -> LineRegistryResult<()>
+    where
+        R: Read + Seek,
+        P: Fn(&str) -> Option<NaiveDateTime>,
+        F: Fn() -> bool,
+        G: Fn(u64),
+    {
+        let mut buf = vec![];
+        // offset right after the last line read
+        let mut position = 0;
+        let mut step_start = 0;
+        while step_start < self.length {
+            if is_interrupted() {
+                return Err(LineRegistryError::Cancelled);
+            }
+            if step_start > position {
+                // skip the rest of the line the step starts in
+                reader.seek(SeekFrom::Start(step_start - 1))?;
+                buf.clear();
+                position = step_start - 1 + reader.read_until(b'\n', &mut buf)? as u64;
+            } else {
+                reader.seek(SeekFrom::Start(position))?;
+            }
+            let step_end = (step_start + self.step).min(self.length);
+            while position < step_end {
+                buf.clear();
+                let n = reader.read_until(b'\n', &mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                let offset = position;
+                position += n as u64;
+                let mut line = decode_substituting(&buf);
+                utils::trim_newline(&mut line);
+                if let Some(date) = parse(&line) {
+                    self.push(offset, date);
+                    break;
+                }
+            }
+            step_start = step_end.max(position);
+            self.crawled.store(step_start.min(self.length), Relaxed);
+            bytes_processed(step_start.min(self.length));
+        }
+        self.is_complete.store(true, Relaxed);
+        Ok(())
+    }
+
+    fn push(&self, offset: u64, date: NaiveDateTime) {
+        let mut samples = self.samples.write().unwrap();
+        let max_date = samples.last().map_or(date, |last| last.max_date.max(date));
+        samples.push(Sample { offset, date, max_date });
+    }
+
+    /// Offset to look for `date` from: the looked for line is the first one dated `date` or
+    /// later, or the last one dated earlier, if there is no such line. Indexed lines before the
+    /// offset are dated earlier than `date`, lines between them are not checked and may be dated
+    /// later if they are logged out of order. `None`, if the index has not reached the date yet
+    /// or there are no dates.
+    pub fn find(&self, date: NaiveDateTime) -> Option<u64> {
+        let samples = self.samples.read().unwrap();
+        let i = samples.partition_point(|sample| sample.max_date < date);
+        if i == samples.len() && !(self.is_complete() && i > 0) {
+            return None;
+        }
+        Some(i.checked_sub(1).map_or(0, |i| samples[i].offset))
+    }
+
+    /// Number of bytes indexed so far
+    pub fn get_crawled(&self) -> u64 {
+        self.crawled.load(Relaxed)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.is_complete.load(Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Cursor};
+
+    use chrono::{Duration, NaiveDateTime};
+    use spectral::prelude::*;
+
+    use super::TimeIndex;
+
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+    fn date(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, FORMAT).unwrap()
+    }
+
+    fn parse(line: &str) -> Option<NaiveDateTime> {
+        line.get(..19).and_then(|s| NaiveDateTime::parse_from_str(s, FORMAT).ok())
+    }
+
+    /// A line per second, with a line without date after every one. Returns content and offsets
+    /// of dated lines.
+    fn content(dates: &[NaiveDateTime]) -> (String, Vec<usize>) {
+        let mut content = String::new();
+        let mut offsets = vec![];
+        for date in dates {
+            offsets.push(content.len());
+            content += &format!("{} message\n  details\n", date.format(FORMAT));
+        }
+        (content, offsets)
+    }
+
+    fn build(content: &str, step: u64) -> TimeIndex {
+        let index = TimeIndex::with_step(content.len() as u64, step);
+        let mut reader = BufReader::new(Cursor::new(content.as_bytes()));
+        index.build(&mut reader, parse, || false, |_| {}).unwrap();
+        index
+    }
+
+    #[test]
+    fn test_find_in_sorted() {
+        let start = date("2024-01-31 10:00:00");
+        let dates: Vec<_> = (0..1000).map(|i| start + Duration::seconds(i)).collect();
+        let (content, offsets) = content(&dates);
+        let index = build(&content, 1024);
+        assert_that!(index.is_complete()).is_true();
+        assert_that!(index.samples.read().unwrap().len()).is_greater_than(20);
+
+        for i in [1, 10, 500, 999] {
+            let offset = index.find(dates[i]).unwrap() as usize;
+            assert_that!(offset).is_less_than(offsets[i]);
+            assert_that!(offsets[i] - offset).is_less_than_or_equal_to(2 * 1024);
+        }
+        assert_that!(index.find(start - Duration::hours(1))).is_equal_to(Some(0));
+        let last = index.find(start + Duration::hours(1)).unwrap() as usize;
+        assert_that!(last).is_less_than_or_equal_to(*offsets.last().unwrap());
+    }
+
+    #[test]
+    fn test_find_in_unsorted() {
+        let start = date("2024-01-31 10:00:00");
+        // lines 400-449 are logged a minute late
+        let dates: Vec<_> = (0..1000)
+            .map(|i| start + Duration::seconds(i) - Duration::seconds(if (400..450).contains(&i) { 60 } else { 0 }))
+            .collect();
+        let (content, offsets) = content(&dates);
+        let index = build(&content, 512);
+
+        let target = start + Duration::seconds(420);
+        let offset = index.find(target).unwrap() as usize;
+        let first_later = dates.iter().position(|date| *date >= target).unwrap();
+        assert_that!(offset).is_less_than_or_equal_to(offsets[first_later]);
+        let skipped = offsets.iter().take_while(|o| **o < offset).count();
+        assert_that!(dates[..skipped].iter().all(|date| *date < target)).is_true();
+    }
+
+    #[test]
+    fn test_not_reached() {
+        let (content, _) = content(&[date("2024-01-31 10:00:00")]);
+        let index = TimeIndex::with_step(content.len() as u64, 1024);
+        assert_that!(index.find(date("2024-01-31 10:00:00"))).is_none();
+        let index = build(&content, 1024);
+        assert_that!(index.find(date("2024-01-31 11:00:00"))).is_equal_to(Some(0));
+    }
+}
@@ -53,6 +53,36 @@ pub fn skip_token<R: Read + Seek>(offset: Integer, direction: Direction, f: &mut
     }
 }
 
+/// Part of a line: either a word or a run of delimiters
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub is_delimiter: bool,
+}
+
+/// Splits `s` into words and runs of delimiters. Uses the same notion of delimiter as
+/// [skip_token].
+pub fn tokenize(s: &str) -> Vec<Token> {
+    let mut result = vec![];
+    let mut start = 0;
+    let mut current = None;
+    for (i, ch) in s.char_indices() {
+        let d = is_delimiter(&ch);
+        match current {
+            Some(c) if c != d => {
+                result.push(Token { text: &s[start..i], is_delimiter: c });
+                start = i;
+            },
+            _ => {},
+        }
+        current = Some(d);
+    }
+    if let Some(c) = current {
+        result.push(Token { text: &s[start..], is_delimiter: c });
+    }
+    result
+}
+
 enum State {
     InToken,
     InWhitespace,
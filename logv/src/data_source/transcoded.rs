@@ -0,0 +1,459 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, RwLock};
+
+use memmap2::Mmap;
+
+use crate::data_source::delimiter::Delimiter;
+use crate::data_source::encoding::Encoding;
+use crate::data_source::reader_factory::transcoded::TranscodedReaderFactory;
+use crate::data_source::reader_factory::ReaderFactory;
+use crate::data_source::LineSourceBackend;
+
+/// Number of file bytes decoded at once
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Number of decoded chunks kept in memory by a file
+const CACHED_CHUNKS: usize = 8;
+
+/// Number of bytes after the end of a chunk looked through for a delimiter that the end cuts
+const DELIMITER_MARGIN: usize = 4 * 1024;
+
+#[derive(Debug, Copy, Clone)]
+struct Chunk {
+    /// Offset in the file
+    file_start: u64,
+    /// Offset in decoded content
+    start: u64,
+}
+
+/// Chunks found so far
+struct Index {
+    chunks: Vec<Chunk>,
+    /// Offset in the file where the last chunk ends
+    file_end: u64,
+    /// Length of decoded content of the chunks
+    length: u64,
+}
+
+/// File in other encoding than UTF-8 or with records that are not delimited by line breaks, read
+/// as UTF-8 lines (see [Delimiter::convert]). The file is mapped into memory and decoded by chunks
+/// on demand, so that only a few of them are in memory at a time.
+///
+/// Where chunks start in decoded content is found out by a pass over the file, which may be done
+/// in background while the beginning is read, see [Self::open_first_chunk]. Until then the
+/// content looks like a growing file.
+///
+/// Offsets in decoded content differ from offsets in the file, see [Self::to_file_offset] and
+/// [Self::to_decoded_offset]. Cloning is cheap.
+#[derive(Clone, Debug)]
+pub struct TranscodedBackend(Arc<TranscodedFile>);
+
+pub struct TranscodedFile {
+    path: PathBuf,
+    // empty files cannot be mapped
+    mmap: Option<Mmap>,
+    encoding: Encoding,
+    delimiter: Delimiter,
+    chunk_size: usize,
+    index: RwLock<Index>,
+    // recently decoded chunks by index, the latest first
+    cache: Mutex<VecDeque<(usize, Arc<Vec<u8>>)>>,
+}
+
+impl TranscodedBackend {
+    /// Maps the file at `path` and finds out where its chunks start in decoded content, which
+    /// takes a pass over the whole file. `bytes_processed` is called with the number of bytes
+    /// passed.
+    pub fn open<F, G>(
+        path: &Path,
+        encoding: Encoding,
+        delimiter: Delimiter,
+        is_interrupted: F,
+        bytes_processed: G,
+    ) -> std::io::Result<Self>
+    where
+        F: Fn() -> bool,
+        G: Fn(u64),
+    {
+        Self::open_with_chunk_size(path, encoding, delimiter, CHUNK_SIZE, is_interrupted, bytes_processed)
+    }
+
+    /// Maps the file at `path` and decodes only its first chunk, so that the beginning can be
+    /// shown at once. The rest is found out by [Self::index_rest].
+    pub fn open_first_chunk(path: &Path, encoding: Encoding, delimiter: Delimiter) -> std::io::Result<Self> {
+        let backend = Self::map(path, encoding, delimiter, CHUNK_SIZE)?;
+        backend.0.index_next_chunk();
+        Ok(backend)
+    }
+
+    fn open_with_chunk_size<F, G>(
+        path: &Path,
+        encoding: Encoding,
+        delimiter: Delimiter,
+        chunk_size: usize,
+        is_interrupted: F,
+        bytes_processed: G,
+    ) -> std::io::Result<Self>
+    where
+        F: Fn() -> bool,
+        G: Fn(u64),
+    {
+        let backend = Self::map(path, encoding, delimiter, chunk_size)?;
+        backend.index_rest(is_interrupted, bytes_processed)?;
+        Ok(backend)
+    }
+
+    fn map(path: &Path, encoding: Encoding, delimiter: Delimiter, chunk_size: usize) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() {
+            return Err(std::io::Error::new(ErrorKind::Unsupported, "Only regular files can be decoded"));
+        }
+        // SAFETY: the map is read-only. Concurrent modification of the file is accepted the same
+        // way as for plain reads.
+        let mmap = match metadata.len() {
+            0 => None,
+            _ => Some(unsafe { Mmap::map(&file)? }),
+        };
+        let bom_len = encoding.bom_len(mmap.as_deref().unwrap_or_default());
+        let file = TranscodedFile {
+            path: path.to_path_buf(),
+            mmap,
+            encoding,
+            delimiter,
+            chunk_size,
+            index: RwLock::new(Index { chunks: vec![], file_end: bom_len as u64, length: 0 }),
+            cache: Mutex::new(VecDeque::new()),
+        };
+        Ok(TranscodedBackend(Arc::new(file)))
+    }
+
+    /// Finds out where the chunks that are not indexed yet start in decoded content, which
+    /// takes a pass over the rest of the file. `bytes_processed` is called with the offset in
+    /// the file reached so far.
+    pub fn index_rest<F, G>(&self, is_interrupted: F, bytes_processed: G) -> std::io::Result<()>
+    where
+        F: Fn() -> bool,
+        G: Fn(u64),
+    {
+        while let Some(file_end) = self.0.index_next_chunk() {
+            if is_interrupted() {
+                return Err(std::io::Error::new(ErrorKind::Interrupted, "Decoding is cancelled"));
+            }
+            bytes_processed(file_end);
+        }
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0.path
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.0.encoding
+    }
+
+    /// Length of the file, as opposed to the length of decoded content
+    pub fn file_length(&self) -> u64 {
+        self.0.bytes().len() as u64
+    }
+
+    /// Offset of the char that contains decoded `offset` in the file
+    pub fn to_file_offset(&self, offset: u64) -> u64 {
+        self.0.to_file_offset(offset)
+    }
+
+    /// Offset in decoded content of the char that contains byte at `file_offset`
+    pub fn to_decoded_offset(&self, file_offset: u64) -> u64 {
+        self.0.to_decoded_offset(file_offset)
+    }
+}
+
+impl Index {
+    fn chunk_index(&self, offset: u64) -> Option<usize> {
+        self.chunks.partition_point(|chunk| chunk.start <= offset).checked_sub(1)
+    }
+
+    /// Range of the file taken by the `i`-th chunk
+    fn file_range(&self, i: usize) -> Range<usize> {
+        let end = self.chunks.get(i + 1).map_or(self.file_end, |next| next.file_start);
+        self.chunks[i].file_start as usize..end as usize
+    }
+}
+
+impl TranscodedFile {
+    fn bytes(&self) -> &[u8] {
+        self.mmap.as_deref().unwrap_or_default()
+    }
+
+    fn length(&self) -> u64 {
+        self.index.read().unwrap().length
+    }
+
+    /// Adds the chunk that follows the indexed ones. Returns where it ends in the file, or
+    /// `None` if the whole file is indexed.
+    fn index_next_chunk(&self) -> Option<u64> {
+        let bytes = self.bytes();
+        let (file_start, start) = {
+            let index = self.index.read().unwrap();
+            (index.file_end as usize, index.length)
+        };
+        if file_start >= bytes.len() {
+            return None;
+        }
+        let encoding = self.encoding;
+        let window = &bytes[file_start..(file_start + self.chunk_size).min(bytes.len())];
+        let len = match encoding.whole_chars_len(window) {
+            // the last char is cut by the end of the file
+            0 => window.len(),
+            len if file_start + window.len() == bytes.len() => window.len().max(len),
+            len => {
+                let margin = &bytes[file_start..(file_start + len + DELIMITER_MARGIN).min(bytes.len())];
+                let margin = &margin[..encoding.whole_chars_len(margin)];
+                self.delimiter.align_to_records(encoding, margin, len)
+            }
+        };
+        let mut length = start;
+        self.delimiter.convert(encoding, &bytes[file_start..file_start + len], |_, converted| length += converted.len() as u64);
+        let file_end = (file_start + len) as u64;
+        let mut index = self.index.write().unwrap();
+        index.chunks.push(Chunk { file_start: file_start as u64, start });
+        index.file_end = file_end;
+        index.length = length;
+        Some(file_end)
+    }
+
+    fn decoded_chunk(&self, i: usize, file_range: Range<usize>) -> Arc<Vec<u8>> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((_, content)) = cache.iter().find(|(j, _)| *j == i) {
+            return Arc::clone(content);
+        }
+        let bytes = &self.bytes()[file_range];
+        let mut content = String::with_capacity(bytes.len());
+        self.delimiter.convert(self.encoding, bytes, |_, converted| content.push_str(converted));
+        let content = Arc::new(content.into_bytes());
+        cache.push_front((i, Arc::clone(&content)));
+        cache.truncate(CACHED_CHUNKS);
+        content
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> usize {
+        let (i, start, file_range) = {
+            let index = self.index.read().unwrap();
+            let Some(i) = index.chunk_index(offset).filter(|_| offset < index.length) else { return 0; };
+            (i, index.chunks[i].start, index.file_range(i))
+        };
+        let content = self.decoded_chunk(i, file_range);
+        let from = (offset - start) as usize;
+        let n = buf.len().min(content.len() - from);
+        buf[..n].copy_from_slice(&content[from..from + n]);
+        n
+    }
+
+    fn to_file_offset(&self, offset: u64) -> u64 {
+        let (chunk, file_range) = {
+            let index = self.index.read().unwrap();
+            let Some(i) = index.chunk_index(offset) else {
+                return self.encoding.bom_len(self.bytes()) as u64;
+            };
+            (index.chunks[i], index.file_range(i))
+        };
+        let bytes = &self.bytes()[file_range];
+        let mut position = chunk.start;
+        let mut result = None;
+        self.delimiter.convert(self.encoding, bytes, |file_offset, converted| {
+            if result.is_none() && position + converted.len() as u64 > offset {
+                result = Some(file_offset);
+            }
+            position += converted.len() as u64;
+        });
+        chunk.file_start + result.unwrap_or(bytes.len()) as u64
+    }
+
+    fn to_decoded_offset(&self, file_offset: u64) -> u64 {
+        let (chunk, file_range) = {
+            let index = self.index.read().unwrap();
+            if file_offset >= index.file_end {
+                return index.length;
+            }
+            let Some(i) = index.chunks.partition_point(|chunk| chunk.file_start <= file_offset).checked_sub(1) else {
+                return 0;
+            };
+            (index.chunks[i], index.file_range(i))
+        };
+        let local_offset = (file_offset - chunk.file_start) as usize;
+        let mut position = chunk.start;
+        let mut result = position;
+        self.delimiter.convert(self.encoding, &self.bytes()[file_range], |char_offset, converted| {
+            // chars converted to nothing belong to the previous char
+            if char_offset <= local_offset && !converted.is_empty() {
+                result = position;
+            }
+            position += converted.len() as u64;
+        });
+        result
+    }
+}
+
+impl std::fmt::Debug for TranscodedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "TranscodedFile({:?} in {} delimited by {}, {} bytes decoded)",
+            self.path, self.encoding, self.delimiter, self.length()
+        )
+    }
+}
+
+impl LineSourceBackend<TranscodedReader> for TranscodedBackend {
+    fn get_length(&self) -> u64 {
+        self.0.length()
+    }
+
+    fn new_reader(&self) -> BufReader<TranscodedReader> {
+        BufReader::new(TranscodedReader::new(Arc::clone(&self.0)))
+    }
+
+    fn reader_factory(&self) -> Box<dyn ReaderFactory> {
+        Box::new(TranscodedReaderFactory::new(Arc::clone(&self.0)))
+    }
+}
+
+/// Reads decoded content of a [TranscodedBackend]
+pub struct TranscodedReader {
+    file: Arc<TranscodedFile>,
+    position: u64,
+}
+
+impl TranscodedReader {
+    pub fn new(file: Arc<TranscodedFile>) -> Self {
+        TranscodedReader { file, position: 0 }
+    }
+}
+
+impl Read for TranscodedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.file.read_at(self.position, buf);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for TranscodedReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.file.length().checked_add_signed(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(std::io::Error::new(ErrorKind::InvalidInput, "Seek to a negative offset")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::{Read, Seek, SeekFrom};
+
+    use spectral::prelude::*;
+
+    use crate::data_source::delimiter::Delimiter;
+    use crate::data_source::encoding::Encoding;
+    use crate::data_source::LineSourceBackend;
+    use crate::test_extensions::TempFile;
+    use super::TranscodedBackend;
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(s.encode_utf16().flat_map(|u| u.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn test_read_by_chunks() {
+        let text = "Grüße 😀\nzweite Zeile\n".repeat(10);
+        let file = TempFile::new("transcoded-read", utf16le(&text));
+        // chunks are cut in the middle of surrogate pairs
+        let backend = TranscodedBackend::open_with_chunk_size(file.path(), Encoding::Utf16Le, Delimiter::NewLine, 15, || false, |_| {}).unwrap();
+        assert_that!(backend.get_length()).is_equal_to(text.len() as u64);
+
+        let mut content = String::new();
+        backend.new_reader().read_to_string(&mut content).unwrap();
+        assert_that!(content).is_equal_to(text.clone());
+
+        let mut reader = backend.new_reader();
+        let mut buf = [0u8; 6];
+        reader.seek(SeekFrom::Start(text.find('z').unwrap() as u64)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_that!(&buf).is_equal_to(b"zweite");
+    }
+
+    #[test]
+    fn test_index_by_chunks() {
+        let file = TempFile::new("transcoded-incremental", b"ab\ncd\nef\n");
+        let backend = TranscodedBackend::map(file.path(), Encoding::Latin1, Delimiter::NewLine, 3).unwrap();
+        backend.0.index_next_chunk();
+        assert_that!(backend.get_length()).is_equal_to(3);
+        let mut text = String::new();
+        backend.new_reader().read_to_string(&mut text).unwrap();
+        assert_that!(text.as_str()).is_equal_to("ab\n");
+        // offsets that are not indexed yet are at the end
+        assert_that!(backend.to_decoded_offset(7)).is_equal_to(3);
+
+        let processed = RefCell::new(vec![]);
+        backend.index_rest(|| false, |b| processed.borrow_mut().push(b)).unwrap();
+        assert_that!(processed.into_inner()).is_equal_to(vec![6, 9]);
+        text.clear();
+        backend.new_reader().read_to_string(&mut text).unwrap();
+        assert_that!(text.as_str()).is_equal_to("ab\ncd\nef\n");
+    }
+
+    #[test]
+    fn test_file_offsets() {
+        let file = TempFile::new("transcoded-offsets", b"a\xfcb\nc\xdf\n");
+        let backend = TranscodedBackend::open_with_chunk_size(file.path(), Encoding::Latin1, Delimiter::NewLine, 4, || false, |_| {}).unwrap();
+        // "aüb\ncß\n"
+        assert_that!(backend.get_length()).is_equal_to(9);
+        let file_offsets: Vec<u64> = (0..=9).map(|offset| backend.to_file_offset(offset)).collect();
+        assert_that!(file_offsets).is_equal_to(vec![0, 1, 1, 2, 3, 4, 5, 5, 6, 7]);
+        let offsets: Vec<u64> = (0..=7).map(|offset| backend.to_decoded_offset(offset)).collect();
+        assert_that!(offsets).is_equal_to(vec![0, 1, 3, 4, 5, 6, 8, 9]);
+
+        let utf16 = TempFile::new("transcoded-offsets-utf16", utf16le("ab\n"));
+        let backend = TranscodedBackend::open(utf16.path(), Encoding::Utf16Le, Delimiter::NewLine, || false, |_| {}).unwrap();
+        assert_that!(backend.to_file_offset(1)).is_equal_to(4);
+        assert_that!(backend.to_decoded_offset(5)).is_equal_to(1);
+    }
+
+    #[test]
+    fn test_delimited() {
+        let content = b"a\n---\nb\nc\n---\nd\x1a\xff";
+        let file = TempFile::new("transcoded-delimited", content);
+        let delimiter = "/\\n-{3,}\\n/".parse().unwrap();
+        // chunk ends are moved past delimiters they cut
+        let backend = TranscodedBackend::open_with_chunk_size(file.path(), Encoding::Utf8, delimiter, 3, || false, |_| {}).unwrap();
+        let mut text = String::new();
+        backend.new_reader().read_to_string(&mut text).unwrap();
+        assert_that!(text.as_str()).is_equal_to("a\nb\u{2424}c\nd\u{1a}\u{FFFD}");
+        // "c" is at 6 in the text and at 8 in the file
+        assert_that!(backend.to_file_offset(6)).is_equal_to(8);
+        assert_that!(backend.to_decoded_offset(8)).is_equal_to(6);
+        // the rest of chars of a delimiter belong to its first char
+        assert_that!(backend.to_decoded_offset(3)).is_equal_to(1);
+
+        let backend = TranscodedBackend::open(file.path(), Encoding::Utf8, Delimiter::Byte(0x1a), || false, |_| {}).unwrap();
+        let mut text = String::new();
+        backend.new_reader().read_to_string(&mut text).unwrap();
+        assert_that!(text.as_str()).is_equal_to("a\u{2424}---\u{2424}b\u{2424}c\u{2424}---\u{2424}d\n\u{FFFD}");
+    }
+}
@@ -0,0 +1,292 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Utc};
+use fluent_integer::Integer;
+use human_bytes::human_bytes;
+use regex::Regex;
+use uuid::Uuid;
+
+use crate::args::{Args, Command};
+use crate::background_process::task_context::TaskContext;
+use crate::data_source::concat_backend::ConcatBackend;
+use crate::data_source::delimiter::Delimiter;
+use crate::data_source::encoding::Encoding;
+use crate::data_source::file_set;
+use crate::data_source::filtered::FilteredLineSource;
+use crate::data_source::line_source_holder::ConcreteLineSourceHolder;
+use crate::data_source::mmap_backend::MmapBackend;
+use crate::data_source::transcoded::TranscodedBackend;
+use crate::data_source::{FileBackend, LineSource, LineSourceImpl};
+use crate::model::abstract_go_to_model::{GoToError, GoToResult};
+use crate::model::date_format_model::PinnedDateFormats;
+use crate::model::date_input::parse_date_input;
+use crate::model::go_to_date_model::{bin_search, last_date, DATE_FORMAT};
+use crate::model::guess_date_format::{guess_date_format, DateSample, GuessContext, KnownDateFormat};
+use crate::model::time_delta::format_delta;
+use crate::model::time_zone::DisplayZone;
+
+/// File opened without UI, the same way it is opened in the viewer
+struct Source {
+    path: PathBuf,
+    line_source: ConcreteLineSourceHolder,
+    // file looked through to guess date format
+    date_sample: DateSample,
+}
+
+/// Runs `command` over the file given in `args` and prints results to `out`
+pub fn run(command: &Command, args: &Args, out: &mut dyn Write) -> anyhow::Result<()> {
+    let file = args.file.as_deref().ok_or(anyhow!("File is not specified, use --file"))?;
+    let source = open(Path::new(file), args)?;
+    let mut out = BufWriter::new(out);
+    match command {
+        Command::Grep { pattern, regex, context, count } =>
+            grep(source, pattern, *regex, *context, *count, &mut out)?,
+        Command::Slice { from, to } =>
+            slice(source, from.as_deref(), to.as_deref(), &guess_context(args, file), &mut out)?,
+        Command::Stats =>
+            stats(source, &guess_context(args, file), &mut out)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn open(path: &Path, args: &Args) -> anyhow::Result<Source> {
+    let path = std::path::absolute(path)?;
+    if file_set::is_file_set(&path) {
+        let file_set = file_set::list_files(&path)?;
+        // results go to stdout, so the warning does not mix with them
+        if let Some(warning) = file_set.skipped_warning() {
+            eprintln!("Warning: {}", warning);
+        }
+        let backend = ConcatBackend::new(&file_set.files)?;
+        let date_sample = DateSample::File(backend.parts().last().map(|part| part.path.clone()).unwrap_or_else(|| path.clone()));
+        let line_source = ConcreteLineSourceHolder::from(LineSourceImpl::new(backend));
+        return Ok(Source { path, line_source, date_sample });
+    }
+    if !path.is_file() {
+        bail!("{:?} is not found", path);
+    }
+    let encoding = match args.encoding {
+        Some(encoding) => encoding,
+        None => Encoding::detect_file(&path)?,
+    };
+    let delimiter = match args.delimiter.clone() {
+        Some(delimiter) => delimiter,
+        None => Delimiter::detect_file(&path)?,
+    };
+    if encoding.needs_decoding() || !delimiter.is_new_line() {
+        let description = format!("Failed to decode {:?} as {} delimited by {}", path, encoding, delimiter);
+        let backend = TranscodedBackend::open(&path, encoding, delimiter, || false, |_| {})
+            .with_context(|| description)?;
+        let line_source = ConcreteLineSourceHolder::from(LineSourceImpl::new(backend.clone()));
+        return Ok(Source { path, line_source, date_sample: DateSample::Transcoded(backend) });
+    }
+    let line_source = if args.mmap {
+        ConcreteLineSourceHolder::from(LineSourceImpl::new(MmapBackend::new(&path)?))
+    } else {
+        ConcreteLineSourceHolder::from(LineSourceImpl::<File, FileBackend>::from_file_name(path.clone()))
+    };
+    Ok(Source { date_sample: DateSample::File(path.clone()), path, line_source })
+}
+
+fn guess_context(args: &Args, file: &str) -> GuessContext {
+    let time = std::fs::metadata(file)
+        .and_then(|m| m.created())
+        .unwrap_or(SystemTime::now());
+    let dt: DateTime<Utc> = time.into();
+    GuessContext::with_year(dt.year() as u16)
+        .with_zones(args.timezone.unwrap_or_default(), DisplayZone::Original)
+}
+
+/// Date format pinned for the file in the viewer, or the guessed one
+fn date_format(source: &Source) -> anyhow::Result<&'static KnownDateFormat> {
+    PinnedDateFormats::load().get(&source.path)
+        .or_else(|| guess_date_format(source.date_sample.clone()))
+        .ok_or(anyhow!("Date format of {:?} is not recognized", source.path))
+}
+
+fn grep(source: Source, pattern: &str, is_regex: bool, context: u8, count: bool, out: &mut dyn Write) -> anyhow::Result<()> {
+    // as in grep, only matching lines are counted
+    let context = if count { 0 } else { context };
+    let filtered = if is_regex {
+        FilteredLineSource::with_regex(source.line_source, &Regex::new(pattern)?, context)
+    } else {
+        FilteredLineSource::with_substring(source.line_source, pattern, context)
+    };
+    let mut reader = filtered.reader_factory().new_reader()?;
+    if count {
+        let mut buf = vec![0; 64 * 1024];
+        let mut lines = 0_u64;
+        let mut last = b'\n';
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            lines += buf[..n].iter().filter(|b| **b == b'\n').count() as u64;
+            last = buf[n - 1];
+        }
+        if last != b'\n' {
+            lines += 1;
+        }
+        writeln!(out, "{}", lines)?;
+    } else {
+        std::io::copy(&mut reader, out)?;
+    }
+    Ok(())
+}
+
+/// Prints lines from the first one dated `from` or later up to the last one dated `to` or
+/// earlier. The first line is found by the same bisection as in go-to-date dialog, so lines
+/// before it are not read.
+fn slice(mut source: Source, from: Option<&str>, to: Option<&str>, guess_ctx: &GuessContext, out: &mut dyn Write) -> anyhow::Result<()> {
+    let known_date_format = date_format(&source)?;
+    let reference = last_date(&mut source.line_source, known_date_format, guess_ctx);
+    let parse = |input: &str| parse_date_input(input, reference, Utc::now(), guess_ctx)
+        .or_else(|err| known_date_format.parse(input, guess_ctx).ok_or(err))
+        .with_context(|| format!("Failed to parse date {:?}", input));
+    let from = from.map(parse).transpose()?;
+    let to = to.map(parse).transpose()?;
+
+    let mut offset = match from {
+        Some(from) => match bin_search(from, &mut source.line_source, known_date_format, *guess_ctx, &mut task_context()) {
+            Ok(offset) => offset,
+            Err(GoToError::NotReachable) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        },
+        None => Integer::from(0),
+    };
+    let mut started = from.is_none();
+    while let Some(line) = source.line_source.read_next_line(offset) {
+        let date = known_date_format.parse(&line.content, guess_ctx);
+        if to.zip(date).is_some_and(|(to, date)| date > to) {
+            break;
+        }
+        // if no line is dated `from`, the search stops at the last line before it
+        started = started || from.zip(date).is_some_and(|(from, date)| date >= from);
+        if started {
+            writeln!(out, "{}", line.content)?;
+        }
+        offset = line.end + 1;
+    }
+    Ok(())
+}
+
+fn stats(mut source: Source, guess_ctx: &GuessContext, out: &mut dyn Write) -> anyhow::Result<()> {
+    let known_date_format = date_format(&source).ok();
+    let mut lines = 0_u64;
+    let mut dated_lines = 0_u64;
+    let mut first: Option<NaiveDateTime> = None;
+    let mut previous: Option<NaiveDateTime> = None;
+    // longest time between adjacent dated lines and the line number after it
+    let mut longest_gap: Option<(Duration, u64)> = None;
+    let mut offset = Integer::from(0);
+    while let Some(line) = source.line_source.read_next_line(offset) {
+        lines += 1;
+        offset = line.end + 1;
+        let Some(date) = known_date_format.and_then(|kdf| kdf.parse(&line.content, guess_ctx)) else { continue; };
+        dated_lines += 1;
+        first.get_or_insert(date);
+        if let Some(previous) = previous {
+            let gap = date - previous;
+            if longest_gap.is_none_or(|(longest, _)| gap > longest) {
+                longest_gap = Some((gap, lines));
+            }
+        }
+        previous = Some(date);
+    }
+
+    let size = source.line_source.get_length().as_u64();
+    writeln!(out, "File: {}", source.path.display())?;
+    writeln!(out, "Size: {} ({} bytes)", human_bytes(size as f64), size)?;
+    writeln!(out, "Lines: {}", lines)?;
+    let Some(known_date_format) = known_date_format else {
+        writeln!(out, "Date format: not recognized")?;
+        return Ok(());
+    };
+    writeln!(out, "Date format: {}", known_date_format.get_definition())?;
+    writeln!(out, "Lines with date: {}", dated_lines)?;
+    if let (Some(first), Some(last)) = (first, previous) {
+        writeln!(out, "First date: {}", first.format(DATE_FORMAT))?;
+        writeln!(out, "Last date: {}", last.format(DATE_FORMAT))?;
+        writeln!(out, "Time span: {}", format_delta(last - first))?;
+    }
+    if let Some((gap, line_no)) = longest_gap {
+        writeln!(out, "Longest gap: {} before line {}", format_delta(gap), line_no)?;
+    }
+    Ok(())
+}
+
+fn task_context() -> TaskContext<(), GoToResult> {
+    let (sender, _) = crossbeam_channel::unbounded();
+    let (_, interrupt_receiver) = crossbeam_channel::unbounded();
+    TaskContext::new(sender, interrupt_receiver, Uuid::new_v4())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use clap::Parser;
+    use spectral::prelude::*;
+
+    use crate::args::Args;
+    use crate::test_extensions::TempFile;
+    use super::run;
+
+    const LOG: &str = "2024-01-31 10:00:00 INFO started\n\
+        2024-01-31 10:00:05 ERROR failed\n\
+        \tat line 1\n\
+        2024-01-31 10:01:00 INFO retrying\n\
+        2024-01-31 10:05:00 ERROR failed again\n\
+        2024-01-31 10:06:00 INFO stopped\n";
+
+    fn run_command(path: &Path, command: &[&str]) -> String {
+        let args = Args::parse_from(["logv", "-f", path.to_str().unwrap()].iter().chain(command));
+        let mut out = vec![];
+        run(args.command.as_ref().unwrap(), &args, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_grep() {
+        let file = TempFile::new("headless-grep.log", LOG);
+        assert_that!(run_command(file.path(), &["grep", "ERROR"]).as_str())
+            .is_equal_to("2024-01-31 10:00:05 ERROR failed\n2024-01-31 10:05:00 ERROR failed again\n");
+        assert_that!(run_command(file.path(), &["grep", "-r", "ERROR|stopped", "-c"]).as_str()).is_equal_to("3\n");
+        assert_that!(run_command(file.path(), &["grep", "ERROR", "-C", "1", "-c"]).as_str()).is_equal_to("2\n");
+    }
+
+    #[test]
+    fn test_slice() {
+        let file = TempFile::new("headless-slice.log", LOG);
+        assert_that!(run_command(file.path(), &["slice", "--from", "2024-01-31 10:00:01", "--to", "2024-01-31 10:01:00"]).as_str())
+            .is_equal_to("2024-01-31 10:00:05 ERROR failed\n\tat line 1\n2024-01-31 10:01:00 INFO retrying\n");
+        assert_that!(run_command(file.path(), &["slice", "--from", "-1m"]).as_str())
+            .is_equal_to("2024-01-31 10:05:00 ERROR failed again\n2024-01-31 10:06:00 INFO stopped\n");
+    }
+
+    #[test]
+    fn test_stats() {
+        let file = TempFile::new("headless-stats.log", LOG);
+        let stats = run_command(file.path(), &["stats"]);
+        assert_that!(stats.as_str()).contains("Lines: 6\n");
+        assert_that!(stats.as_str()).contains("Lines with date: 5\n");
+        assert_that!(stats.as_str()).contains("First date: 2024-01-31 10:00:00\n");
+        assert_that!(stats.as_str()).contains("Last date: 2024-01-31 10:06:00\n");
+    }
+
+    #[test]
+    fn test_dates_of_decoded_file() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(LOG.encode_utf16().flat_map(|u| u.to_le_bytes()));
+        let file = TempFile::new("headless-utf16.log", bytes);
+        let stats = run_command(file.path(), &["stats"]);
+        assert_that!(stats.as_str()).contains("Lines with date: 5\n");
+        assert_that!(stats.as_str()).contains("Last date: 2024-01-31 10:06:00\n");
+    }
+}
@@ -1,14 +1,20 @@
 use crate::app_theme::app_theme::AppTheme;
+use crate::config::HighlightRule;
 use crate::highlight::custom_highlighter::create_filtered_highlighter;
 use crate::highlight::date_highlight::create_date_highlighter;
 use crate::highlight::highlight::Highlighter;
+use crate::highlight::logfmt_highlighter::create_logfmt_highlighter;
+use crate::highlight::rule_highlighter::create_rule_highlighter;
 use crate::highlight::search_highlighter::create_search_highlighter;
 use crate::highlight::style_with_priority::StyleWithPriority;
+use regex::Regex;
 use std::rc::Rc;
 
-pub fn cursive_highlighters(app_theme: &AppTheme) -> Vec<Rc<dyn Highlighter<StyleWithPriority> + 'static>> {
+pub fn cursive_highlighters(app_theme: &AppTheme, rules: &[(Regex, HighlightRule)]) -> Vec<Rc<dyn Highlighter<StyleWithPriority> + 'static>> {
     vec![
         Rc::new(create_date_highlighter(app_theme)),
+        Rc::new(create_logfmt_highlighter(app_theme)),
+        Rc::new(create_rule_highlighter(app_theme, rules)),
         Rc::new(create_search_highlighter(app_theme)),
         Rc::new(create_filtered_highlighter(app_theme)),
     ]
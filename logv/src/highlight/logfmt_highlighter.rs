@@ -0,0 +1,37 @@
+use crate::highlight::highlight::{Highlight, Highlighter};
+use crate::highlight::style_with_priority::StyleWithPriority;
+use crate::model::logfmt;
+use crate::model::model::RootModel;
+use crate::model::rendered::LineRender;
+use crate::app_theme::app_theme::{AppTheme, AppThemeKey};
+
+pub struct LogfmtHighlighter<T> {
+    key_payload: T,
+    value_payload: T,
+}
+
+impl <T> LogfmtHighlighter<T> {
+    pub fn new(key_payload: T, value_payload: T) -> Self {
+        LogfmtHighlighter {
+            key_payload,
+            value_payload,
+        }
+    }
+}
+
+impl <T> Highlighter<T> for LogfmtHighlighter<T> where T: Clone {
+    fn process(&self, line: &LineRender, _model: &RootModel) -> Vec<Highlight<T>> {
+        let mut result = vec![];
+        for pair in logfmt::parse_if_logfmt(&line.content).unwrap_or_default() {
+            result.push(Highlight::new(pair.key_start, pair.key_end(), self.key_payload.clone()));
+            if pair.value_end > pair.value_start {
+                result.push(Highlight::new(pair.value_start, pair.value_end, self.value_payload.clone()));
+            }
+        }
+        result
+    }
+}
+
+pub fn create_logfmt_highlighter(app_theme: &AppTheme) -> LogfmtHighlighter<StyleWithPriority> {
+    LogfmtHighlighter::new(app_theme[AppThemeKey::LogfmtKey], app_theme[AppThemeKey::LogfmtValue])
+}
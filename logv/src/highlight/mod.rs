@@ -4,3 +4,5 @@ pub mod style_with_priority;
 mod date_highlight;
 mod search_highlighter;
 mod custom_highlighter;
+mod logfmt_highlighter;
+mod rule_highlighter;
@@ -0,0 +1,38 @@
+use regex::Regex;
+
+use crate::app_theme::app_theme::AppTheme;
+use crate::config::HighlightRule;
+use crate::highlight::highlight::{Highlight, Highlighter};
+use crate::highlight::style_with_priority::StyleWithPriority;
+use crate::model::model::RootModel;
+use crate::model::rendered::LineRender;
+
+/// Highlights matches of user-defined rules
+pub struct RuleHighlighter<T> {
+    rules: Vec<(Regex, T)>,
+}
+
+impl<T> RuleHighlighter<T> {
+    pub fn new(rules: Vec<(Regex, T)>) -> Self {
+        Self {
+            rules,
+        }
+    }
+}
+
+impl<T: Clone> Highlighter<T> for RuleHighlighter<T> {
+    fn process(&self, line_render: &LineRender, _model: &RootModel) -> Vec<Highlight<T>> {
+        self.rules.iter()
+            .flat_map(|(regex, payload)| regex.find_iter(&line_render.content)
+                .map(|m| Highlight::new(m.start(), m.end(), payload.clone())))
+            .collect()
+    }
+}
+
+/// Rules whose style cannot be resolved in `app_theme` are skipped
+pub fn create_rule_highlighter(app_theme: &AppTheme, rules: &[(Regex, HighlightRule)]) -> RuleHighlighter<StyleWithPriority> {
+    let rules = rules.iter()
+        .filter_map(|(regex, rule)| app_theme.rule_style(rule).ok().map(|style| (regex.clone(), style)))
+        .collect();
+    RuleHighlighter::new(rules)
+}
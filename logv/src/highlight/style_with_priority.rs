@@ -25,6 +25,14 @@ impl StyleWithPriority {
     pub fn get_style(&self) -> Style {
         self.style
     }
+
+    /// Same priorities with another style
+    pub fn with_style(self, style: Style) -> Self {
+        StyleWithPriority {
+            style,
+            ..self
+        }
+    }
 }
 
 impl Add for StyleWithPriority {
@@ -61,8 +69,11 @@ pub enum StylePriority {
     Selection,
     LineNumber,
     Date,
+    Field,
+    Rule,
     Search,
     Filter,
+    Bookmark,
 }
 
 impl Into<(u8, u8)> for StylePriority {
@@ -73,8 +84,11 @@ impl Into<(u8, u8)> for StylePriority {
             StylePriority::Selection => (0x01, 0xfe),
             StylePriority::LineNumber => (0x01, 0xff),
             StylePriority::Date => (0x77, 0x77),
+            StylePriority::Field => (0x70, 0x70),
+            StylePriority::Rule => (0x78, 0x78),
             StylePriority::Search => (0x90, 0x90),
             StylePriority::Filter => (0x80, 0x80),
+            StylePriority::Bookmark => (0xa0, 0xa0),
         }
     }
 }
\ No newline at end of file
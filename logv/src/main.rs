@@ -15,7 +15,7 @@ use std::fs::OpenOptions;
 use std::panic;
 use std::time::Duration;
 
-use anyhow::{anyhow, bail};
+use anyhow::{anyhow, Context};
 use clap::Parser;
 use crossbeam_channel::{Receiver, Sender, unbounded};
 use cursive::{Cursive, CursiveRunner, View};
@@ -36,14 +36,21 @@ use crate::app_theme::app_theme::{AppTheme, AppThemeName};
 use crate::application_metrics::ApplicationRecorder;
 use crate::args::Args;
 use crate::background_process::background_process_registry::BackgroundProcessRegistry;
+use crate::config::AppConfig;
+use crate::model::guess_date_format::set_user_formats;
 use crate::model::help_model::HelpModelEvent;
 use crate::model::metrics_model::MetricsHolder;
 use crate::model::model::{ModelEvent, RootModel};
 use crate::model::model::ModelEvent::*;
+use crate::profiles::{find_profile, OS_PROFILE};
 use crate::search::searcher::SearchError;
 use crate::shared::Shared;
 use crate::ui::bgp_status::handle_bgp_event;
+use crate::ui::clustering_dialog::handle_clustering_model_event;
+use crate::ui::date_format_dialog::handle_date_format_model_event;
 use crate::ui::error_dialog::build_error_dialog;
+use crate::ui::field_table_dialog::handle_field_table_model_event;
+use crate::ui::details_dialog::handle_details_model_event;
 use crate::ui::filter_dialog::handle_filter_dialog_model_event;
 use crate::ui::go_to_date_dialog::build_go_to_date_dialog;
 use crate::ui::go_to_dialog::build_go_to_dialog;
@@ -53,9 +60,10 @@ use crate::ui::metrics_dialog::handle_metrics_model_event;
 use crate::ui::open_file_dialog::{build_open_file_dialog, handle_open_file_model_event};
 use crate::ui::progress_dialog::handle_progress_model_event;
 use crate::ui::search_ui::build_search_ui;
+use crate::ui::theme_dialog::handle_theme_model_event;
 use crate::ui::ui_elements::UIElementName;
 use crate::ui::with_root_model::WithRootModel;
-use crate::utils::stat;
+use crate::utils::{stat, GraphemeRender};
 
 mod model;
 mod ui;
@@ -77,28 +85,48 @@ mod args;
 mod profiles;
 mod bounded_vec_deque;
 mod app_theme;
+mod config;
+mod headless;
 
 const METRIC_APP_CYCLE: &str = "app_cycle";
 const PROFILER_FLUSH_PERIOD: Duration = Duration::from_secs(5);
 
 fn main() -> anyhow::Result<()> {
 	let args = Args::parse();
+	// explicitly given config must be valid, while the default one is just reported
+	let (config, config_error) = match &args.config {
+		Some(path) => (AppConfig::load_from(path)?, None),
+		None => AppConfig::load()
+			.map_or_else(|err| (AppConfig::default(), Some(err)), |config| (config, None)),
+	};
+	let config = config.merge(&args);
+	if let Some(command) = &args.command {
+		init_config(&config);
+		return headless::run(command, &args, &mut std::io::stdout().lock());
+	}
 
-	init_logging(&args)?;
+	init_logging(&config)?;
+	if let Some(err) = config_error {
+		log::warn!("Failed to load config: {:?}", err);
+	}
+	for err in &config.errors {
+		log::warn!("Invalid config: {}", err);
+	}
 	init_panic_hook();
 	let metrics = init_metrics();
 	init_profiler(&args);
+	init_config(&config);
 
-	let Ok((app_theme, theme)) = AppTheme::load(AppThemeName::SolarizedLight) else {
-		bail!("Failed to load theme");
-	};
+	let theme_name = config.theme.clone().unwrap_or(AppThemeName::SolarizedLight);
+	let (app_theme, theme) = AppTheme::load(&theme_name)
+		.with_context(|| format!("Failed to load theme {}", theme_name))?;
 	let (sender, receiver) = unbounded();
-	let (model, background_process_registry) = create_model(&args, sender, metrics.ok(), app_theme);
+	let (model, background_process_registry) = create_model(&args, config, sender, metrics.ok(), app_theme);
 
     run_ui(receiver, model, background_process_registry, theme)
 }
 
-fn init_logging(args: &Args) -> std::io::Result<()> {
+fn init_logging(config: &AppConfig) -> std::io::Result<()> {
 	let file = OpenOptions::new().write(true).open("./logv.log");
 	if let Ok(file) = file {
 		file.set_len(0)?;
@@ -109,7 +137,7 @@ fn init_logging(args: &Args) -> std::io::Result<()> {
 		.build("./logv.log")
 		.unwrap();
 
-	let level = match args.log_level.as_ref() {
+	let level = match config.log_level.as_ref() {
 		Some(loglevel) => *loglevel,
 		None => LevelFilter::Info
 	};
@@ -154,6 +182,13 @@ fn init_metrics() -> anyhow::Result<MetricsHolder> {
 		})
 }
 
+fn init_config(config: &AppConfig) {
+	for err in set_user_formats(&config.date_formats) {
+		log::warn!("{}", err);
+	}
+	GraphemeRender::set_tab_width(config.tab_width);
+}
+
 fn init_profiler(args: &Args) {
 	if let Some(port) = args.profiler_port.as_ref() {
 		let server_addr = format!("127.0.0.1:{}", port);
@@ -166,12 +201,28 @@ fn init_profiler(args: &Args) {
 
 fn create_model(
 	args: &Args,
+	config: AppConfig,
 	sender: Sender<ModelEvent>,
 	metrics_holder: Option<MetricsHolder>,
 	app_theme: AppTheme,
 ) -> (Shared<RootModel>, Shared<BackgroundProcessRegistry>) {
+	let profile = config.profile.as_deref()
+		.and_then(|name| find_profile(name)
+			.or_else(|| {
+				log::warn!("Unknown key profile {}", name);
+				None
+			}))
+		.unwrap_or_else(|| OS_PROFILE.clone());
 	let background_process_registry = Shared::new(BackgroundProcessRegistry::new());
-	let model = RootModel::new(sender, background_process_registry.clone(), metrics_holder, app_theme);
+	let model = RootModel::new(sender, background_process_registry.clone(), metrics_holder, app_theme, &profile);
+	for err in model.get_mut_ref().apply_config(config) {
+		log::warn!("{}", err);
+	}
+	model.get_mut_ref().set_use_mmap(args.mmap);
+	model.get_mut_ref().set_encoding(args.encoding);
+	model.get_mut_ref().set_delimiter(args.delimiter);
+	model.get_mut_ref().set_assumed_zone(args.timezone.unwrap_or_default());
+	model.get_mut_ref().set_gap_threshold(chrono::Duration::seconds(args.gap_threshold.into()));
 	model.get_mut_ref().set_file_name(args.file.as_deref());
 	(model, background_process_registry)
 }
@@ -360,11 +411,59 @@ fn handle_model_update(app: &mut CursiveRunner<Cursive>, model: Shared<RootModel
 			callback(app);
 			Ok(true)
 		},
+		ClusteringEvent(evt) => {
+			let callback = {
+				let root_model = model.get_mut_ref();
+				let model = root_model.get_clustering_model();
+				handle_clustering_model_event(&model, evt)
+			};
+			callback(app);
+			Ok(true)
+		},
+		DateFormatEvent(evt) => {
+			let callback = {
+				let root_model = model.get_mut_ref();
+				handle_date_format_model_event(&root_model, evt)
+			};
+			callback(app);
+			Ok(true)
+		},
+		FieldTableEvent(evt) => {
+			let callback = {
+				let root_model = model.get_mut_ref();
+				let model = root_model.get_field_table_model();
+				handle_field_table_model_event(&model, evt)
+			};
+			callback(app);
+			Ok(true)
+		},
+		DetailsEvent(evt) => {
+			let callback = {
+				let root_model = model.get_mut_ref();
+				let model = root_model.get_details_model();
+				handle_details_model_event(&model, &root_model.app_theme, evt)
+			};
+			callback(app);
+			Ok(true)
+		},
 		ThemeEvent(app_theme_name) => {
-			let (app_theme, theme) = AppTheme::load(app_theme_name)
-				.map_err(|_| "Failed to load theme")?;
-			app.set_theme(theme);
-			model.get_mut_ref().app_theme = app_theme;
+			match AppTheme::load(&app_theme_name) {
+				Ok((app_theme, theme)) => {
+					app.set_theme(theme);
+					model.get_mut_ref().app_theme = app_theme;
+				}
+				Err(err) => model.get_mut_ref().set_error(Box::new(format!("{:#}", err))),
+			}
+			Ok(true)
+		},
+		ThemeDialogEvent(evt) => {
+			let callback = {
+				let root_model = model.get_mut_ref();
+				let current = root_model.app_theme.name.clone();
+				let model = root_model.get_theme_model();
+				handle_theme_model_event(&model, &current, evt)
+			};
+			callback(app);
 			Ok(true)
 		},
 		Hint(hint) => {
@@ -126,7 +126,6 @@ pub enum GoToError {
     #[error("Operation is cancelled")]
     Cancelled,
     #[error("Total source length is unknown")]
-    #[allow(dead_code)]
     LengthUnknown,
 }
 
@@ -0,0 +1,160 @@
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use anyhow::bail;
+use crossbeam_channel::Sender;
+use fluent_integer::Integer;
+
+use crate::background_process::background_process_handler::BackgroundProcessHandler;
+use crate::background_process::run_in_background::RunInBackground;
+use crate::background_process::signal::Signal;
+use crate::background_process::task_context::TaskContext;
+use crate::data_source::line_source_holder::ConcreteLineSourceHolder;
+use crate::data_source::reader_factory::{HasReaderFactory, ReaderFactory};
+use crate::model::bgp_model::BGPModel;
+use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerManager, EscapeHandlerResult};
+use crate::model::log_template::{Clusters, TemplateCollector, TemplateStats};
+use crate::model::model::{ModelEvent, RootModel};
+use crate::shared::Shared;
+use crate::utils;
+use crate::utils::event_emitter::EventEmitter;
+
+const INTERRUPT_CHECK_RATE: Duration = Duration::from_millis(100);
+
+#[derive(Debug)]
+pub enum ClusteringModelEvent {
+    Open(bool),
+    Updated,
+}
+
+pub struct ClusteringModel {
+    model_sender: Sender<ModelEvent>,
+    runner: Shared<BGPModel>,
+    is_open: bool,
+    escape_handler_manager: EscapeHandlerManager,
+    clusters: Option<Clusters>,
+    handler: Option<BackgroundProcessHandler>,
+}
+
+impl ClusteringModel {
+    pub fn new(
+        model_sender: Sender<ModelEvent>,
+        runner: Shared<BGPModel>,
+        escape_handler: Shared<CompoundEscapeHandler>,
+    ) -> Self {
+        ClusteringModel {
+            model_sender,
+            runner,
+            is_open: false,
+            escape_handler_manager: EscapeHandlerManager::new(escape_handler, Self::on_esc),
+            clusters: None,
+            handler: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn set_open(&mut self, is_open: bool) {
+        if self.is_open != is_open {
+            self.is_open = is_open;
+            self.escape_handler_manager.toggle(is_open);
+            self.emit_event(ClusteringModelEvent::Open(is_open));
+        }
+    }
+
+    /// Templates found so far. `None` means that analysis is in progress.
+    pub fn get_templates(&self) -> Option<&Vec<TemplateStats>> {
+        self.clusters.as_ref().map(|clusters| &clusters.templates)
+    }
+
+    /// Number of lines left out after the limit of templates has been reached
+    pub fn get_unclustered(&self) -> usize {
+        self.clusters.as_ref().map_or(0, |clusters| clusters.unclustered)
+    }
+
+    pub fn reset(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            handler.interrupt();
+        }
+        self.clusters = None;
+    }
+
+    /// Starts analysis of `source` unless it is already done or in progress
+    pub fn analyze(&mut self, source: &ConcreteLineSourceHolder) {
+        if self.clusters.is_some() || self.handler.is_some() {
+            return;
+        }
+        let reader_factory = source.reader_factory();
+        let file_length = source.get_length();
+        let runner = &mut *self.runner.get_mut_ref();
+        let handler = runner.background_process_builder::<(), _, anyhow::Result<Clusters>, _>()
+            .with_title("Pattern clustering")
+            .with_description("Group lines by message templates")
+            .with_task(move |ctx| collect_templates(ctx, reader_factory, file_length))
+            .with_listener(|model, signal, id| {
+                let Signal::Complete(result) = signal else { return; };
+                let mut clustering_model = model.get_clustering_model();
+                if clustering_model.handler.as_ref().map(|h| h.get_id()) != Some(id) {
+                    return;
+                }
+                clustering_model.handler = None;
+                match result {
+                    Ok(clusters) => {
+                        clustering_model.clusters = Some(clusters);
+                        clustering_model.emit_event(ClusteringModelEvent::Updated);
+                    }
+                    Err(err) => {
+                        drop(clustering_model);
+                        model.set_error(Box::new(err));
+                    }
+                }
+            })
+            .run();
+        self.handler = Some(handler);
+    }
+
+    fn emit_event(&self, evt: ClusteringModelEvent) {
+        self.model_sender.emit_event(ModelEvent::ClusteringEvent(evt));
+    }
+
+    fn on_esc(root_model: &mut RootModel) -> EscapeHandlerResult {
+        let me = &mut *root_model.get_clustering_model();
+        if me.is_open() {
+            me.set_open(false);
+            EscapeHandlerResult::Dismiss
+        } else {
+            EscapeHandlerResult::Ignore
+        }
+    }
+}
+
+fn collect_templates(
+    ctx: &mut TaskContext<(), anyhow::Result<Clusters>>,
+    reader_factory: Box<dyn ReaderFactory>,
+    file_length: Integer,
+) -> anyhow::Result<Clusters> {
+    let mut reader = BufReader::new(reader_factory.new_reader()?);
+    let mut collector = TemplateCollector::default();
+    let mut buf = vec![];
+    let mut offset = 0_u64;
+    loop {
+        if ctx.interrupted_debounced(INTERRUPT_CHECK_RATE) {
+            bail!("Pattern clustering has been cancelled");
+        }
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let mut line = String::from_utf8_lossy(&buf).to_string();
+        utils::trim_newline(&mut line);
+        collector.add(&line, offset.into());
+        offset += n as u64;
+        if file_length > 0 {
+            ctx.update_progress_u64(offset, file_length.as_u64());
+        }
+    }
+    Ok(collector.into_clusters())
+}
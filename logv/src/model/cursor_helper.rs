@@ -3,11 +3,12 @@ use std::cell::RefMut;
 use fluent_integer::Integer;
 use crate::data_source::{Direction, LineSource};
 use crate::data_source::line_source_holder::LineSourceHolder;
-use crate::model::rendered::{DataRender, LineRender};
+use crate::model::rendered::{DataRender, LineRender, LineRenderer};
 
 pub struct LineIterator<'a> {
     cache: &'a DataRender,
     datasource: RefMut<'a, LineSourceHolder>,
+    line_renderer: LineRenderer,
     direction: Direction,
     current_line: Option<Cow<'a, LineRender>>,
     line_number: Integer,
@@ -19,12 +20,14 @@ impl<'a> LineIterator<'a> {
     pub fn new(
         cache: &'a DataRender,
         datasource: RefMut<'a, LineSourceHolder>,
+        line_renderer: LineRenderer,
         direction: Direction,
         line_number: Integer,
     ) -> Self {
         LineIterator {
             cache,
             datasource,
+            line_renderer,
             direction,
             current_line: None,
             line_number,
@@ -35,19 +38,21 @@ impl<'a> LineIterator<'a> {
 
     fn read_non_cached_line_backward(&mut self) -> Option<Cow<'a, LineRender>> {
         let datasource = &mut *self.datasource;
+        let line_renderer = self.line_renderer;
         self.current_line.as_ref()
             .map(|current_line| current_line.start - 1)
             .and_then(|s| datasource.read_prev_line(s))
-            .map(LineRender::new)
+            .map(|line| line_renderer.render(line))
             .map(Cow::Owned)
     }
 
     fn read_non_cached_line_forward(&mut self) -> Option<Cow<'a, LineRender>> {
         let datasource = &mut *self.datasource;
+        let line_renderer = self.line_renderer;
         self.current_line.as_ref()
             .map(|current_line| current_line.end + 1)
             .and_then(|s| datasource.read_next_line(s))
-            .map(LineRender::new)
+            .map(|line| line_renderer.render(line))
             .map(Cow::Owned)
     }
 
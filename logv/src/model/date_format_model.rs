@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use crossbeam_channel::Sender;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::background_process::background_process_handler::BackgroundProcessHandler;
+use crate::background_process::run_in_background::RunInBackground;
+use crate::background_process::signal::Signal;
+use crate::config::config_dir;
+use crate::model::bgp_model::BGPModel;
+use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerManager, EscapeHandlerResult};
+use crate::model::guess_date_format::{find_or_create_format, rate_date_formats_in_file, DateSample, FormatRating, KnownDateFormat};
+use crate::model::model::{ModelEvent, RootModel};
+use crate::shared::Shared;
+use crate::utils::event_emitter::EventEmitter;
+
+const PINNED_FORMATS_FILE_NAME: &str = "date_formats.yaml";
+
+#[derive(Debug)]
+pub enum DateFormatModelEvent {
+    Open(bool),
+    Updated,
+}
+
+/// Lists date formats that match lines sampled from the file, so that user can pin one of them
+/// instead of the guessed one
+pub struct DateFormatModel {
+    model_sender: Sender<ModelEvent>,
+    runner: Shared<BGPModel>,
+    is_open: bool,
+    escape_handler_manager: EscapeHandlerManager,
+    ratings: Option<Vec<FormatRating>>,
+    handler: Option<BackgroundProcessHandler>,
+    pinned: PinnedDateFormats,
+}
+
+impl DateFormatModel {
+    pub fn new(
+        model_sender: Sender<ModelEvent>,
+        runner: Shared<BGPModel>,
+        escape_handler: Shared<CompoundEscapeHandler>,
+    ) -> Self {
+        DateFormatModel {
+            model_sender,
+            runner,
+            is_open: false,
+            escape_handler_manager: EscapeHandlerManager::new(escape_handler, Self::on_esc),
+            ratings: None,
+            handler: None,
+            pinned: PinnedDateFormats::load(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn set_open(&mut self, is_open: bool) {
+        if self.is_open != is_open {
+            self.is_open = is_open;
+            self.escape_handler_manager.toggle(is_open);
+            self.emit_event(DateFormatModelEvent::Open(is_open));
+        }
+    }
+
+    /// Formats that match sampled lines, the best one goes first. `None` means that analysis is
+    /// in progress.
+    pub fn get_ratings(&self) -> Option<&Vec<FormatRating>> {
+        self.ratings.as_ref()
+    }
+
+    pub fn reset(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            handler.interrupt();
+        }
+        self.ratings = None;
+    }
+
+    /// Starts rating formats against the file unless it is already done or in progress
+    pub fn analyze(&mut self, sample: DateSample) {
+        if self.ratings.is_some() || self.handler.is_some() {
+            return;
+        }
+        let runner = &mut *self.runner.get_mut_ref();
+        let handler = runner.background_process_builder::<(), _, Vec<FormatRating>, _>()
+            .with_title("Rate date formats")
+            .with_description(format!("Rate date formats for {:?}", sample.path()))
+            .with_task(move |_| rate_date_formats_in_file(sample))
+            .with_listener(|model, signal, id| {
+                let Signal::Complete(ratings) = signal else { return; };
+                let mut date_format_model = model.get_date_format_model();
+                if date_format_model.handler.as_ref().map(|h| h.get_id()) != Some(id) {
+                    return;
+                }
+                date_format_model.handler = None;
+                date_format_model.ratings = Some(ratings);
+                date_format_model.emit_event(DateFormatModelEvent::Updated);
+            })
+            .run();
+        self.handler = Some(handler);
+    }
+
+    /// Format pinned for the file, if any
+    pub fn get_pinned(&self, path: &Path) -> Option<&'static KnownDateFormat> {
+        self.pinned.get(path)
+    }
+
+    /// Pins `date_format` for the file or unpins it, if `None`. The choice is saved.
+    pub fn pin(&mut self, path: &Path, date_format: Option<&'static KnownDateFormat>) -> anyhow::Result<()> {
+        self.pinned.set(path, date_format.map(|kdf| kdf.get_definition()));
+        self.pinned.save()?;
+        self.emit_event(DateFormatModelEvent::Updated);
+        Ok(())
+    }
+
+    fn emit_event(&self, evt: DateFormatModelEvent) {
+        self.model_sender.emit_event(ModelEvent::DateFormatEvent(evt));
+    }
+
+    fn on_esc(root_model: &mut RootModel) -> EscapeHandlerResult {
+        let me = &mut *root_model.get_date_format_model();
+        if me.is_open() {
+            me.set_open(false);
+            EscapeHandlerResult::Dismiss
+        } else {
+            EscapeHandlerResult::Ignore
+        }
+    }
+}
+
+/// Date formats pinned by user, by absolute file path. Kept in `date_formats.yaml` in
+/// [config_dir]:
+/// ```yaml
+/// /var/log/app.log: "%Y-%m-%d %H:%M:%S"
+/// ```
+#[derive(Debug, Default)]
+pub struct PinnedDateFormats {
+    formats: BTreeMap<String, String>,
+}
+
+impl PinnedDateFormats {
+    pub fn load() -> Self {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            return PinnedDateFormats::default();
+        };
+        std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Self::parse(&content))
+            .unwrap_or_else(|err| {
+                log::warn!("Failed to load pinned date formats from {:?}: {:?}", path, err);
+                PinnedDateFormats::default()
+            })
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        let docs = YamlLoader::load_from_str(content)?;
+        let formats = docs.first()
+            .and_then(|doc| doc.as_hash())
+            .map(|hash| hash.iter()
+                .filter_map(|(path, date_format)| path.as_str().zip(date_format.as_str()))
+                .map(|(path, date_format)| (path.to_string(), date_format.to_string()))
+                .collect())
+            .unwrap_or_default();
+        Ok(PinnedDateFormats {
+            formats,
+        })
+    }
+
+    fn to_yaml(&self) -> anyhow::Result<String> {
+        let mut hash = yaml_rust2::yaml::Hash::new();
+        for (path, date_format) in &self.formats {
+            hash.insert(Yaml::String(path.clone()), Yaml::String(date_format.clone()));
+        }
+        let mut out = String::new();
+        YamlEmitter::new(&mut out).dump(&Yaml::Hash(hash))?;
+        Ok(out)
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or(anyhow!("Config directory is unknown"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, self.to_yaml()?)
+            .with_context(|| format!("Failed to save pinned date formats to {:?}", path))
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&'static KnownDateFormat> {
+        let definition = self.formats.get(path.to_string_lossy().as_ref())?;
+        find_or_create_format(definition)
+            .map_err(|err| log::warn!("Pinned date format of {:?} is not valid: {}", path, err))
+            .ok()
+    }
+
+    fn set(&mut self, path: &Path, definition: Option<&str>) {
+        let key = path.to_string_lossy().to_string();
+        match definition {
+            Some(definition) => self.formats.insert(key, definition.to_string()),
+            None => self.formats.remove(&key),
+        };
+    }
+
+    fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(PINNED_FORMATS_FILE_NAME))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use spectral::prelude::*;
+
+    use super::PinnedDateFormats;
+
+    #[test]
+    fn test_pinned_formats_round_trip() {
+        let mut pinned = PinnedDateFormats::default();
+        pinned.set(Path::new("/var/log/app.log"), Some("%d.%m.%Y %H:%M:%S"));
+        pinned.set(Path::new("/var/log/other.log"), Some("[%d/%m %T]"));
+        pinned.set(Path::new("/var/log/other.log"), None);
+
+        let parsed = PinnedDateFormats::parse(&pinned.to_yaml().unwrap()).unwrap();
+        assert_that!(parsed.formats).is_equal_to(pinned.formats);
+        assert_that!(parsed.get(Path::new("/var/log/app.log")).map(|kdf| kdf.get_definition()))
+            .is_equal_to(Some("%d.%m.%Y %H:%M:%S"));
+        assert_that!(parsed.get(Path::new("/var/log/other.log"))).is_none();
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::model::guess_date_format::GuessContext;
+
+/// Examples of accepted input shown in go-to-date dialog
+pub const DATE_INPUT_HINTS: &[(&str, &str)] = &[
+    ("2024-01-31 10:00:00", "date and time, ISO-8601 or 31-Jan-2024 10:00:00"),
+    ("10:00", "time on the date of the current line"),
+    ("-15m, +1h30m", "relative to the current line (s, m, h, d, w)"),
+    ("yesterday 14:00", "today, yesterday or now"),
+    ("1706695200", "Unix epoch seconds or millis, optionally prefixed with @"),
+];
+
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+    "%d-%b-%Y %H:%M:%S",
+    "%d-%b-%Y %H:%M",
+];
+
+/// Plain numbers of this length and longer are considered epoch timestamps
+const MIN_EPOCH_DIGITS: usize = 9;
+
+const TIME_FORMATS: &[&str] = &[
+    "%H:%M:%S%.f",
+    "%H:%M",
+];
+
+lazy_static! {
+    static ref RELATIVE_PART_REGEX: Regex = Regex::new(r"(\d+)\s*([smhdw])").unwrap();
+    static ref RELATIVE_REGEX: Regex = Regex::new(r"^[+-]\s*(\d+\s*[smhdw]\s*)+$").unwrap();
+}
+
+/// Parses go-to-date input into a date.
+///
+/// Time-only and relative input is resolved against `reference`, which is the date of the
+/// current line. `now`, `today` and `yesterday` are resolved against `now`. Epoch timestamps,
+/// dates with offsets and `now` are converted into the zone dates of lines are parsed in, see
+/// [GuessContext::wall_time].
+pub fn parse_date_input(
+    input: &str,
+    reference: Option<NaiveDateTime>,
+    now: DateTime<Utc>,
+    context: &GuessContext,
+) -> anyhow::Result<NaiveDateTime> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("Date is empty");
+    }
+    let need_reference = || reference.ok_or(anyhow!("Date of the current line is not recognized"));
+
+    if let Some(dt) = parse_absolute(input, context) {
+        return Ok(dt);
+    }
+    if let Some(time) = parse_time(input) {
+        return Ok(need_reference()?.date().and_time(time));
+    }
+    if RELATIVE_REGEX.is_match(input) {
+        return need_reference()?.checked_add_signed(parse_duration(input)?)
+            .ok_or(anyhow!("Date is out of range: {}", input));
+    }
+    if let Some(epoch) = input.strip_prefix('@') {
+        return parse_epoch(epoch.trim(), context);
+    }
+    if input.len() >= MIN_EPOCH_DIGITS && input.bytes().all(|b| b.is_ascii_digit()) {
+        return parse_epoch(input, context);
+    }
+
+    let lowercase = input.to_lowercase();
+    let (day, time) = lowercase.split_once(' ')
+        .map(|(day, time)| (day, Some(time.trim())))
+        .unwrap_or((lowercase.as_str(), None));
+    let now = context.wall_time(now.fixed_offset());
+    let date = match day {
+        "now" if time.is_none() => return Ok(now),
+        "today" => now.date(),
+        "yesterday" => now.date() - Duration::days(1),
+        _ => bail!("Unrecognized date: {}", input),
+    };
+    match time {
+        Some(time) => parse_time(time)
+            .map(|time| date.and_time(time))
+            .ok_or(anyhow!("Unrecognized time: {}", time)),
+        None => Ok(date.and_time(NaiveTime::MIN)),
+    }
+}
+
+fn parse_absolute(input: &str, context: &GuessContext) -> Option<NaiveDateTime> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Some(context.wall_time(dt));
+    }
+    DATE_TIME_FORMATS.iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(input, fmt).ok())
+        .or_else(|| NaiveDate::parse_from_str(input, "%Y-%m-%d").ok()
+            .map(|date| date.and_time(NaiveTime::MIN)))
+}
+
+fn parse_time(input: &str) -> Option<NaiveTime> {
+    TIME_FORMATS.iter()
+        .find_map(|fmt| NaiveTime::parse_from_str(input, fmt).ok())
+}
+
+/// Parses signed sum of durations, e.g. `-1h30m`
+fn parse_duration(input: &str) -> anyhow::Result<Duration> {
+    let sign = if input.starts_with('-') { -1 } else { 1 };
+    let out_of_range = || anyhow!("Duration is out of range: {}", input);
+    let mut result = Duration::zero();
+    for captures in RELATIVE_PART_REGEX.captures_iter(input) {
+        let n = captures[1].parse::<i64>().map_err(|_| out_of_range())? * sign;
+        let part = match &captures[2] {
+            "s" => Duration::try_seconds(n),
+            "m" => Duration::try_minutes(n),
+            "h" => Duration::try_hours(n),
+            "d" => Duration::try_days(n),
+            _ => Duration::try_weeks(n),
+        };
+        result = part.and_then(|part| result.checked_add(&part)).ok_or_else(out_of_range)?;
+    }
+    Ok(result)
+}
+
+/// Epoch timestamps of 13 digits and more are considered millis
+fn parse_epoch(input: &str, context: &GuessContext) -> anyhow::Result<NaiveDateTime> {
+    let value: i64 = input.parse()?;
+    let dt = if input.trim_start_matches('-').len() >= 13 {
+        DateTime::from_timestamp_millis(value)
+    } else {
+        DateTime::from_timestamp(value, 0)
+    };
+    dt.map(|dt| context.wall_time(dt.fixed_offset()))
+        .ok_or(anyhow!("Timestamp is out of range: {}", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDateTime};
+    use spectral::prelude::*;
+
+    use crate::model::guess_date_format::GuessContext;
+    use crate::model::time_zone::{AssumedZone, DisplayZone};
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    /// Dates of lines are in UTC unless `offset_hours` is given
+    fn context(offset_hours: i32) -> GuessContext {
+        let offset = FixedOffset::east_opt(offset_hours * 3600).unwrap();
+        GuessContext::with_year(2024).with_zones(AssumedZone::Fixed(offset), DisplayZone::Original)
+    }
+
+    fn parse_date_input(input: &str, reference: Option<NaiveDateTime>, now: NaiveDateTime) -> anyhow::Result<NaiveDateTime> {
+        super::parse_date_input(input, reference, now.and_utc(), &context(0))
+    }
+
+    #[test]
+    fn test_absolute() {
+        let now = dt("2024-02-01 12:00:00");
+        assert_that!(parse_date_input("2024-01-31 10:00:00", None, now).unwrap()).is_equal_to(dt("2024-01-31 10:00:00"));
+        assert_that!(parse_date_input("2024-01-31T10:00", None, now).unwrap()).is_equal_to(dt("2024-01-31 10:00:00"));
+        assert_that!(parse_date_input("2024-01-31T10:00:00+02:00", None, now).unwrap()).is_equal_to(dt("2024-01-31 08:00:00"));
+        assert_that!(parse_date_input("31-Jan-2024 10:00:00", None, now).unwrap()).is_equal_to(dt("2024-01-31 10:00:00"));
+        assert_that!(parse_date_input("2024-01-31", None, now).unwrap()).is_equal_to(dt("2024-01-31 00:00:00"));
+        assert_that!(parse_date_input("@1706695200", None, now).unwrap()).is_equal_to(dt("2024-01-31 10:00:00"));
+        assert_that!(parse_date_input("1706695200000", None, now).unwrap()).is_equal_to(dt("2024-01-31 10:00:00"));
+    }
+
+    #[test]
+    fn test_absolute_in_assumed_zone() {
+        let now = dt("2024-02-01 12:00:00");
+        let parse = |input: &str| super::parse_date_input(input, None, now.and_utc(), &context(2)).unwrap();
+        assert_that!(parse("2024-01-31T10:00:00+02:00")).is_equal_to(dt("2024-01-31 10:00:00"));
+        assert_that!(parse("2024-01-31T10:00:00Z")).is_equal_to(dt("2024-01-31 12:00:00"));
+        assert_that!(parse("@1706695200")).is_equal_to(dt("2024-01-31 12:00:00"));
+        assert_that!(parse("2024-01-31 10:00:00")).is_equal_to(dt("2024-01-31 10:00:00"));
+
+        let in_utc = GuessContext::with_year(2024).with_zones(AssumedZone::Fixed(FixedOffset::east_opt(7200).unwrap()), DisplayZone::Utc);
+        assert_that!(super::parse_date_input("@1706695200", None, now.and_utc(), &in_utc).unwrap()).is_equal_to(dt("2024-01-31 10:00:00"));
+    }
+
+    #[test]
+    fn test_now_in_assumed_zone() {
+        let now = dt("2024-02-01 23:00:00").and_utc();
+        let parse = |input: &str| super::parse_date_input(input, None, now, &context(2)).unwrap();
+        assert_that!(parse("now")).is_equal_to(dt("2024-02-02 01:00:00"));
+        assert_that!(parse("today")).is_equal_to(dt("2024-02-02 00:00:00"));
+        assert_that!(parse("yesterday 14:00")).is_equal_to(dt("2024-02-01 14:00:00"));
+    }
+
+    #[test]
+    fn test_relative() {
+        let now = dt("2024-02-01 12:00:00");
+        let reference = Some(dt("2024-01-31 10:00:00"));
+        assert_that!(parse_date_input("14:30", reference, now).unwrap()).is_equal_to(dt("2024-01-31 14:30:00"));
+        assert_that!(parse_date_input("-15m", reference, now).unwrap()).is_equal_to(dt("2024-01-31 09:45:00"));
+        assert_that!(parse_date_input("+1h 30m", reference, now).unwrap()).is_equal_to(dt("2024-01-31 11:30:00"));
+        assert_that!(parse_date_input("yesterday 14:00", reference, now).unwrap()).is_equal_to(dt("2024-01-31 14:00:00"));
+        assert_that!(parse_date_input("Today", reference, now).unwrap()).is_equal_to(dt("2024-02-01 00:00:00"));
+        assert_that!(parse_date_input("now", reference, now).unwrap()).is_equal_to(now);
+    }
+
+    #[test]
+    fn test_errors() {
+        let now = dt("2024-02-01 12:00:00");
+        assert_that!(parse_date_input("-15m", None, now)).is_err();
+        assert_that!(parse_date_input("10:00", None, now)).is_err();
+        assert_that!(parse_date_input("tomorrow", None, now)).is_err();
+        assert_that!(parse_date_input("yesterday 25:00", None, now)).is_err();
+        assert_that!(parse_date_input("", None, now)).is_err();
+        let reference = Some(dt("2024-01-31 10:00:00"));
+        assert_that!(parse_date_input("+99999999999999999999w", reference, now)).is_err();
+        assert_that!(parse_date_input("-9999999999999w", reference, now)).is_err();
+        assert_that!(parse_date_input("+2000000000000h 2000000000000h", reference, now)).is_err();
+    }
+}
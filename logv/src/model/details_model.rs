@@ -0,0 +1,82 @@
+use crossbeam_channel::Sender;
+
+use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerManager, EscapeHandlerResult};
+use crate::model::model::{ModelEvent, RootModel};
+use crate::model::pretty_print::Span;
+use crate::shared::Shared;
+use crate::utils::event_emitter::EventEmitter;
+
+#[derive(Debug)]
+pub enum DetailsModelEvent {
+    Open(bool),
+}
+
+/// Popup showing one record as a list of named fields followed by (pretty-printed) text
+pub struct DetailsModel {
+    model_sender: Sender<ModelEvent>,
+    is_open: bool,
+    escape_handler_manager: EscapeHandlerManager,
+    title: String,
+    fields: Vec<(String, String)>,
+    body: Vec<Span>,
+}
+
+impl DetailsModel {
+    pub fn new(model_sender: Sender<ModelEvent>, escape_handler: Shared<CompoundEscapeHandler>) -> Self {
+        DetailsModel {
+            model_sender,
+            is_open: false,
+            escape_handler_manager: EscapeHandlerManager::new(escape_handler, Self::on_esc),
+            title: String::new(),
+            fields: vec![],
+            body: vec![],
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn set_open(&mut self, is_open: bool) {
+        if self.is_open != is_open {
+            self.is_open = is_open;
+            self.escape_handler_manager.toggle(is_open);
+            self.emit_event(DetailsModelEvent::Open(is_open));
+        }
+    }
+
+    /// Replaces content and opens the popup
+    pub fn show(&mut self, title: impl ToString, fields: Vec<(String, String)>, body: Vec<Span>) {
+        self.set_open(false);
+        self.title = title.to_string();
+        self.fields = fields;
+        self.body = body;
+        self.set_open(true);
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn get_fields(&self) -> &Vec<(String, String)> {
+        &self.fields
+    }
+
+    pub fn get_body(&self) -> &Vec<Span> {
+        &self.body
+    }
+
+    fn emit_event(&self, evt: DetailsModelEvent) {
+        self.model_sender.emit_event(ModelEvent::DetailsEvent(evt));
+    }
+
+    fn on_esc(root_model: &mut RootModel) -> EscapeHandlerResult {
+        let me = &mut *root_model.get_details_model();
+        if me.is_open() {
+            me.set_open(false);
+            EscapeHandlerResult::Dismiss
+        } else {
+            EscapeHandlerResult::Ignore
+        }
+    }
+}
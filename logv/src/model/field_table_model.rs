@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use crossbeam_channel::Sender;
+use fluent_integer::Integer;
+
+use crate::data_source::LineSource;
+use crate::data_source::line_source_holder::LineSourceHolder;
+use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerManager, EscapeHandlerResult};
+use crate::model::model::{ModelEvent, RootModel};
+use crate::model::parse_rule::ParseRule;
+use crate::shared::Shared;
+use crate::utils::event_emitter::EventEmitter;
+
+/// Number of lines read from data source at once
+const PAGE_SIZE: usize = 500;
+
+#[derive(Debug)]
+pub enum FieldTableModelEvent {
+    Open(bool),
+    /// Columns are changed by a new rule
+    RuleUpdated,
+    /// Columns are shown or hidden
+    ColumnsUpdated,
+    /// Another page is read
+    RowsUpdated,
+}
+
+/// Line split into fields according to [ParseRule]
+#[derive(Clone, Debug)]
+pub struct ParsedRow {
+    pub offset: Integer,
+    pub values: Vec<String>,
+}
+
+pub struct FieldTableModel {
+    model_sender: Sender<ModelEvent>,
+    is_open: bool,
+    escape_handler_manager: EscapeHandlerManager,
+    rule_text: String,
+    rule: Option<ParseRule>,
+    hidden_columns: HashSet<usize>,
+    rows: Vec<ParsedRow>,
+    page_starts: Vec<Integer>,
+    next_page_start: Option<Integer>,
+}
+
+impl FieldTableModel {
+    pub fn new(model_sender: Sender<ModelEvent>, escape_handler: Shared<CompoundEscapeHandler>) -> Self {
+        FieldTableModel {
+            model_sender,
+            is_open: false,
+            escape_handler_manager: EscapeHandlerManager::new(escape_handler, Self::on_esc),
+            rule_text: String::new(),
+            rule: None,
+            hidden_columns: HashSet::new(),
+            rows: vec![],
+            page_starts: vec![],
+            next_page_start: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn set_open(&mut self, is_open: bool) {
+        if self.is_open != is_open {
+            self.is_open = is_open;
+            self.escape_handler_manager.toggle(is_open);
+            self.emit_event(FieldTableModelEvent::Open(is_open));
+        }
+    }
+
+    pub fn get_rule_text(&self) -> &str {
+        &self.rule_text
+    }
+
+    pub fn get_rule(&self) -> Option<&ParseRule> {
+        self.rule.as_ref()
+    }
+
+    /// Compiles `rule_text` and re-reads current page. Invalid rule keeps the current one.
+    pub fn set_rule(&mut self, rule_text: &str, ds: &mut LineSourceHolder) -> Result<(), regex::Error> {
+        let rule = ParseRule::new(rule_text)?;
+        self.rule_text = rule_text.to_string();
+        self.rule = Some(rule);
+        self.hidden_columns.clear();
+        let start = self.page_starts.pop().unwrap_or_default();
+        self.load_page(ds, start);
+        self.emit_event(FieldTableModelEvent::RuleUpdated);
+        Ok(())
+    }
+
+    pub fn is_column_visible(&self, column: usize) -> bool {
+        !self.hidden_columns.contains(&column)
+    }
+
+    pub fn set_column_visible(&mut self, column: usize, visible: bool) {
+        let changed = if visible {
+            self.hidden_columns.remove(&column)
+        } else {
+            self.hidden_columns.insert(column)
+        };
+        if changed {
+            self.emit_event(FieldTableModelEvent::ColumnsUpdated);
+        }
+    }
+
+    pub fn get_rows(&self) -> &Vec<ParsedRow> {
+        &self.rows
+    }
+
+    pub fn has_prev_page(&self) -> bool {
+        self.page_starts.len() > 1
+    }
+
+    pub fn has_next_page(&self) -> bool {
+        self.next_page_start.is_some()
+    }
+
+    /// Starts paging from `start`, forgetting previous pages
+    pub fn reset_pages(&mut self, ds: &mut LineSourceHolder, start: Integer) {
+        self.page_starts.clear();
+        self.load_page(ds, start);
+    }
+
+    pub fn next_page(&mut self, ds: &mut LineSourceHolder) {
+        if let Some(start) = self.next_page_start {
+            self.load_page(ds, start);
+        }
+    }
+
+    pub fn prev_page(&mut self, ds: &mut LineSourceHolder) {
+        if self.has_prev_page() {
+            self.page_starts.pop();
+            let start = self.page_starts.pop().unwrap_or_default();
+            self.load_page(ds, start);
+        }
+    }
+
+    fn load_page(&mut self, ds: &mut LineSourceHolder, start: Integer) {
+        let data = ds.read_lines(start, PAGE_SIZE.into());
+        self.page_starts.push(start);
+        self.next_page_start = data.end
+            .filter(|_| data.lines.len() == PAGE_SIZE)
+            .map(|end| end + 1);
+        self.rows = match &self.rule {
+            Some(rule) => data.lines.into_iter()
+                .filter_map(|line| rule.parse(&line.content)
+                    .map(|values| ParsedRow { offset: line.start, values }))
+                .collect(),
+            None => vec![],
+        };
+        self.emit_event(FieldTableModelEvent::RowsUpdated);
+    }
+
+    fn emit_event(&self, evt: FieldTableModelEvent) {
+        self.model_sender.emit_event(ModelEvent::FieldTableEvent(evt));
+    }
+
+    fn on_esc(root_model: &mut RootModel) -> EscapeHandlerResult {
+        let me = &mut *root_model.get_field_table_model();
+        if me.is_open() {
+            me.set_open(false);
+            EscapeHandlerResult::Dismiss
+        } else {
+            EscapeHandlerResult::Ignore
+        }
+    }
+}
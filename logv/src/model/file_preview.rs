@@ -0,0 +1,128 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::data_source::encoding::Encoding;
+
+/// Number of bytes read from the beginning of a file to show its preview
+const PREVIEW_SIZE: u64 = 4096;
+pub const PREVIEW_LINES: usize = 5;
+
+/// Kind of file content recognized by its first bytes
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FileKind {
+    Empty,
+    Text(Encoding),
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+    Zip,
+    Binary,
+}
+
+/// What a file looks like: its kind and first lines, if it is text
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Preview {
+    pub kind: FileKind,
+    pub lines: Vec<String>,
+}
+
+impl FileKind {
+    pub fn detect(sample: &[u8]) -> FileKind {
+        const SIGNATURES: [(&[u8], FileKind); 5] = [
+            (&[0x1F, 0x8B], FileKind::Gzip),
+            (b"BZh", FileKind::Bzip2),
+            (&[0xFD, b'7', b'z', b'X', b'Z', 0x00], FileKind::Xz),
+            (&[0x28, 0xB5, 0x2F, 0xFD], FileKind::Zstd),
+            (b"PK\x03\x04", FileKind::Zip),
+        ];
+        if sample.is_empty() {
+            return FileKind::Empty;
+        }
+        if let Some((_, kind)) = SIGNATURES.iter().find(|(signature, _)| sample.starts_with(signature)) {
+            return *kind;
+        }
+        match Encoding::detect(sample) {
+            // zero bytes are expected only in UTF-16
+            Encoding::Utf8 | Encoding::Latin1 if sample.contains(&0) => FileKind::Binary,
+            encoding => FileKind::Text(encoding),
+        }
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, FileKind::Gzip | FileKind::Bzip2 | FileKind::Xz | FileKind::Zstd | FileKind::Zip)
+    }
+}
+
+impl Display for FileKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileKind::Empty => write!(f, "empty"),
+            FileKind::Text(encoding) => write!(f, "text, {}", encoding),
+            FileKind::Gzip => write!(f, "gzip compressed"),
+            FileKind::Bzip2 => write!(f, "bzip2 compressed"),
+            FileKind::Xz => write!(f, "xz compressed"),
+            FileKind::Zstd => write!(f, "zstd compressed"),
+            FileKind::Zip => write!(f, "zip archive"),
+            FileKind::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+impl Preview {
+    pub fn read(path: &Path) -> std::io::Result<Preview> {
+        let mut sample = vec![];
+        File::open(path)?.take(PREVIEW_SIZE).read_to_end(&mut sample)?;
+        Ok(Preview::from(&sample[..]))
+    }
+}
+
+impl From<&[u8]> for Preview {
+    fn from(sample: &[u8]) -> Self {
+        let kind = FileKind::detect(sample);
+        let lines = match kind {
+            FileKind::Text(encoding) => encoding.decode(sample)
+                .lines()
+                .take(PREVIEW_LINES)
+                .map(|line| line.trim_end_matches('\r').to_string())
+                .collect(),
+            _ => vec![],
+        };
+        Preview {
+            kind,
+            lines,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use crate::data_source::encoding::Encoding;
+    use super::{FileKind, Preview, PREVIEW_LINES};
+
+    #[test]
+    fn test_detect() {
+        assert_that!(FileKind::detect(b"")).is_equal_to(FileKind::Empty);
+        assert_that!(FileKind::detect(&[0x1F, 0x8B, 0x08, 0x00])).is_equal_to(FileKind::Gzip);
+        assert_that!(FileKind::detect(b"BZh91AY&SY")).is_equal_to(FileKind::Bzip2);
+        assert_that!(FileKind::detect(b"line 1\nline 2\n")).is_equal_to(FileKind::Text(Encoding::Utf8));
+        assert_that!(FileKind::detect(&[0x7F, b'E', b'L', b'F', 0x02, 0x01, 0x00, 0x00])).is_equal_to(FileKind::Binary);
+        assert_that!(FileKind::detect(&[0x1F, 0x8B]).is_compressed()).is_true();
+    }
+
+    #[test]
+    fn test_preview() {
+        let content = (1..10).map(|i| format!("line {}\r\n", i)).collect::<String>();
+        let preview = Preview::from(content.as_bytes());
+        assert_that!(preview.lines).has_length(PREVIEW_LINES);
+        assert_that!(preview.lines[0].as_str()).is_equal_to("line 1");
+
+        let preview = Preview::from(&[0x1F, 0x8B, 0x08, 0x00][..]);
+        assert_that!(preview.kind).is_equal_to(FileKind::Gzip);
+        assert_that!(preview.lines).is_empty();
+    }
+}
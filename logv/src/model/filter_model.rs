@@ -14,6 +14,7 @@ pub struct FilterDialogModel {
     is_open: bool,
     pattern: String,
     is_regexp: bool,
+    is_field_filter: bool,
     neighbourhood: String,
     escape_handler_manager: EscapeHandlerManager,
 }
@@ -25,6 +26,7 @@ impl FilterDialogModel {
             is_open: false,
             pattern: String::new(),
             is_regexp: false,
+            is_field_filter: false,
             neighbourhood: 0.to_string(),
             escape_handler_manager: EscapeHandlerManager::new(escape_handler, Self::on_esc),
         }
@@ -54,6 +56,15 @@ impl FilterDialogModel {
         self.is_regexp
     }
 
+    /// Pattern is a logfmt condition: `key=value` or `key~regex`
+    pub fn is_field_filter(&self) -> bool {
+        self.is_field_filter
+    }
+
+    pub fn set_field_filter(&mut self, is_field_filter: bool) {
+        self.is_field_filter = is_field_filter;
+    }
+
     pub fn get_neighbourhood(&self) -> &str {
         &self.neighbourhood
     }
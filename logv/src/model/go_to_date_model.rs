@@ -1,8 +1,10 @@
 use crate::background_process::run_in_background::RunInBackground;
 use crate::background_process::task_context::TaskContext;
-use crate::data_source::line_source_holder::ConcreteLineSourceHolder;
-use crate::data_source::{Direction, FileBackend, Line, LineSourceImpl};
+use crate::data_source::line_source_holder::{ConcreteLineSourceHolder, LineSourceFactory};
+use crate::data_source::time_index::TimeIndex;
+use crate::data_source::{Direction, Line};
 use crate::model::abstract_go_to_model::{AbstractGoToModel, GoToError, GoToResult};
+use crate::model::date_input::parse_date_input;
 use crate::model::guess_date_format::{GuessContext, KnownDateFormat};
 use crate::model::model::{ModelEvent, RootModel};
 use crate::shared::Shared;
@@ -12,11 +14,11 @@ use crossbeam_channel::Sender;
 use fluent_integer::Integer;
 use log::Level;
 use std::cmp::Ordering;
-use std::path::PathBuf;
+use std::sync::Arc;
 use uuid::Uuid;
 use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerResult};
 
-pub const DATE_FORMAT: &str = "%d-%b-%Y %T";
+pub const DATE_FORMAT: &str = "%Y-%m-%d %T";
 
 pub struct GoToDateModel<R: RunInBackground> {
     go_to_model: AbstractGoToModel<R>,
@@ -58,20 +60,32 @@ impl<R: RunInBackground + 'static> GoToDateModel<R> {
         self.value = value.to_string()
     }
 
-    pub fn submit(&mut self, file_name: &str, known_date_format: &'static KnownDateFormat, guess_context: GuessContext) -> Result<(), anyhow::Error> {
-        let date = NaiveDateTime::parse_from_str(&self.value, DATE_FORMAT)?;
+    /// Searches for the date entered in the line source made by `source`. `reference` is the
+    /// date of the current line, which relative input is resolved against. If `time_index` has
+    /// reached the date, only lines near it are looked through.
+    pub fn submit(
+        &mut self,
+        source: LineSourceFactory,
+        time_index: Option<Arc<TimeIndex>>,
+        known_date_format: &'static KnownDateFormat,
+        guess_context: GuessContext,
+        reference: Option<NaiveDateTime>,
+    ) -> Result<(), anyhow::Error> {
+        // input may also be written the way dates are in the file
+        let date = parse_date_input(&self.value, reference, Utc::now(), &guess_context)
+            .or_else(|err| known_date_format.parse(&self.value, &guess_context).ok_or(err))?;
         let date_str = date.to_string();
-        let path = PathBuf::from(file_name);
         self.go_to_model.submit(Self::handle_result, move |ctx| {
-            let description = format!("Search date {} in {:?}", &date_str, path);
+            let description = format!("Search date {}", &date_str);
             measure_l(Level::Info, &description, || {
-                let total = std::fs::metadata(path.as_path())
-                    .map(|m| m.len())?;
-                if total == 0 {
+                let mut reader = source();
+                if reader.get_length() == 0 {
                     return Err(GoToError::NotReachable);
                 }
-                let reader = LineSourceImpl::new(FileBackend::new(path));
-                let result = bin_search(date, &mut ConcreteLineSourceHolder::from(reader), known_date_format, guess_context, ctx);
+                let result = match time_index.and_then(|index| index.find(date)) {
+                    Some(offset) => scan_from(date, &mut reader, offset.into(), known_date_format, &guess_context, ctx),
+                    None => bin_search(date, &mut reader, known_date_format, guess_context, ctx),
+                };
                 log::info!("Search date {} finished: {:?}", date_str, &result);
                 result
             })
@@ -100,7 +114,7 @@ impl<R: RunInBackground + 'static> GoToDateModel<R> {
 ///
 /// Best match means that the line discovered is the first line with date equal to
 /// requested `date` or the last line among those whose date is less than `date`.
-fn bin_search(
+pub fn bin_search(
     date: NaiveDateTime,
     reader: &mut ConcreteLineSourceHolder,
     known_date_format: &'static KnownDateFormat,
@@ -164,6 +178,47 @@ fn bin_search(
     Ok(line1.start)
 }
 
+/// Finds the same line as [bin_search] by looking through lines from `offset`, which all lines
+/// dated earlier than `date` are before, unless they are logged out of order. See
+/// [TimeIndex::find].
+pub fn scan_from(
+    date: NaiveDateTime,
+    reader: &mut ConcreteLineSourceHolder,
+    offset: Integer,
+    known_date_format: &'static KnownDateFormat,
+    guess_ctx: &GuessContext,
+    ctx: &mut TaskContext<(), GoToResult>,
+) -> GoToResult {
+    let mut offset = offset;
+    // last line dated earlier than `date`
+    let mut candidate: Option<Integer> = None;
+    while let Some(line) = reader.read_next_line(offset) {
+        if ctx.interrupted() {
+            return Err(GoToError::Cancelled);
+        }
+        if let Some(dt) = known_date_format.parse(&line.content, guess_ctx) {
+            match dt.cmp(&date) {
+                Ordering::Less => candidate = Some(line.start),
+                Ordering::Equal => return Ok(line.start),
+                Ordering::Greater => return Ok(candidate.unwrap_or(line.start)),
+            }
+        }
+        offset = line.end + 1;
+    }
+    candidate.ok_or(GoToError::NotReachable)
+}
+
+/// Date of the last line with a recognized date
+pub fn last_date(
+    reader: &mut ConcreteLineSourceHolder,
+    known_date_format: &'static KnownDateFormat,
+    guess_context: &GuessContext,
+) -> Option<NaiveDateTime> {
+    let total = reader.get_length();
+    take_line(reader, total - 1, -1, Direction::Backward, known_date_format, guess_context)
+        .map(|(_, dt)| dt)
+}
+
 fn are_lines_same(line1: &Line, line2: &Line) -> bool {
     line1.start == line2.start && line1.end == line2.end
 }
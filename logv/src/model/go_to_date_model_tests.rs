@@ -236,9 +236,43 @@ mod test_bin_search {
             &mut ctx)
     }
 
-    fn create_task_ctx() -> TaskContext<(), GoToResult> {
+    pub(super) fn create_task_ctx() -> TaskContext<(), GoToResult> {
         let (msg_sender, _) = crossbeam_channel::unbounded();
         let (_, interrupt_receiver) = crossbeam_channel::unbounded();
         TaskContext::new(msg_sender, interrupt_receiver, Uuid::new_v4())
     }
-}
\ No newline at end of file
+}
+
+mod test_scan_from {
+    use super::{DATE_FORMAT, TEXT, TEXT2};
+    use super::test_bin_search::create_task_ctx;
+    use crate::data_source::{LineSourceImpl, StrBackend};
+    use crate::model::abstract_go_to_model::GoToResult;
+    use crate::model::go_to_date_model::scan_from;
+    use crate::model::guess_date_format::GuessContext;
+    use chrono::NaiveDateTime;
+    use spectral::prelude::*;
+
+    #[test]
+    fn finds_same_lines_as_bin_search() {
+        assert_that!(do_scan(TEXT, 0, "2022-02-12 12:00:00")).is_ok_containing(&72.into());
+        assert_that!(do_scan(TEXT, 0, "2022-02-12 11:30:00")).is_ok_containing(&27.into());
+        assert_that!(do_scan(TEXT, 0, "2022-01-01 00:00:00")).is_ok_containing(&0.into());
+        assert_that!(do_scan(TEXT, 0, "2025-01-01 00:00:00")).is_ok_containing(&126.into());
+        assert_that!(do_scan(TEXT2, 0, "2022-02-12 10:01:00")).is_ok_containing(&28.into());
+    }
+
+    #[test]
+    fn from_offset() {
+        assert_that!(do_scan(TEXT, 27, "2022-02-12 12:00:00")).is_ok_containing(&72.into());
+        assert_that!(do_scan(TEXT, 27, "2022-02-12 11:30:00")).is_ok_containing(&27.into());
+    }
+
+    fn do_scan(text: &'static str, offset: usize, date: &'static str) -> GoToResult {
+        let mut src = LineSourceImpl::new(StrBackend::new(text)).into();
+        let guess_ctx = GuessContext::with_year(2023);
+        let mut ctx = create_task_ctx();
+        let target_date = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S").unwrap();
+        scan_from(target_date, &mut src, offset.into(), &DATE_FORMAT, &guess_ctx, &mut ctx)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::background_process::run_in_background::RunInBackground;
 use crate::data_source::line_registry::{LineRegistry, LineRegistryImpl};
 use crate::data_source::reader_factory::ReaderFactory;
+use crate::data_source::transcoded::TranscodedBackend;
 use crate::data_source::BUFFER_SIZE;
 use crate::model::abstract_go_to_model::{AbstractGoToModel, GoToError};
 use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerResult};
@@ -10,12 +11,52 @@ use anyhow::bail;
 use crossbeam_channel::Sender;
 use fluent_integer::Integer;
 use std::io::{BufReader, Read};
+use std::str::FromStr;
 use std::sync::Arc;
 
+/// Destination of go-to: a line number, a byte offset or a percentage of data length. Offsets
+/// and percentages do not need line numbers, so they are resolved instantly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GoToTarget {
+    Line(u64),
+    Offset(u64),
+    Percentage(f64),
+}
+
+impl FromStr for GoToTarget {
+    type Err = anyhow::Error;
+
+    /// Accepts a line number (`42`), an offset (`@123456` or `0x1E240`) or a percentage (`75%`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(percentage) = s.strip_suffix('%') {
+            let percentage = percentage.trim().parse::<f64>()?;
+            if !(0.0..=100.0).contains(&percentage) {
+                bail!("Percentage must be between 0 and 100")
+            }
+            return Ok(GoToTarget::Percentage(percentage));
+        }
+        let offset = s.strip_prefix('@').map(str::trim).unwrap_or(s);
+        if let Some(hex) = offset.strip_prefix("0x").or_else(|| offset.strip_prefix("0X")) {
+            return Ok(GoToTarget::Offset(u64::from_str_radix(hex, 16)?));
+        }
+        if offset.len() < s.len() {
+            return Ok(GoToTarget::Offset(offset.parse::<u64>()?));
+        }
+        let line = s.parse::<u64>()?;
+        if line < 1 {
+            bail!("Line number must not be less than 1")
+        }
+        Ok(GoToTarget::Line(line))
+    }
+}
+
 pub struct GoToLineModel<R: RunInBackground> {
     go_to_model: AbstractGoToModel<R>,
     value: String,
     line_registry: Option<Arc<LineRegistryImpl>>,
+    // offsets are entered as file offsets, which differ from offsets in decoded content
+    transcoded: Option<TranscodedBackend>,
     warning: Option<String>,
 }
 
@@ -36,6 +77,7 @@ impl<R: RunInBackground> GoToLineModel<R> {
             go_to_model,
             value: String::new(),
             line_registry: None,
+            transcoded: None,
             warning: None,
         }
     }
@@ -60,6 +102,10 @@ impl<R: RunInBackground> GoToLineModel<R> {
         self.line_registry = line_registry;
     }
 
+    pub fn set_transcoded(&mut self, transcoded: Option<TranscodedBackend>) {
+        self.transcoded = transcoded;
+    }
+
     pub fn set_warning(&mut self, warning: Option<impl ToString>) {
         self.warning = warning.map(|arg| arg.to_string());
     }
@@ -73,10 +119,29 @@ impl<R: RunInBackground> GoToLineModel<R> {
         reader_factory: Box<dyn ReaderFactory>,
         total: Option<Integer>
     ) -> Result<(), anyhow::Error> {
-        let line = self.value.parse::<u64>()?;
-        if line < 1 {
-            bail!("Line number must not be less than 1")
-        }
+        let line = match self.value.parse::<GoToTarget>()? {
+            GoToTarget::Line(line) => line,
+            GoToTarget::Offset(offset) => {
+                let total = match &self.transcoded {
+                    Some(transcoded) => Some(transcoded.file_length().into()),
+                    None => total,
+                };
+                if let Some(total) = total.filter(|total| *total < offset) {
+                    bail!("Offset {} is beyond the end of data ({} bytes)", offset, total)
+                }
+                let offset = self.transcoded.as_ref().map_or(offset, |transcoded| transcoded.to_decoded_offset(offset));
+                self.set_is_open(false);
+                self.go_to_model.complete(Ok(offset.into()))?;
+                return Ok(());
+            },
+            GoToTarget::Percentage(percentage) => {
+                let total = total.ok_or(GoToError::LengthUnknown)?;
+                let offset = (total.as_u64() as f64 * percentage / 100.0) as u64;
+                self.set_is_open(false);
+                self.go_to_model.complete(Ok(offset.into()))?;
+                return Ok(());
+            },
+        };
 
         if let Some(line_registry) = &self.line_registry {
             let offset = line_registry.find_offset_by_line_number(line - 1);
@@ -145,3 +210,23 @@ impl<R: RunInBackground> GoToLineModel<R> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::GoToTarget;
+
+    #[test]
+    fn test_parse_go_to_target() {
+        assert_that!("42".parse::<GoToTarget>().unwrap()).is_equal_to(GoToTarget::Line(42));
+        assert_that!("@123456".parse::<GoToTarget>().unwrap()).is_equal_to(GoToTarget::Offset(123456));
+        assert_that!("0x1E240".parse::<GoToTarget>().unwrap()).is_equal_to(GoToTarget::Offset(0x1E240));
+        assert_that!("@0x1e240".parse::<GoToTarget>().unwrap()).is_equal_to(GoToTarget::Offset(0x1E240));
+        assert_that!(" 75% ".parse::<GoToTarget>().unwrap()).is_equal_to(GoToTarget::Percentage(75.0));
+        assert_that!("0".parse::<GoToTarget>()).is_err();
+        assert_that!("101%".parse::<GoToTarget>()).is_err();
+        assert_that!("@".parse::<GoToTarget>()).is_err();
+        assert_that!("0xZZ".parse::<GoToTarget>()).is_err();
+    }
+}
@@ -1,43 +1,181 @@
-use crate::data_source::{FileBackend, LineSource, LineSourceImpl};
-use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use crate::data_source::transcoded::TranscodedBackend;
+use crate::data_source::{FileBackend, LineSource, LineSourceBackend, LineSourceImpl};
+use fluent_integer::Integer;
+use std::cmp::Reverse;
+use crate::model::time_zone::{parse_zone_suffix, AssumedZone, DisplayZone};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use lazy_static::lazy_static;
 use phf::{phf_map, phf_ordered_set};
-use regex::{Captures, Match, Regex};
+use regex::{Match, Regex};
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
-use std::path::PathBuf;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use std::time::SystemTime;
 
 const N: usize = 100;
+/// Number of points evenly spread over the file, where [N] lines are taken from
+const SAMPLE_POINTS: usize = 5;
+
+/// File that lines are sampled from to guess its date format
+#[derive(Clone, Debug)]
+pub enum DateSample {
+    File(PathBuf),
+    /// File in other encoding than UTF-8 or with other delimiters than line breaks, which lines
+    /// are taken from decoded content
+    Transcoded(TranscodedBackend),
+}
+
+impl DateSample {
+    pub fn path(&self) -> &Path {
+        match self {
+            DateSample::File(path) => path,
+            DateSample::Transcoded(backend) => backend.path(),
+        }
+    }
+}
+
+pub fn guess_date_format(sample: DateSample) -> Option<&'static KnownDateFormat> {
+    rate_date_formats_in_file(sample)
+        .into_iter()
+        .next()
+        .map(|rating| rating.format)
+}
 
-pub fn guess_date_format(path: PathBuf) -> Option<&'static KnownDateFormat> {
-    let time = std::fs::metadata(&path)
+/// Ratings of formats that match lines sampled from the file, the best one goes first
+pub fn rate_date_formats_in_file(sample: DateSample) -> Vec<FormatRating> {
+    let time = std::fs::metadata(sample.path())
         .and_then(|m| m.created())
         .unwrap_or(SystemTime::now());
     let dt: DateTime<Utc> = time.into();
     let ctx = GuessContext::with_year(dt.year() as u16);
-    let mut line_source = LineSourceImpl::<File, FileBackend>::from_file_name(path);
-    guess_date_format0(&mut line_source, &ctx)
+    let lines = match sample {
+        DateSample::File(path) => sample_all(&mut LineSourceImpl::<File, FileBackend>::from_file_name(path)),
+        DateSample::Transcoded(backend) => sample_all(&mut LineSourceImpl::new(backend)),
+    };
+    rate_date_formats(&lines, &ctx)
 }
 
-fn guess_date_format0(line_source: &mut dyn LineSource, ctx: &GuessContext) -> Option<&'static KnownDateFormat> {
-    let data = line_source.read_lines(0.into(), N.into());
-    FORMATS
-        .iter()
-        .map(|known_date_format| {
-            let rating: usize = data
-                .lines
-                .iter()
-                .map(|line| known_date_format.guess(&line.content, ctx).0)
-                .sum();
-            (known_date_format, rating)
+fn sample_all<R: Read + Seek, B: LineSourceBackend<R>>(line_source: &mut LineSourceImpl<R, B>) -> Vec<String> {
+    let length = line_source.get_length();
+    sample_lines(line_source, length)
+}
+
+fn guess_date_format0(line_source: &mut dyn LineSource, length: Integer, ctx: &GuessContext) -> Option<&'static KnownDateFormat> {
+    rate_date_formats(&sample_lines(line_source, length), ctx)
+        .into_iter()
+        .next()
+        .map(|rating| rating.format)
+}
+
+/// Takes [N] lines at each of [SAMPLE_POINTS], so that a banner or a stack trace at the
+/// beginning of the file does not hide dates
+fn sample_lines(line_source: &mut dyn LineSource, length: Integer) -> Vec<String> {
+    let mut lines = vec![];
+    let mut next_offset = Integer::from(0);
+    for i in 0..SAMPLE_POINTS {
+        let offset = (length * i / Integer::from(SAMPLE_POINTS)).max(next_offset);
+        if offset >= length && i > 0 {
+            break;
+        }
+        let data = line_source.read_lines(offset, N.into());
+        let Some(end) = data.end else { break; };
+        next_offset = end;
+        lines.extend(data.lines.into_iter().map(|line| line.content));
+    }
+    lines
+}
+
+/// Rates formats by total length of dates they match in `lines`. Formats that match nothing
+/// are omitted. Of formats with equal rating, the more specific (i.e. defined later) goes first.
+fn rate_date_formats(lines: &[String], ctx: &GuessContext) -> Vec<FormatRating> {
+    let mut ratings: Vec<(usize, FormatRating)> = all_formats()
+        .enumerate()
+        .filter_map(|(i, format)| {
+            let mut rating = FormatRating {
+                format,
+                rating: 0,
+                matched_lines: 0,
+                example: None,
+            };
+            for line in lines {
+                if let Some((_, m)) = format.parse_and_match(line, ctx) {
+                    rating.rating += m.len();
+                    rating.matched_lines += 1;
+                    if rating.example.is_none() {
+                        rating.example = Some(line[m.start()..m.end()].to_string());
+                    }
+                }
+            }
+            (rating.rating > 0).then_some((i, rating))
         })
-        .filter(|(_, n)| *n > 0)
-        .max_by_key(|(_, n)| *n)
-        .map(|(kdf, _)| kdf)
+        .collect();
+    ratings.sort_by_key(|(i, rating)| Reverse((rating.rating, *i)));
+    ratings.into_iter().map(|(_, rating)| rating).collect()
+}
+
+/// How well a date format fits a sample of lines
+#[derive(Debug, Clone)]
+pub struct FormatRating {
+    pub format: &'static KnownDateFormat,
+    pub rating: usize,
+    pub matched_lines: usize,
+    /// The first matched date as it is written
+    pub example: Option<String>,
+}
+
+/// Built-in formats followed by the ones from user settings
+pub fn all_formats() -> impl Iterator<Item = &'static KnownDateFormat> {
+    FORMATS.iter().chain(USER_FORMATS.get().into_iter().flatten())
 }
 
+/// Adds chrono formats from user settings to the built-in ones. Formats can be set only once.
+/// Returns errors of the formats that are not supported.
+pub fn set_user_formats(date_formats: &[String]) -> Vec<String> {
+    let mut formats = vec![];
+    let mut errors = vec![];
+    for date_format in date_formats {
+        match KnownDateFormat::user_defined(date_format) {
+            Ok(kdf) => formats.push(kdf),
+            Err(err) => errors.push(err),
+        }
+    }
+    if USER_FORMATS.set(formats).is_err() {
+        errors.push(String::from("User date formats are already set"));
+    }
+    errors
+}
+
+/// Known format with the given definition. Formats that are not known (e.g. typed by user) are
+/// created once per definition and live until the end of the program.
+pub fn find_or_create_format(definition: &str) -> Result<&'static KnownDateFormat, String> {
+    if let Some(kdf) = all_formats().find(|kdf| kdf.get_definition() == definition) {
+        return Ok(kdf);
+    }
+    let mut created = CREATED_FORMATS.lock().unwrap();
+    if let Some(kdf) = created.get(definition) {
+        return Ok(kdf);
+    }
+    let kdf: &'static KnownDateFormat = Box::leak(Box::new(KnownDateFormat::user_defined(definition)?));
+    created.insert(definition.to_string(), kdf);
+    Ok(kdf)
+}
+
+static USER_FORMATS: OnceLock<Vec<KnownDateFormat>> = OnceLock::new();
+
+lazy_static! {
+    /// Formats created by [find_or_create_format]
+    static ref CREATED_FORMATS: Mutex<HashMap<String, &'static KnownDateFormat>> = Mutex::new(HashMap::new());
+}
+
+const EPOCH_SECONDS: &str = "%s";
+const EPOCH_MILLIS: &str = "%s%3f";
+/// Epoch timestamps are shown in display zone as readable dates
+const EPOCH_RENDER_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
 const FORMAT_TO_PATTERN: phf::Map<&str, &str> = phf_map! {
     "%Y" => "\\d{4}",
     "%C" => "\\d{2}",
@@ -45,11 +183,17 @@ const FORMAT_TO_PATTERN: phf::Map<&str, &str> = phf_map! {
     "%m" => "0[1-9]|1[0-2]",
     "%b" => "Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec",
     "%B" => "January|February|March|April|May|June|July|August|September|October|November|December",
+    "%h" => "Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec",
     "%d" => "0[1-9]|1\\d|2\\d|3[0-1]",
-    // "%a" => todo
-    // "%A" => todo
+    "%e" => " ?[1-9]|1\\d|2\\d|3[0-1]",
+    "%j" => "\\d{3}",
+    "%a" => "Mon|Tue|Wed|Thu|Fri|Sat|Sun",
+    "%A" => "Monday|Tuesday|Wednesday|Thursday|Friday|Saturday|Sunday",
+    "%F" => "\\d{4}-\\d{2}-\\d{2}",
     "%H" => "[0-1]\\d|2[0-3]",
+    "%k" => " ?\\d|1\\d|2[0-3]",
     "%I" => "0[0-9]|1[0-2]",
+    "%T" => "\\d{2}:\\d{2}:\\d{2}",
     "%M" => "\\d{2}",
     "%S" => "\\d{2}",
     "%P" => "am|pm",
@@ -58,6 +202,13 @@ const FORMAT_TO_PATTERN: phf::Map<&str, &str> = phf_map! {
     "%.3f" => "\\.\\d{3}",
     "%.6f" => "\\.\\d{6}",
     "%.9f" => "\\.\\d{9}",
+    "%f" => "\\d{9}",
+    "%3f" => "\\d{3}",
+    "%6f" => "\\d{6}",
+    "%9f" => "\\d{9}",
+    "%z" => "[+-]\\d{4}",
+    "%:z" => "[+-]\\d{2}:\\d{2}",
+    "%s" => "\\b\\d{10}",
 };
 
 const DATE_FORMATS: phf::OrderedSet<&str> = phf_ordered_set! {
@@ -75,12 +226,16 @@ const DATE_FORMATS: phf::OrderedSet<&str> = phf_ordered_set! {
     "%d %b %y",
     "%d %B %Y",
     "%d %B %y",
+    "%Y-%m-%d",
+    "%Y/%m/%d",
 };
 
 lazy_static! {
     static ref INCOMPLETE_DATE_FORMATS: Vec<(&'static str, FormatPreprocessor, Preprocessor)> = {
         vec![
-            ("%b %d", |fmt: &str| format!("%Y {}", fmt), |data: &str, ctx: &GuessContext| format!("{} {}", ctx.get_default_year(), data))
+            // syslog pads day with space
+            ("%b %e", with_year_format, with_default_year),
+            ("%b %d", with_year_format, with_default_year),
         ]
     };
 }
@@ -92,12 +247,19 @@ const TIME_FORMATS: phf::OrderedSet<&str> = phf_ordered_set! {
     "%H:%M:%S%.3f",
     "%H:%M:%S%.6f",
     "%H:%M:%S%.9f",
+    "%H:%M:%S,%3f",
     "%I:%M%P",
     "%I:%M%p",
     "%I:%M %P",
     "%I:%M %p",
 };
 
+/// Formats that are not combinations of date and time formats
+const COMPLETE_FORMATS: phf::OrderedSet<&str> = phf_ordered_set! {
+    // Apache and nginx common log format, zone follows
+    "%d/%b/%Y:%H:%M:%S",
+};
+
 const JOINERS: phf::OrderedSet<&str> = phf_ordered_set! {
     " ",
     "T",
@@ -122,25 +284,35 @@ lazy_static! {
                 }
             }
         }
+        for key in COMPLETE_FORMATS.iter() {
+            formats.push(KnownDateFormat::new(key.to_string()));
+        }
+        formats.push(KnownDateFormat::new(EPOCH_SECONDS.to_string()));
+        formats.push(KnownDateFormat::new_with_preprocessor(
+            EPOCH_MILLIS.to_string(),
+            Box::new(|_: &str| String::from("%s%.3f")),
+            |data: &str, _: &GuessContext| format!("{}.{}", &data[..10], &data[10..]),
+        ));
         formats
     };
-    static ref PATTERN_PART_REGEX: Regex = Regex::new("%(\\w|\\.[369]?f)").unwrap();
+    static ref PATTERN_PART_REGEX: Regex = Regex::new("%(\\.?[369]?f|:z|\\w)").unwrap();
 }
 
 pub struct KnownDateFormat {
+    definition: String,    // format as it is defined, e.g. in user settings
     date_format: String,
+    render_format: String, // format the date is shown in after conversion to display zone
     pattern: Regex,
     preprocessor: Option<Preprocessor>,
+    is_epoch: bool,   // epoch timestamps are in UTC
+    has_offset: bool, // zone is part of the format rather than a suffix
 }
 
 impl KnownDateFormat {
     pub fn new(date_format: String) -> Self {
-        let pattern = Self::build_pattern(&date_format);
-        KnownDateFormat {
-            date_format,
-            pattern,
-            preprocessor: None,
-        }
+        let pattern = Self::build_pattern(&date_format)
+            .unwrap_or_else(|err| panic!("{}", err));
+        Self::from_parts(date_format.clone(), date_format.clone(), date_format, pattern, None)
     }
 
     fn new_with_preprocessor(
@@ -148,11 +320,49 @@ impl KnownDateFormat {
         format_preprocessor: Box<dyn FnOnce(&str) -> String>,
         preprocessor: Preprocessor
     ) -> Self {
-        let pattern = Self::build_pattern(date_format.as_str());
+        let pattern = Self::build_pattern(date_format.as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        let full_format = format_preprocessor(date_format.as_str());
+        Self::from_parts(date_format.clone(), full_format, date_format, pattern, Some(preprocessor))
+    }
+
+    /// Chrono format from user settings. Dates without year are considered to be in the default
+    /// year, as with built-in incomplete formats. Formats must contain month and day or another
+    /// date part, time alone can not be placed on the timeline.
+    pub fn user_defined(date_format: &str) -> Result<Self, String> {
+        let has_date = ["%m", "%b", "%B", "%h", "%d", "%e", "%j", "%D", "%F", "%x", "%c", "%v", "%+", "%s"].iter()
+            .any(|part| date_format.contains(part));
+        if !has_date {
+            return Err(format!("Date format {} does not have a date part, e.g. %d and %m", date_format));
+        }
+        let pattern = Self::build_pattern(date_format)?;
+        let has_year = ["%Y", "%y", "%C", "%F", "%s"].iter().any(|part| date_format.contains(part));
+        let definition = date_format.to_string();
+        let kdf = if has_year {
+            Self::from_parts(definition.clone(), definition.clone(), definition, pattern, None)
+        } else {
+            Self::from_parts(definition.clone(), with_year_format(date_format), definition, pattern, Some(with_default_year))
+        };
+        Ok(kdf)
+    }
+
+    fn from_parts(
+        definition: String,
+        date_format: String,
+        render_format: String,
+        pattern: Regex,
+        preprocessor: Option<Preprocessor>,
+    ) -> Self {
+        let is_epoch = date_format.contains("%s");
+        let has_offset = date_format.contains("%z") || date_format.contains("%:z");
         KnownDateFormat {
-            date_format: format_preprocessor(date_format.as_str()),
+            definition,
+            date_format,
+            render_format: if is_epoch { EPOCH_RENDER_FORMAT.to_string() } else { render_format },
             pattern,
-            preprocessor: Some(preprocessor),
+            preprocessor,
+            is_epoch,
+            has_offset,
         }
     }
 
@@ -165,41 +375,87 @@ impl KnownDateFormat {
         &self.date_format
     }
 
+    /// Format as it is defined, i.e. before it is completed with the default year
+    pub fn get_definition(&self) -> &str {
+        &self.definition
+    }
+
     pub fn parse(&self, data: &str, context: &GuessContext) -> Option<NaiveDateTime> {
         self.parse_and_match(data, context)
             .map(|(dt, _)| dt)
     }
 
-    pub fn guess(&self, data: &str, context: &GuessContext) -> GuessRating {
-        self.parse_and_match(data, context)
-            .map(|(_, m)| GuessRating(m.len()))
-            .unwrap_or_default()
+    /// Finds the date and converts it into the display zone of `context`. The matched interval
+    /// includes the zone that follows the date, if any.
+    pub fn parse_and_match(&self, data: &str, context: &GuessContext) -> Option<(NaiveDateTime, MatchedInterval)> {
+        let (dt, zone, m) = self.parse_written(data, context)?;
+        let interval = MatchedInterval::new(m.start(), m.end() + zone.map_or(0, |(_, len)| len));
+        let dt = match context.display_zone {
+            DisplayZone::Original => dt,
+            display_zone => context.resolve(&dt, zone.map(|(offset, _)| offset))
+                .map(|dt| display_zone.convert(dt).naive_local())
+                .unwrap_or(dt),
+        };
+        Some((dt, interval))
     }
 
-    pub fn parse_and_match(&self, data: &str, context: &GuessContext) -> Option<(NaiveDateTime, MatchedInterval)> {
+    /// Text the date should be shown as in the display zone of `context`, along with the
+    /// interval it replaces. `None` if the date is shown as it is written.
+    pub fn render_in_display_zone(&self, data: &str, context: &GuessContext) -> Option<(String, MatchedInterval)> {
+        if context.display_zone == DisplayZone::Original {
+            return None;
+        }
+        let (dt, zone, m) = self.parse_written(data, context)?;
+        let dt = context.display_zone.convert(context.resolve(&dt, zone.map(|(offset, _)| offset))?);
+        let zone_len = zone.map_or(0, |(_, len)| len);
+        let mut render = dt.format(&self.render_format).to_string();
+        if !self.has_offset {
+            let separator = if zone_len > 0 && !data[m.end()..].starts_with(' ') { "" } else { " " };
+            render = format!("{}{}{}", render, separator, dt.format("%:z"));
+        }
+        Some((render, MatchedInterval::new(m.start(), m.end() + zone_len)))
+    }
+
+    /// Date as it is written along with the zone that follows it and its length
+    fn parse_written<'a>(&self, data: &'a str, context: &GuessContext) -> Option<(NaiveDateTime, Option<(FixedOffset, usize)>, Match<'a>)> {
         // TODO: probably try parsing all matches
-        self.pattern
-            .find(data)
-            .and_then(|m| {
-                let s = m.as_str();
-                let data = match &self.preprocessor {
-                    Some(f) => Cow::Owned(f(s, context)),
-                    None => Cow::Borrowed(s)
-                };
-                NaiveDateTime::parse_from_str(&data, &self.date_format)
-                    .ok()
-                    .map(|dt| (dt, MatchedInterval::from(m)))
-            })
-    }
-
-    fn build_pattern(date_format: &str) -> Regex {
-        let pattern = PATTERN_PART_REGEX.replace_all(date_format, |cpt: &Captures| {
-            let m = cpt.get(0).unwrap().as_str();
-            let pattern = FORMAT_TO_PATTERN.get(m)
-                .unwrap_or_else(|| panic!("Unknown part: {}", m));
-            format!("({})", pattern)
-        });
-        Regex::new(&pattern).unwrap()
+        let m = self.pattern.find(data)?;
+        let rest = &data[m.end()..];
+        if self.is_epoch && rest.starts_with(|c: char| c.is_ascii_digit()) {
+            // a part of a longer number
+            return None;
+        }
+        let s = m.as_str();
+        let preprocessed = match &self.preprocessor {
+            Some(f) => Cow::Owned(f(s, context)),
+            None => Cow::Borrowed(s)
+        };
+        if self.has_offset {
+            let dt = DateTime::parse_from_str(&preprocessed, &self.date_format).ok()?;
+            return Some((dt.naive_local(), Some((*dt.offset(), 0)), m));
+        }
+        let dt = NaiveDateTime::parse_from_str(&preprocessed, &self.date_format).ok()
+            .or_else(|| NaiveDate::parse_from_str(&preprocessed, &self.date_format).ok()
+                .map(|date| date.and_time(NaiveTime::MIN)))?;
+        let zone = match self.is_epoch {
+            true => FixedOffset::east_opt(0).map(|utc| (utc, 0)),
+            false => parse_zone_suffix(rest),
+        };
+        Some((dt, zone, m))
+    }
+
+    fn build_pattern(date_format: &str) -> Result<Regex, String> {
+        let mut pattern = String::new();
+        let mut last = 0;
+        for m in PATTERN_PART_REGEX.find_iter(date_format) {
+            pattern += &regex::escape(&date_format[last..m.start()]);
+            let part = FORMAT_TO_PATTERN.get(m.as_str())
+                .ok_or_else(|| format!("Unknown part {} of date format {}", m.as_str(), date_format))?;
+            pattern += &format!("({})", part);
+            last = m.end();
+        }
+        pattern += &regex::escape(&date_format[last..]);
+        Regex::new(&pattern).map_err(|err| format!("Invalid date format {}: {}", date_format, err))
     }
 }
 
@@ -209,6 +465,7 @@ impl Debug for KnownDateFormat {
     }
 }
 
+#[derive(Debug)]
 pub struct MatchedInterval {
     start: usize,
     end: usize,
@@ -241,27 +498,62 @@ impl From<Match<'_>> for MatchedInterval {
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct GuessContext {
     default_year: u16,
+    assumed_zone: AssumedZone,
+    display_zone: DisplayZone,
 }
 
 impl GuessContext {
     pub fn with_year(year: u16) -> Self {
         GuessContext {
             default_year: year,
+            assumed_zone: AssumedZone::default(),
+            display_zone: DisplayZone::default(),
         }
     }
 
+    /// Dates without zone are considered to be in `assumed_zone`, parsed dates are converted
+    /// into `display_zone`
+    pub fn with_zones(mut self, assumed_zone: AssumedZone, display_zone: DisplayZone) -> Self {
+        self.assumed_zone = assumed_zone;
+        self.display_zone = display_zone;
+        self
+    }
+
     pub fn get_default_year(&self) -> u16 {
         self.default_year
     }
+
+    /// Wall time of the instant `dt` in the zone dates are parsed in: the display zone or, when
+    /// dates are shown as written, the assumed zone
+    pub fn wall_time(&self, dt: DateTime<FixedOffset>) -> NaiveDateTime {
+        match self.display_zone {
+            DisplayZone::Original => self.assumed_zone.wall_time(dt),
+            display_zone => display_zone.convert(dt).naive_local(),
+        }
+    }
+
+    fn resolve(&self, dt: &NaiveDateTime, offset: Option<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+        match offset {
+            Some(offset) => offset.from_local_datetime(dt).single(),
+            None => self.assumed_zone.resolve(dt),
+        }
+    }
+}
+
+fn with_year_format(date_format: &str) -> String {
+    format!("%Y {}", date_format)
+}
+
+fn with_default_year(data: &str, context: &GuessContext) -> String {
+    format!("{} {}", context.get_default_year(), data)
 }
 
 type FormatPreprocessor = fn(&str) -> String;
 type Preprocessor = fn(&str, &GuessContext) -> String;
 
-#[derive(Default, Copy, Clone, Debug)]
-pub struct GuessRating(usize);
 
 #[cfg(test)]
 mod tests {
@@ -270,7 +562,8 @@ mod tests {
     use lazy_static::lazy_static;
     use spectral::prelude::*;
 
-    use crate::model::guess_date_format::{GuessContext, FORMATS};
+    use crate::model::guess_date_format::{find_or_create_format, GuessContext, KnownDateFormat, FORMATS};
+    use crate::model::time_zone::{AssumedZone, DisplayZone};
     use chrono::NaiveDateTime;
 
     lazy_static! {
@@ -285,6 +578,11 @@ mod tests {
             let mut test_cases = vec![
                 ("abc", None),
                 ("123: 10.11.2023 22:30:10 The text goes here", Some("%d.%m.%Y %H:%M:%S")),
+                ("2024-05-01T10:00:00.123+02:00 INFO started", Some("%Y-%m-%dT%H:%M:%S%.3f")),
+                ("127.0.0.1 - - [10/Oct/2000:13:55:36 -0700] \"GET / HTTP/1.0\" 200", Some("%d/%b/%Y:%H:%M:%S")),
+                ("Feb  3 13:30:06 host sshd[42]: message", Some("%Y %b %e %H:%M:%S")),
+                ("1706695200 message", Some("%s")),
+                ("1706695200123 message", Some("%s%.3f")),
             ];
             for (input, pattern) in PARSE_TEST_CASES.iter() {
                 test_cases.push((input, Some(pattern)));
@@ -305,7 +603,8 @@ mod tests {
         let ctx = GuessContext::with_year(2023);
         for (input, expected) in TEST_CASES.iter() {
             let mut line_source = LineSourceImpl::from_str(input);
-            let actual = guess_date_format0(&mut line_source, &ctx)
+            let length = line_source.get_length();
+            let actual = guess_date_format0(&mut line_source, length, &ctx)
                 .map(|kdf| kdf.get_date_format().to_string());
             let description = format!("{} => {:?}", input, expected);
             asserting(&description).that(&actual.as_deref()).is_equal_to(expected);
@@ -351,6 +650,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_time_zones() {
+        let format = FORMATS.iter()
+            .find(|kdf| kdf.date_format == "%d.%m.%Y %H:%M:%S")
+            .unwrap();
+        let assumed_zone: AssumedZone = "+02:00".parse().unwrap();
+        let ctx = GuessContext::with_year(2023).with_zones(assumed_zone, DisplayZone::Utc);
+        let parse = |input: &str| format.parse_and_match(input, &ctx)
+            .map(|(dt, m)| (dt.format("%D %T").to_string(), m.start(), m.end()));
+
+        assert_that!(parse("10.11.2023 22:30:10 INFO")).is_equal_to(Some((String::from("11/10/23 20:30:10"), 0, 19)));
+        assert_that!(parse("10.11.2023 22:30:10 -0500 INFO")).is_equal_to(Some((String::from("11/11/23 03:30:10"), 0, 25)));
+        assert_that!(parse("[10.11.2023 22:30:10Z] INFO")).is_equal_to(Some((String::from("11/10/23 22:30:10"), 1, 21)));
+
+        let render = format.render_in_display_zone("10.11.2023 22:30:10 -0500 INFO", &ctx)
+            .map(|(render, m)| (render, m.start(), m.end()));
+        assert_that!(render).is_equal_to(Some((String::from("11.11.2023 03:30:10 +00:00"), 0, 25)));
+        let render = format.render_in_display_zone("10.11.2023 22:30:10 INFO", &ctx)
+            .map(|(render, m)| (render, m.start(), m.end()));
+        assert_that!(render).is_equal_to(Some((String::from("10.11.2023 20:30:10 +00:00"), 0, 19)));
+
+        let ctx = GuessContext::with_year(2023).with_zones(assumed_zone, DisplayZone::Original);
+        assert_that!(format.render_in_display_zone("10.11.2023 22:30:10 -0500 INFO", &ctx)).is_none();
+        assert_that!(format.parse("10.11.2023 22:30:10 -0500 INFO", &ctx).map(|dt| dt.format("%T").to_string()))
+            .is_equal_to(Some(String::from("22:30:10")));
+    }
+
+    #[test]
+    fn test_user_defined() {
+        let ctx = GuessContext::with_year(2023);
+        let parse = |date_format: &str, input: &str| KnownDateFormat::user_defined(date_format).unwrap()
+            .parse_and_match(input, &ctx)
+            .map(|(dt, m)| (dt.format("%D %T").to_string(), m.start(), m.end()));
+
+        assert_that!(parse("[%d/%m/%Y %T]", "x [31/01/2024 10:00:00] y")).is_equal_to(Some((String::from("01/31/24 10:00:00"), 2, 23)));
+        assert_that!(parse("%d/%m %H:%M", "31/01 10:00 y")).is_equal_to(Some((String::from("01/31/23 10:00:00"), 0, 11)));
+        assert_that!(parse("%Y%m%d", "id=20240131")).is_equal_to(Some((String::from("01/31/24 00:00:00"), 3, 11)));
+        assert_that!(KnownDateFormat::user_defined("%d %Q")).is_err();
+        assert_that!(KnownDateFormat::user_defined("[%T]")).is_err();
+
+        let ctx = GuessContext::with_year(2023).with_zones(AssumedZone::default(), DisplayZone::Utc);
+        let kdf = KnownDateFormat::user_defined("%Y-%m-%d %H:%M:%S %z").unwrap();
+        assert_that!(kdf.parse("2024-01-31 10:00:00 +0200 x", &ctx).map(|dt| dt.format("%T").to_string()))
+            .is_equal_to(Some(String::from("08:00:00")));
+        let render = kdf.render_in_display_zone("2024-01-31 10:00:00 +0200 x", &ctx).map(|(render, _)| render);
+        assert_that!(render).is_equal_to(Some(String::from("2024-01-31 08:00:00 +0000")));
+    }
+
+    #[test]
+    fn test_find_or_create_format() {
+        let built_in = find_or_create_format("%Y-%m-%d %H:%M:%S").unwrap();
+        assert_that!(FORMATS.iter().any(|kdf| std::ptr::eq(kdf, built_in))).is_true();
+        let created = find_or_create_format("[%d/%m/%Y %T]").unwrap();
+        assert_that!(std::ptr::eq(created, find_or_create_format("[%d/%m/%Y %T]").unwrap())).is_true();
+        assert_that!(find_or_create_format("%d %Q")).is_err();
+    }
+
+    #[test]
+    fn test_epoch() {
+        let ctx = GuessContext::with_year(2023).with_zones(AssumedZone::default(), DisplayZone::Utc);
+        let millis = FORMATS.iter().find(|kdf| kdf.date_format == "%s%.3f").unwrap();
+        assert_that!(millis.parse("1706695200123 x", &ctx).map(|dt| dt.format("%D %T%.3f").to_string()))
+            .is_equal_to(Some(String::from("01/31/24 10:00:00.123")));
+        let render = millis.render_in_display_zone("1706695200123 x", &ctx).map(|(render, _)| render);
+        assert_that!(render).is_equal_to(Some(String::from("2024-01-31 10:00:00.123 +00:00")));
+        let seconds = FORMATS.iter().find(|kdf| kdf.date_format == "%s").unwrap();
+        assert_that!(seconds.parse("17066952001234 x", &ctx)).is_none();
+    }
+
     // #[test]
     // fn print_formats() {
     //     for fmt in FORMATS.iter() {
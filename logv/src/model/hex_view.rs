@@ -0,0 +1,131 @@
+use fluent_integer::Integer;
+
+/// Number of bytes shown in a row of hex view
+pub const BYTES_PER_ROW: usize = 16;
+
+/// State of hex view, which shows bytes of the data source as offset, hex and ASCII columns.
+/// Cursor and selection are shared with the text view, as both are byte offsets.
+#[derive(Debug, Default)]
+pub struct HexView {
+    first_row: Integer, // offset of the first byte in viewport, a multiple of BYTES_PER_ROW
+    bytes: Vec<u8>,     // bytes that fit into viewport
+}
+
+impl HexView {
+    pub fn new(first_row: Integer) -> Self {
+        HexView {
+            first_row: Self::row_start(first_row),
+            bytes: vec![],
+        }
+    }
+
+    /// Offset of the row that contains `offset`
+    pub fn row_start(offset: Integer) -> Integer {
+        let offset = offset.as_u64();
+        (offset - offset % BYTES_PER_ROW as u64).into()
+    }
+
+    pub fn get_first_row(&self) -> Integer {
+        self.first_row
+    }
+
+    pub fn set_first_row(&mut self, first_row: Integer) {
+        self.first_row = Self::row_start(first_row);
+    }
+
+    pub fn set_bytes(&mut self, bytes: Vec<u8>) {
+        self.bytes = bytes;
+    }
+
+    /// Rows in viewport as pairs of offset and bytes
+    pub fn rows(&self) -> impl Iterator<Item = (Integer, &[u8])> {
+        let first_row = self.first_row;
+        self.bytes.chunks(BYTES_PER_ROW)
+            .enumerate()
+            .map(move |(i, chunk)| (first_row + i * BYTES_PER_ROW, chunk))
+    }
+
+    /// Offset of the first row that makes `offset` visible in viewport of `height` rows with
+    /// least scrolling
+    pub fn first_row_to_fit(&self, offset: Integer, height: usize) -> Integer {
+        let row = Self::row_start(offset);
+        let rows_below = height.saturating_sub(1) * BYTES_PER_ROW;
+        if row < self.first_row {
+            row
+        } else if row > self.first_row + rows_below {
+            row - rows_below
+        } else {
+            self.first_row
+        }
+    }
+
+    /// Greatest offset of the first row, i.e. the one that has the last row of content at the
+    /// bottom of viewport of `height` rows
+    pub fn max_first_row(length: Integer, height: usize) -> Integer {
+        let last_row = Self::row_start((length - 1).max(Integer::from(0)));
+        let rows_above = height.saturating_sub(1) * BYTES_PER_ROW;
+        (last_row - rows_above).max(Integer::from(0))
+    }
+}
+
+/// Char shown in ASCII column for byte `b`
+pub fn to_ascii(b: u8) -> char {
+    if b.is_ascii_graphic() || b == b' ' {
+        char::from(b)
+    } else {
+        '.'
+    }
+}
+
+/// Text of the offset column
+pub fn format_offset(offset: Integer) -> String {
+    format!("{:08x}", offset.as_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use fluent_integer::Integer;
+    use spectral::prelude::*;
+
+    use super::{format_offset, to_ascii, HexView};
+
+    #[test]
+    fn test_row_start() {
+        assert_that!(HexView::row_start(Integer::from(0))).is_equal_to(Integer::from(0));
+        assert_that!(HexView::row_start(Integer::from(15))).is_equal_to(Integer::from(0));
+        assert_that!(HexView::row_start(Integer::from(33))).is_equal_to(Integer::from(32));
+    }
+
+    #[test]
+    fn test_first_row_to_fit() {
+        let hex_view = HexView::new(Integer::from(32));
+        assert_that!(hex_view.first_row_to_fit(Integer::from(40), 3)).is_equal_to(Integer::from(32));
+        assert_that!(hex_view.first_row_to_fit(Integer::from(70), 3)).is_equal_to(Integer::from(32));
+        assert_that!(hex_view.first_row_to_fit(Integer::from(85), 3)).is_equal_to(Integer::from(48));
+        assert_that!(hex_view.first_row_to_fit(Integer::from(5), 3)).is_equal_to(Integer::from(0));
+    }
+
+    #[test]
+    fn test_max_first_row() {
+        assert_that!(HexView::max_first_row(Integer::from(100), 3)).is_equal_to(Integer::from(64));
+        assert_that!(HexView::max_first_row(Integer::from(96), 3)).is_equal_to(Integer::from(48));
+        assert_that!(HexView::max_first_row(Integer::from(20), 3)).is_equal_to(Integer::from(0));
+        assert_that!(HexView::max_first_row(Integer::from(0), 3)).is_equal_to(Integer::from(0));
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut hex_view = HexView::new(Integer::from(16));
+        hex_view.set_bytes((0..20).collect());
+        let rows: Vec<_> = hex_view.rows().map(|(offset, bytes)| (offset, bytes.len())).collect();
+        assert_that!(rows).is_equal_to(vec![(Integer::from(16), 16), (Integer::from(32), 4)]);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_that!(format_offset(Integer::from(0x1e240))).is_equal_to(String::from("0001e240"));
+        assert_that!(to_ascii(b'a')).is_equal_to('a');
+        assert_that!(to_ascii(b'\n')).is_equal_to('.');
+        assert_that!(to_ascii(0xff)).is_equal_to('.');
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use fluent_integer::Integer;
+
+use crate::data_source::tokenizer::tokenize;
+
+const MASK: &str = "*";
+pub const MAX_TEMPLATES: usize = 10_000;
+const MIN_HEX_LENGTH: usize = 8;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TemplatePart {
+    Literal(String),
+    Variable,
+}
+
+/// Line with variable parts (numbers, ids, hashes) masked
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Template(Vec<TemplatePart>);
+
+#[derive(Clone, Debug)]
+pub struct TemplateStats {
+    pub template: Template,
+    pub count: usize,
+    pub first_offset: Integer,
+}
+
+/// Templates, most frequent first, and the number of lines that didn't fit in [MAX_TEMPLATES]
+#[derive(Clone, Debug, Default)]
+pub struct Clusters {
+    pub templates: Vec<TemplateStats>,
+    pub unclustered: usize,
+}
+
+/// Groups lines by their templates
+#[derive(Default)]
+pub struct TemplateCollector {
+    templates: HashMap<Template, (usize, Integer)>,
+    unclustered: usize,
+}
+
+impl Template {
+    pub fn from_line(line: &str) -> Self {
+        let mut parts: Vec<TemplatePart> = vec![];
+        for token in tokenize(line) {
+            let part = if !token.is_delimiter && is_variable(token.text) {
+                TemplatePart::Variable
+            } else {
+                TemplatePart::Literal(token.text.to_string())
+            };
+            let merged = match (&part, parts.last_mut()) {
+                (TemplatePart::Literal(s), Some(TemplatePart::Literal(prev))) => {
+                    prev.push_str(s);
+                    true
+                },
+                _ => false,
+            };
+            if !merged {
+                parts.push(part);
+            }
+        }
+        Template(parts)
+    }
+
+    /// Regular expression that matches whole lines of this template
+    pub fn to_regex(&self) -> String {
+        let body: String = self.0.iter()
+            .map(|part| match part {
+                TemplatePart::Literal(s) => regex::escape(s),
+                TemplatePart::Variable => String::from(r"\w+"),
+            })
+            .collect();
+        format!("^{}$", body)
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.0 {
+            match part {
+                TemplatePart::Literal(s) => write!(f, "{}", s)?,
+                TemplatePart::Variable => write!(f, "{}", MASK)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TemplateCollector {
+    pub fn add(&mut self, line: &str, offset: Integer) {
+        let template = Template::from_line(line);
+        if let Some((count, _)) = self.templates.get_mut(&template) {
+            *count += 1;
+        } else if self.templates.len() < MAX_TEMPLATES {
+            self.templates.insert(template, (1, offset));
+        } else {
+            self.unclustered += 1;
+        }
+    }
+
+    pub fn into_clusters(self) -> Clusters {
+        let mut templates: Vec<TemplateStats> = self.templates.into_iter()
+            .map(|(template, (count, first_offset))| TemplateStats { template, count, first_offset })
+            .collect();
+        templates.sort_by(|a, b| b.count.cmp(&a.count).then(a.first_offset.cmp(&b.first_offset)));
+        Clusters { templates, unclustered: self.unclustered }
+    }
+}
+
+fn is_variable(token: &str) -> bool {
+    token.chars().any(|ch| ch.is_ascii_digit())
+        || (token.len() >= MIN_HEX_LENGTH && token.chars().all(|ch| ch.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use spectral::prelude::*;
+
+    use super::{Template, TemplateCollector, MAX_TEMPLATES};
+
+    #[test]
+    fn test_masking() {
+        let template = Template::from_line("Connection to 10.0.0.1 timed out after 300 ms");
+        assert_that!(template.to_string()).is_equal_to(String::from("Connection to *.*.*.* timed out after * ms"));
+    }
+
+    #[test]
+    fn test_masking_ids() {
+        let template = Template::from_line("request 0x1F2e id=a3f9 hash deadbeefcafe user=bob");
+        assert_that!(template.to_string()).is_equal_to(String::from("request * id=* hash * user=bob"));
+    }
+
+    #[test]
+    fn test_regex() {
+        let template = Template::from_line("[main] took 12 ms (a+b)");
+        let regex = Regex::new(&template.to_regex()).unwrap();
+        asserting!("Same template").that(&regex.is_match("[main] took 345 ms (a+b)")).is_true();
+        asserting!("Different template").that(&regex.is_match("[main] took 345 s (a+b)")).is_false();
+        asserting!("Whole line").that(&regex.is_match("x [main] took 345 ms (a+b)")).is_false();
+    }
+
+    #[test]
+    fn test_collector() {
+        let mut collector = TemplateCollector::default();
+        collector.add("a 1", 0.into());
+        collector.add("b", 4.into());
+        collector.add("a 2", 6.into());
+        let clusters = collector.into_clusters();
+        let stats = clusters.templates;
+        assert_that!(stats).has_length(2);
+        assert_that!(stats[0].template.to_string()).is_equal_to(String::from("a *"));
+        assert_that!(stats[0].count).is_equal_to(2);
+        assert_that!(stats[0].first_offset).is_equal_to(fluent_integer::Integer::from(0));
+        assert_that!(stats[1].count).is_equal_to(1);
+        assert_that!(clusters.unclustered).is_equal_to(0);
+    }
+
+    #[test]
+    fn test_collector_limit() {
+        let mut collector = TemplateCollector::default();
+        // words without digits, so that every line is a template of its own
+        let word = |mut i: usize| {
+            let mut word = String::new();
+            loop {
+                word.push((b'g' + (i % 20) as u8) as char);
+                i /= 20;
+                if i == 0 {
+                    return word;
+                }
+            }
+        };
+        for i in 0..MAX_TEMPLATES + 2 {
+            collector.add(&format!("line {}", word(i)), 0.into());
+        }
+        collector.add("line g", 0.into());
+        let clusters = collector.into_clusters();
+        assert_that!(clusters.templates).has_length(MAX_TEMPLATES);
+        assert_that!(clusters.unclustered).is_equal_to(2);
+    }
+}
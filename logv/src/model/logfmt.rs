@@ -0,0 +1,235 @@
+use std::borrow::Cow;
+
+use anyhow::anyhow;
+use regex::Regex;
+
+/// Single `key=value` pair. Offsets are given in bytes relative to the beginning of line.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LogfmtPair<'a> {
+    pub key: &'a str,
+    pub key_start: usize,
+    /// Unquoted and unescaped value
+    pub value: Cow<'a, str>,
+    /// Raw value boundaries, including quotes
+    pub value_start: usize,
+    pub value_end: usize,
+}
+
+impl<'a> LogfmtPair<'a> {
+    pub fn key_end(&self) -> usize {
+        self.key_start + self.key.len()
+    }
+}
+
+/// Extracts `key=value` pairs from `line`. Words that are not followed by `=` are skipped, so
+/// lines with a non-logfmt prefix (e.g. date and level) are supported too.
+pub fn parse(line: &str) -> Vec<LogfmtPair<'_>> {
+    scan(line).0
+}
+
+/// Same as [parse], but only if pairs make up most of the line, so that plain text that
+/// happens to contain `=` is not taken for logfmt
+pub fn parse_if_logfmt(line: &str) -> Option<Vec<LogfmtPair<'_>>> {
+    let (pairs, other_words) = scan(line);
+    (pairs.len() > other_words).then_some(pairs)
+}
+
+/// Returns pairs and the number of skipped words
+fn scan(line: &str) -> (Vec<LogfmtPair<'_>>, usize) {
+    let bytes = line.as_bytes();
+    let mut result = vec![];
+    let mut other_words = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let key_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'=' && bytes[i] != b'"' {
+            i += 1;
+        }
+        let key_end = i;
+        if i >= bytes.len() || bytes[i] != b'=' || key_start == key_end {
+            // not a pair, skip the word
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i > key_start {
+                other_words += 1;
+            }
+            continue;
+        }
+        i += 1; // skip '='
+        let value_start = i;
+        let value = if i < bytes.len() && bytes[i] == b'"' {
+            i += 1;
+            let mut escaped = false;
+            let mut has_escapes = false;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' if !escaped => {
+                        escaped = true;
+                        has_escapes = true;
+                    },
+                    b'"' if !escaped => break,
+                    _ => escaped = false,
+                }
+                i += 1;
+            }
+            let content = &line[value_start + 1..i];
+            if i < bytes.len() {
+                i += 1; // closing quote
+            }
+            if has_escapes {
+                Cow::Owned(unescape(content))
+            } else {
+                Cow::Borrowed(content)
+            }
+        } else {
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            Cow::Borrowed(&line[value_start..i])
+        };
+        result.push(LogfmtPair {
+            key: &line[key_start..key_end],
+            key_start,
+            value,
+            value_start,
+            value_end: i,
+        });
+    }
+    (result, other_words)
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(other) => result.push(other),
+                None => result.push(ch),
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldMatcher {
+    Equals(String),
+    Regex(Regex),
+}
+
+/// Condition on a logfmt field: either `key=value` or `key~regex`
+#[derive(Debug, Clone)]
+pub struct FieldFilter {
+    key: String,
+    matcher: FieldMatcher,
+}
+
+impl FieldFilter {
+    pub fn parse(expression: &str) -> anyhow::Result<Self> {
+        let p = expression.find(['=', '~'])
+            .ok_or(anyhow!("Expected key=value or key~regex, got {:?}", expression))?;
+        let key = expression[..p].trim();
+        if key.is_empty() {
+            return Err(anyhow!("Key is missing in {:?}", expression));
+        }
+        let value = &expression[p + 1..];
+        let matcher = if expression[p..].starts_with('=') {
+            FieldMatcher::Equals(value.to_string())
+        } else {
+            FieldMatcher::Regex(Regex::new(value)?)
+        };
+        Ok(FieldFilter { key: key.to_string(), matcher })
+    }
+
+    /// Returns raw boundaries of the matching value, if any
+    pub fn find(&self, line: &str) -> Option<(usize, usize)> {
+        parse(line).into_iter()
+            .filter(|pair| pair.key == self.key)
+            .find(|pair| match &self.matcher {
+                FieldMatcher::Equals(v) => pair.value == v.as_str(),
+                FieldMatcher::Regex(r) => r.is_match(&pair.value),
+            })
+            .map(|pair| (pair.value_start, pair.value_end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::{parse, parse_if_logfmt, FieldFilter};
+
+    #[test]
+    fn test_parse() {
+        let line = r#"ts=2024-01-01T10:00:00Z level=info msg="user logged in" user=42"#;
+        let pairs = parse(line);
+        let actual: Vec<(&str, String)> = pairs.iter()
+            .map(|p| (p.key, p.value.to_string()))
+            .collect();
+        assert_that!(actual).is_equal_to(vec![
+            ("ts", String::from("2024-01-01T10:00:00Z")),
+            ("level", String::from("info")),
+            ("msg", String::from("user logged in")),
+            ("user", String::from("42")),
+        ]);
+        let msg = &pairs[2];
+        assert_that!(line[msg.value_start..msg.value_end].to_string()).is_equal_to(String::from(r#""user logged in""#));
+        assert_that!(line[msg.key_start..msg.key_end()].to_string()).is_equal_to(String::from("msg"));
+    }
+
+    #[test]
+    fn test_parse_escapes_and_prefix() {
+        let pairs = parse(r#"2024-01-01 INFO err="bad \"input\"" empty= done"#);
+        let actual: Vec<(&str, String)> = pairs.iter()
+            .map(|p| (p.key, p.value.to_string()))
+            .collect();
+        assert_that!(actual).is_equal_to(vec![
+            ("err", String::from(r#"bad "input""#)),
+            ("empty", String::new()),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_not_logfmt() {
+        assert_that!(parse("just a plain line")).is_empty();
+    }
+
+    #[test]
+    fn test_parse_if_logfmt() {
+        assert_that!(parse_if_logfmt("2024-01-01 INFO level=info msg=\"user logged in\" user=42")).is_some().has_length(3);
+        assert_that!(parse_if_logfmt("key=value")).is_some().has_length(1);
+        assert_that!(parse_if_logfmt("2024-01-01 INFO Server started with timeout=30")).is_none();
+        assert_that!(parse_if_logfmt("GET /search?q=logs HTTP/1.1")).is_none();
+        assert_that!(parse_if_logfmt("")).is_none();
+    }
+
+    #[test]
+    fn test_filter_equals() {
+        let filter = FieldFilter::parse("level=error").unwrap();
+        assert_that!(filter.find("level=error msg=x")).is_some().is_equal_to((6, 11));
+        assert_that!(filter.find("level=info msg=error")).is_none();
+        assert_that!(filter.find(r#"level="error""#)).is_some();
+    }
+
+    #[test]
+    fn test_filter_regex() {
+        let filter = FieldFilter::parse("user~^4[0-9]$").unwrap();
+        assert_that!(filter.find("user=42")).is_some();
+        assert_that!(filter.find("user=420")).is_none();
+    }
+
+    #[test]
+    fn test_filter_invalid() {
+        assert_that!(FieldFilter::parse("no operator")).is_err();
+        assert_that!(FieldFilter::parse("=value")).is_err();
+    }
+}
@@ -0,0 +1,305 @@
+use std::io::{BufRead, BufReader};
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use fluent_integer::Integer;
+use uuid::Uuid;
+
+use crate::background_process::background_process_handler::BackgroundProcessHandler;
+use crate::background_process::run_in_background::RunInBackground;
+use crate::background_process::signal::Signal;
+use crate::background_process::task_context::TaskContext;
+use crate::data_source::filtered::filtered_line_source::LineFilter;
+use crate::data_source::line_registry::{LineRegistry, LineRegistryImpl};
+use crate::data_source::line_source_holder::ConcreteLineSourceHolder;
+use crate::data_source::reader_factory::{HasReaderFactory, ReaderFactory};
+use crate::data_source::{Direction, LineSource};
+use crate::interval::Interval;
+use crate::model::bgp_model::BGPModel;
+use crate::model::model::ModelEvent;
+use crate::search::searcher::{create_searcher_for, SearchError};
+use crate::shared::Shared;
+use crate::utils;
+use crate::utils::event_emitter::EventEmitter;
+use crate::utils::utf8::decode_substituting;
+
+/// Number of buckets the whole file is split into
+pub const RESOLUTION: usize = 1024;
+
+const PUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum HitKind {
+    Search,
+    Filter,
+}
+
+/// Number of hits per bucket
+#[derive(Clone, Debug)]
+pub struct Density(Vec<u32>);
+
+pub struct MinimapModel {
+    model_sender: Sender<ModelEvent>,
+    runner: Shared<BGPModel>,
+    file_length: Integer,
+    search_hits: Density,
+    filter_hits: Density,
+    search_handler: Option<BackgroundProcessHandler>,
+    filter_handler: Option<BackgroundProcessHandler>,
+}
+
+impl MinimapModel {
+    pub fn new(model_sender: Sender<ModelEvent>, runner: Shared<BGPModel>) -> Self {
+        MinimapModel {
+            model_sender,
+            runner,
+            file_length: 0.into(),
+            search_hits: Density::default(),
+            filter_hits: Density::default(),
+            search_handler: None,
+            filter_handler: None,
+        }
+    }
+
+    pub fn reset(&mut self, file_length: Integer) {
+        self.file_length = file_length;
+        self.set_query(HitKind::Search, None);
+        self.set_query(HitKind::Filter, None);
+    }
+
+    pub fn get_file_length(&self) -> Integer {
+        self.file_length
+    }
+
+    pub fn get_hits(&self, kind: HitKind) -> &Density {
+        match kind {
+            HitKind::Search => &self.search_hits,
+            HitKind::Filter => &self.filter_hits,
+        }
+    }
+
+    /// Starts calculation of hits of `pattern` over the whole `source`. Pass `None` to clear hits
+    /// of the given kind.
+    pub fn update(&mut self, kind: HitKind, source: &ConcreteLineSourceHolder, pattern: Option<(String, bool)>) {
+        self.set_query(kind, None);
+        let Some((pattern, is_regex)) = pattern else { return; };
+        if pattern.is_empty() {
+            return;
+        }
+        let reader_factory = source.reader_factory();
+        let line_registry = source.get_line_registry();
+        let file_length = source.get_length();
+        let description = format!("Locate {:?} across the file", &pattern);
+        self.run(kind, file_length, description, move |ctx| {
+            crawl_hits(ctx, reader_factory, line_registry, pattern, is_regex, file_length)
+        });
+    }
+
+    /// Starts calculation of lines of `source` passing `filter`, so that any filter can be shown,
+    /// including ones that have no equivalent pattern
+    pub fn update_filtered(&mut self, source: &ConcreteLineSourceHolder, filter: LineFilter) {
+        self.set_query(HitKind::Filter, None);
+        let reader_factory = source.reader_factory();
+        let file_length = source.get_length();
+        self.run(HitKind::Filter, file_length, String::from("Locate filtered lines across the file"), move |ctx| {
+            crawl_filtered(ctx, reader_factory, filter, file_length)
+        });
+    }
+
+    fn run<F>(&mut self, kind: HitKind, file_length: Integer, description: String, task: F)
+    where
+        F: FnOnce(&mut TaskContext<Vec<usize>, Result<(), SearchError>>) -> Result<(), SearchError> + Send + 'static
+    {
+        self.file_length = file_length;
+        let runner = &mut *self.runner.get_mut_ref();
+        let handler = runner.background_process_builder::<Vec<usize>, _, Result<(), SearchError>, _>()
+            .with_title("Minimap")
+            .with_description(description)
+            .with_task(task)
+            .with_listener(move |model, signal, id| {
+                match signal {
+                    Signal::Custom(buckets) => {
+                        model.get_minimap_model().accept_hits(kind, buckets, id);
+                    }
+                    Signal::Complete(Err(err)) => {
+                        log::error!("Failed to calculate minimap hits: {:?}", err);
+                    }
+                    _ => {}
+                }
+            })
+            .run();
+        self.set_query(kind, Some(handler));
+    }
+
+    fn set_query(&mut self, kind: HitKind, handler: Option<BackgroundProcessHandler>) {
+        let (hits, current_handler) = match kind {
+            HitKind::Search => (&mut self.search_hits, &mut self.search_handler),
+            HitKind::Filter => (&mut self.filter_hits, &mut self.filter_handler),
+        };
+        if let Some(h) = mem::replace(current_handler, handler) {
+            h.interrupt();
+        }
+        hits.clear();
+        self.model_sender.emit_event(ModelEvent::Repaint);
+    }
+
+    fn accept_hits(&mut self, kind: HitKind, buckets: Vec<usize>, id: &Uuid) {
+        let (hits, handler) = match kind {
+            HitKind::Search => (&mut self.search_hits, &self.search_handler),
+            HitKind::Filter => (&mut self.filter_hits, &self.filter_handler),
+        };
+        if handler.as_ref().map(|h| h.get_id()) != Some(id) {
+            return;
+        }
+        buckets.into_iter().for_each(|b| hits.add(b));
+        self.model_sender.emit_event(ModelEvent::Repaint);
+    }
+}
+
+impl Density {
+    pub fn add(&mut self, bucket: usize) {
+        if let Some(v) = self.0.get_mut(bucket) {
+            *v += 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.0.iter_mut().for_each(|v| *v = 0);
+    }
+
+    /// Sums hits in buckets that fall into `row` when the whole range is split into `rows` rows
+    pub fn count(&self, row: usize, rows: usize) -> u32 {
+        if rows == 0 {
+            return 0;
+        }
+        let s = row * RESOLUTION / rows;
+        let e = ((row + 1) * RESOLUTION / rows).max(s + 1).min(RESOLUTION);
+        self.0[s.min(RESOLUTION)..e].iter().sum()
+    }
+}
+
+impl Default for Density {
+    fn default() -> Self {
+        Density(vec![0; RESOLUTION])
+    }
+}
+
+/// Returns bucket, which `offset` belongs to
+pub fn bucket_of(offset: Integer, file_length: Integer) -> usize {
+    if file_length <= 0 {
+        return 0;
+    }
+    let b = (offset * RESOLUTION / file_length).as_usize();
+    b.min(RESOLUTION - 1)
+}
+
+/// Finds every line that contains `pattern`. A line with several occurrences is counted once.
+/// Line registry is used to skip the rest of a line, when it is already crawled.
+fn crawl_hits(
+    ctx: &mut TaskContext<Vec<usize>, Result<(), SearchError>>,
+    reader_factory: Box<dyn ReaderFactory>,
+    line_registry: Arc<LineRegistryImpl>,
+    pattern: String,
+    is_regex: bool,
+    file_length: Integer,
+) -> Result<(), SearchError> {
+    let mut searcher = create_searcher_for(reader_factory.as_ref(), pattern, is_regex)?;
+    let mut scope = Interval::closed_inf(Integer::from(0));
+    let mut buckets = vec![];
+    let mut last_push = Instant::now();
+    while !ctx.interrupted() {
+        match searcher.search(Direction::Forward, scope) {
+            Ok(occurrence) => {
+                buckets.push(bucket_of(occurrence.start, file_length));
+                let next_line_start = line_registry.count(&Interval::closed_open(Integer::from(0), occurrence.start))
+                    .ok()
+                    .and_then(|line_no| line_registry.find_offset_by_line_number(line_no + 1).ok())
+                    .unwrap_or(occurrence.end);
+                scope = Interval::closed_inf(next_line_start.max(occurrence.start + 1));
+                if last_push.elapsed() > PUSH_INTERVAL {
+                    ctx.update_progress_u64(occurrence.start.as_u64(), file_length.as_u64());
+                    let _ = ctx.send_message(mem::take(&mut buckets));
+                    last_push = Instant::now();
+                }
+            }
+            Err(SearchError::NotFound) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    if !buckets.is_empty() {
+        let _ = ctx.send_message(buckets);
+    }
+    Ok(())
+}
+
+/// Reads every line and marks the ones `filter` finds anything in
+fn crawl_filtered(
+    ctx: &mut TaskContext<Vec<usize>, Result<(), SearchError>>,
+    reader_factory: Box<dyn ReaderFactory>,
+    filter: LineFilter,
+    file_length: Integer,
+) -> Result<(), SearchError> {
+    let mut reader = BufReader::new(reader_factory.new_reader()?);
+    let mut buf = vec![];
+    let mut offset = 0u64;
+    let mut buckets = vec![];
+    let mut last_push = Instant::now();
+    while !ctx.interrupted() {
+        buf.clear();
+        let bytes_read = reader.read_until(b'\n', &mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        let mut line = decode_substituting(&buf);
+        utils::trim_newline(&mut line);
+        if !filter(&line).is_empty() {
+            buckets.push(bucket_of(offset.into(), file_length));
+        }
+        offset += bytes_read as u64;
+        if last_push.elapsed() > PUSH_INTERVAL {
+            ctx.update_progress_u64(offset, file_length.as_u64());
+            let _ = ctx.send_message(mem::take(&mut buckets));
+            last_push = Instant::now();
+        }
+    }
+    if !buckets.is_empty() {
+        let _ = ctx.send_message(buckets);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::{bucket_of, Density, RESOLUTION};
+
+    #[test]
+    fn test_bucket_of() {
+        assert_that!(bucket_of(0.into(), 100.into())).is_equal_to(0);
+        assert_that!(bucket_of(50.into(), 100.into())).is_equal_to(RESOLUTION / 2);
+        assert_that!(bucket_of(100.into(), 100.into())).is_equal_to(RESOLUTION - 1);
+        assert_that!(bucket_of(10.into(), 0.into())).is_equal_to(0);
+    }
+
+    #[test]
+    fn test_density_count() {
+        let mut density = Density::default();
+        density.add(0);
+        density.add(1);
+        density.add(RESOLUTION - 1);
+        asserting!("First row").that(&density.count(0, 2)).is_equal_to(2);
+        asserting!("Second row").that(&density.count(1, 2)).is_equal_to(1);
+        asserting!("More rows than buckets").that(&density.count(0, 2 * RESOLUTION)).is_equal_to(1);
+    }
+
+    #[test]
+    fn test_density_clear() {
+        let mut density = Density::default();
+        density.add(5);
+        density.clear();
+        assert_that!(density.count(0, 1)).is_equal_to(0);
+    }
+}
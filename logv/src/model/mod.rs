@@ -14,7 +14,23 @@ pub mod metrics_model;
 pub mod progress_model;
 pub mod bgp_model;
 pub mod filter_model;
+pub mod minimap_model;
+pub mod clustering_model;
+pub mod log_template;
+pub mod field_table_model;
+pub mod parse_rule;
+pub mod logfmt;
+pub mod details_model;
+pub mod pretty_print;
+pub mod hex_view;
+pub mod date_input;
+pub mod date_format_model;
+pub mod time_zone;
+pub mod time_delta;
+pub mod theme_model;
+pub mod session;
+pub mod file_preview;
 mod navigable_searcher_constructor;
-mod abstract_go_to_model;
-mod guess_date_format;
+pub mod abstract_go_to_model;
+pub mod guess_date_format;
 mod escape_handler;
@@ -1,22 +1,24 @@
-use std::cell::{Ref, RefMut};
+use std::cell::{Cell, Ref, RefMut};
 use std::cmp::{max, min, Ordering};
+use std::collections::{BTreeMap, BTreeSet};
 use std::env::current_dir;
 use std::fmt::Debug;
 use std::fs::File;
-use std::io::{Read, Seek};
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::mem;
 use std::option::Option::Some;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 
 use anyhow::anyhow;
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Utc};
 use crossbeam_channel::Sender;
 use fluent_integer::Integer;
 use num_rational::Ratio;
 use num_traits::identities::Zero;
+use regex::Regex;
 use uuid::Uuid;
 
 use ModelEvent::*;
@@ -28,29 +30,53 @@ use crate::background_process::background_process_registry::BackgroundProcessReg
 use crate::background_process::run_in_background::RunInBackground;
 use crate::background_process::signal::Signal;
 use crate::background_process::task_context::TaskContext;
+use crate::config::{AppConfig, HighlightRule};
+use crate::data_source::concat_backend::ConcatBackend;
+use crate::data_source::file_set;
 use crate::data_source::filtered::FilteredLineSource;
+use crate::data_source::line_index_cache::LineIndexCache;
+use crate::data_source::delimiter::Delimiter;
+use crate::data_source::encoding::Encoding;
+use crate::data_source::mmap_backend::MmapBackend;
 use crate::data_source::line_registry::{LineRegistry, LineRegistryError, LineRegistryImpl};
-use crate::data_source::line_source_holder::{ConcreteLineSourceHolder, LineSourceHolder};
-use crate::data_source::{Data, Direction, FileBackend, LineSource, LineSourceBackend, LineSourceImpl, StrBackend};
+use crate::data_source::line_source_holder::{ConcreteLineSourceHolder, LineSourceFactory, LineSourceHolder};
+use crate::data_source::time_index::TimeIndex;
+use crate::data_source::transcoded::TranscodedBackend;
+use crate::data_source::reader_factory::file::FileBasedReaderFactory;
+use crate::data_source::reader_factory::{HasReaderFactory, ReaderFactory};
+use crate::data_source::{CustomHighlight, Direction, FileBackend, LineSource, LineSourceBackend, LineSourceImpl, StrBackend};
 use crate::interval::{Interval, IntervalBound};
 use crate::model::bgp_model::{BGPModel, BGPModelEvent};
+use crate::model::clustering_model::{ClusteringModel, ClusteringModelEvent};
 use crate::model::cursor_helper;
 use crate::model::cursor_shift::CursorShift;
+use crate::model::date_format_model::{DateFormatModel, DateFormatModelEvent};
+use crate::model::details_model::{DetailsModel, DetailsModelEvent};
 use crate::model::dimension::Dimension;
 use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandler, EscapeHandlerResult};
+use crate::model::field_table_model::{FieldTableModel, FieldTableModelEvent};
 use crate::model::filter_model::{FilterDialogModel, FilterDialogModelEvent};
 use crate::model::go_to_date_model::GoToDateModel;
 use crate::model::go_to_line_model::GoToLineModel;
-use crate::model::guess_date_format::{guess_date_format, GuessContext, KnownDateFormat};
+use crate::model::guess_date_format::{guess_date_format, DateSample, GuessContext, KnownDateFormat};
 use crate::model::help_model::{HelpModel, HelpModelEvent};
+use crate::model::hex_view::{HexView, BYTES_PER_ROW};
+use crate::model::theme_model::{ThemeModel, ThemeModelEvent};
+use crate::model::time_delta::{find_gap, format_delta, time_deltas};
+use crate::model::time_zone::{AssumedZone, DisplayZone};
+use crate::model::logfmt;
+use crate::model::logfmt::FieldFilter;
+use crate::model::pretty_print::pretty_print;
 use crate::model::metrics_model::{MetricsHolder, MetricsModel, MetricsModelEvent};
+use crate::model::minimap_model::{HitKind, MinimapModel};
 use crate::model::open_file_model::{OpenFileModel, OpenFileModelEvent};
 use crate::model::progress_model::{ProgressModel, ProgressModelEvent};
-use crate::model::rendered::{DataRender, LineNumberMissingReason, LineNumberResult, LineRender};
+use crate::model::rendered::{DataRender, LineNumberMissingReason, LineNumberResult, LineRender, LineRenderer};
 use crate::model::scroll_position::ScrollPosition;
 use crate::model::search::Search;
 use crate::model::search_model::SearchModel;
-use crate::profiles::OS_PROFILE;
+use crate::model::session::{file_checksum, file_stamp, now, FilterSpec, SearchSpec, Session, Sessions};
+use crate::profiles::Profile;
 use crate::search::searcher::SearchResult;
 use crate::shared::Shared;
 use crate::utils;
@@ -58,6 +84,10 @@ use crate::utils::event_emitter::EventEmitter;
 use crate::utils::GraphemeRender;
 
 const OFFSET_THRESHOLD: u64 = 8192;
+/// Number of lines above viewport looked through for the date the first time delta is counted from
+const TIME_DELTA_LOOKBACK: i64 = 100;
+/// How often the view is updated while a file is being decoded
+const DECODING_UPDATE_RATE: std::time::Duration = std::time::Duration::from_millis(500);
 
 pub struct RootModel {
     model_sender: Sender<ModelEvent>,
@@ -67,18 +97,40 @@ pub struct RootModel {
     action_registry: Shared<ActionRegistry>,
     open_file_model: Shared<OpenFileModel>,
     file_name: Option<String>,
+    // files shown one after another, if a directory or a glob is opened
+    concat: Option<ConcatBackend>,
+    // index of the concatenated file the cursor is in, shown in the status panel
+    current_part: Cell<Option<usize>>,
+    // file in other encoding than UTF-8, which offsets differ from offsets in the file
+    transcoded: Option<TranscodedBackend>,
+    // the rest of the file is being decoded in background, see [Self::load_transcoded]
+    decoding_handler: Option<BackgroundProcessHandler>,
+    use_mmap: bool,
+    encoding: Option<Encoding>,
+    delimiter: Option<Delimiter>,
+    assumed_zone: AssumedZone,
+    display_zone: DisplayZone,
     is_file_loaded: bool,
     data: Option<DataRender>,
     viewport_height: Integer,
     viewport_width: Integer,
     scroll_position: ScrollPosition,
     horizontal_scroll: Integer,
+    soft_wrap: bool,
+    hex_view: Option<HexView>,
     cursor: Integer,
     selection: Interval<Integer>,
     datasource: Option<Shared<LineSourceHolder>>,
     error: Option<Box<dyn ToString>>,
     show_line_numbers: bool,
     date_format: Option<&'static KnownDateFormat>, // guessed from content
+    // dates of the original source at every few KB
+    time_index: Option<Arc<TimeIndex>>,
+    time_index_handler: Option<BackgroundProcessHandler>,
+    // time deltas of visible lines, by line start
+    time_deltas: Option<BTreeMap<Integer, Duration>>,
+    time_reference: Option<NaiveDateTime>,
+    gap_threshold: Duration,
     // search
     search_model: Shared<SearchModel<BGPModel>>,
     current_search: Shared<Option<Search>>,
@@ -87,6 +139,7 @@ pub struct RootModel {
     go_to_date_model: Shared<GoToDateModel<BGPModel>>,
     // filter
     filter_dialog_model: Shared<FilterDialogModel>,
+    filter_spec: Option<FilterSpec>,
     // help
     help_model: Shared<HelpModel>,
     // metrics
@@ -94,6 +147,21 @@ pub struct RootModel {
     // modal progress dialog
     progress_model: Shared<ProgressModel>,
     bgp_model: Shared<BGPModel>,
+    // minimap
+    minimap_model: Shared<MinimapModel>,
+    bookmarks: BTreeSet<Integer>, // offsets in original source
+    // pattern clustering
+    clustering_model: Shared<ClusteringModel>,
+    date_format_model: Shared<DateFormatModel>,
+    // fields extracted by parse rule
+    field_table_model: Shared<FieldTableModel>,
+    details_model: Shared<DetailsModel>,
+    theme_model: Shared<ThemeModel>,
+    // settings in effect
+    config: AppConfig,
+    highlight_rules: Vec<(Regex, HighlightRule)>,
+    // positions in files opened before
+    sessions: Sessions,
 }
 
 #[derive(Debug)]
@@ -115,7 +183,12 @@ pub enum ModelEvent {
     ProgressEvent(ProgressModelEvent),
     BGPEvent(BGPModelEvent),
     FilterEvent(FilterDialogModelEvent),
+    ClusteringEvent(ClusteringModelEvent),
+    DateFormatEvent(DateFormatModelEvent),
+    FieldTableEvent(FieldTableModelEvent),
+    DetailsEvent(DetailsModelEvent),
     ThemeEvent(AppThemeName),
+    ThemeDialogEvent(ThemeModelEvent),
     Hint(String),
     Error(Option<String>),
     Quit,
@@ -134,12 +207,15 @@ impl RootModel {
         background_process_registry: Shared<BackgroundProcessRegistry>,
         metrics_holder: Option<MetricsHolder>,
         app_theme: AppTheme,
+        profile: &Profile,
     ) -> Shared<RootModel> {
-        let action_registry = Shared::new(ActionRegistry::new(&OS_PROFILE));
+        let action_registry = Shared::new(ActionRegistry::new(profile));
         let bgp_model = Shared::new(BGPModel::new(model_sender.clone(), background_process_registry.clone()));
         let escape_handler = Shared::new(CompoundEscapeHandler::new());
 
-        let open_file_model = OpenFileModel::new(model_sender.clone(), escape_handler.clone());
+        let sessions = Sessions::load();
+        let mut open_file_model = OpenFileModel::new(model_sender.clone(), bgp_model.clone(), escape_handler.clone());
+        open_file_model.set_recent_files(sessions.recent_files());
         let search_model = SearchModel::new(model_sender.clone(), bgp_model.clone(), escape_handler.clone());
         let help_model = HelpModel::new(model_sender.clone(), &*action_registry.get_ref(), escape_handler.clone());
         let metrics_model = MetricsModel::new(model_sender.clone(), metrics_holder, escape_handler.clone());
@@ -147,6 +223,12 @@ impl RootModel {
         let go_to_date_model = GoToDateModel::new(model_sender.clone(), bgp_model.clone(), escape_handler.clone());
         let filter_dialog_model = FilterDialogModel::new(model_sender.clone(), escape_handler.clone());
         let progress_model = ProgressModel::new(model_sender.clone(), background_process_registry.clone());
+        let minimap_model = MinimapModel::new(model_sender.clone(), bgp_model.clone());
+        let clustering_model = ClusteringModel::new(model_sender.clone(), bgp_model.clone(), escape_handler.clone());
+        let date_format_model = DateFormatModel::new(model_sender.clone(), bgp_model.clone(), escape_handler.clone());
+        let field_table_model = FieldTableModel::new(model_sender.clone(), escape_handler.clone());
+        let details_model = DetailsModel::new(model_sender.clone(), escape_handler.clone());
+        let theme_model = ThemeModel::new(model_sender.clone(), escape_handler.clone());
 
         let root_model = RootModel {
             model_sender,
@@ -156,27 +238,54 @@ impl RootModel {
             action_registry,
             open_file_model: Shared::new(open_file_model),
             file_name: None,
+            concat: None,
+            current_part: Cell::new(None),
+            transcoded: None,
+            decoding_handler: None,
+            use_mmap: false,
+            encoding: None,
+            delimiter: None,
+            assumed_zone: AssumedZone::default(),
+            display_zone: DisplayZone::default(),
             is_file_loaded: false,
             data: None,
             viewport_height: 0.into(),
             viewport_width: 0.into(),
             scroll_position: ScrollPosition::default(),
             horizontal_scroll: 0.into(),
+            soft_wrap: false,
+            hex_view: None,
             cursor: 0.into(),
             selection: Interval::empty(),
             datasource: None,
             error: None,
             show_line_numbers: true,
             date_format: None,
+            time_index: None,
+            time_index_handler: None,
+            time_deltas: None,
+            time_reference: None,
+            gap_threshold: Duration::minutes(1),
             search_model: Shared::new(search_model),
             current_search: Shared::new(None),
             go_to_line_model: Shared::new(go_to_line_model),
             go_to_date_model: Shared::new(go_to_date_model),
             filter_dialog_model: Shared::new(filter_dialog_model),
+            filter_spec: None,
             help_model: Shared::new(help_model),
             metrics_model: Shared::new(metrics_model),
             progress_model: Shared::new(progress_model),
             bgp_model,
+            minimap_model: Shared::new(minimap_model),
+            bookmarks: BTreeSet::new(),
+            clustering_model: Shared::new(clustering_model),
+            date_format_model: Shared::new(date_format_model),
+            field_table_model: Shared::new(field_table_model),
+            details_model: Shared::new(details_model),
+            theme_model: Shared::new(theme_model),
+            config: AppConfig::default(),
+            highlight_rules: vec![],
+            sessions,
         };
 
         Shared::new(root_model)
@@ -194,14 +303,75 @@ impl RootModel {
         self.file_name.as_ref().map(|s| &s[..])
     }
 
+    /// Files loaded afterwards are mapped into memory, if possible
+    pub fn set_use_mmap(&mut self, use_mmap: bool) {
+        self.use_mmap = use_mmap;
+    }
+
+    /// Encoding of files loaded afterwards. If `None`, it is detected for each file.
+    pub fn set_encoding(&mut self, encoding: Option<Encoding>) {
+        self.encoding = encoding;
+    }
+
+    /// Record delimiter of files loaded afterwards. If `None`, it is detected for each file.
+    pub fn set_delimiter(&mut self, delimiter: Option<Delimiter>) {
+        self.delimiter = delimiter;
+    }
+
+    /// Time zone of dates that have no zone in files loaded afterwards
+    pub fn set_assumed_zone(&mut self, assumed_zone: AssumedZone) {
+        self.assumed_zone = assumed_zone;
+    }
+
+    /// Applies settings that the model is responsible for. Returns errors of the settings that
+    /// are skipped.
+    pub fn apply_config(&mut self, config: AppConfig) -> Vec<String> {
+        let mut errors = vec![];
+        self.soft_wrap = config.soft_wrap;
+        self.set_show_line_numbers(config.show_line_numbers);
+        self.highlight_rules = config.highlights.iter()
+            .filter_map(|rule| {
+                let checked = Regex::new(&rule.pattern)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|regex| self.app_theme.rule_style(rule).map(|_| regex));
+                match checked {
+                    Ok(regex) => Some((regex, rule.clone())),
+                    Err(err) => {
+                        errors.push(format!("Highlight rule {} is skipped: {}", rule, err));
+                        None
+                    }
+                }
+            })
+            .collect();
+        let mut filter_dialog_model = self.filter_dialog_model.get_mut_ref();
+        filter_dialog_model.set_field_filter(config.filter.field);
+        filter_dialog_model.set_neighbourhood(config.filter.neighbourhood);
+        drop(filter_dialog_model);
+        self.config = config;
+        errors
+    }
+
+    pub fn get_highlight_rules(&self) -> &[(Regex, HighlightRule)] {
+        &self.highlight_rules
+    }
+
+    /// Shows settings in effect: config file merged with command line arguments
+    pub fn show_config(&mut self) {
+        self.details_model.get_mut_ref().show("Configuration", self.config.describe(), vec![]);
+    }
+
     pub fn set_file_name(&mut self, value: Option<&str>) {
         if self.file_name.as_deref().ne(&value) || !self.is_file_loaded {
             log::info!("File name set to {:?}", value);
+            if self.is_file_loaded {
+                self.save_session();
+            }
             self.file_name = value.map(String::from);
             self.search_model.get_mut_ref().set_file_name(value);
             self.go_to_date_model.get_mut_ref().set_value("");
             self.load_file();
             self.is_file_loaded = true;
+            self.restore_session();
         }
     }
 
@@ -209,8 +379,25 @@ impl RootModel {
         self.data.as_ref()
     }
 
-    fn set_data(&mut self, data: Data) {
-        self.data = Some(DataRender::new(data));
+    /// Renders lines with dates in the display zone, unless they are shown as written
+    fn line_renderer(&self) -> LineRenderer {
+        match self.date_format {
+            Some(date_format) if self.display_zone != DisplayZone::Original =>
+                LineRenderer::with_date_in_display_zone(date_format, self.get_date_guess_context()),
+            _ => LineRenderer::default(),
+        }
+    }
+
+    /// Renders cached lines again, e.g. once the zone or format of their dates is changed
+    fn refresh_data(&mut self) {
+        if self.datasource.is_some() {
+            self.update_viewport_content();
+        }
+    }
+
+    fn set_data(&mut self, data: DataRender) {
+        self.data = Some(data);
+        self.update_time_deltas();
         self.model_sender.emit_event(DataUpdated);
         self.emit_cursor_moved();
     }
@@ -227,6 +414,7 @@ impl RootModel {
             self.viewport_height = height;
             // TODO: emit event
             self.update_viewport_content();
+            self.update_hex_view();
         }
     }
 
@@ -258,8 +446,19 @@ impl RootModel {
         true
     }
 
+    /// Scrolls viewport by `n` screen rows, which are lines unless soft wrap is on
+    pub fn scroll(&mut self, n: Integer) -> bool {
+        if self.hex_view.is_some() {
+            return self.scroll_hex_rows(n);
+        }
+        match self.get_wrap_width() {
+            Some(width) => self.scroll_rows(n, width),
+            None => self.scroll_lines(n),
+        }
+    }
+
     #[profiling::function]
-    pub fn scroll(&mut self, num_of_lines: Integer) -> bool {
+    fn scroll_lines(&mut self, num_of_lines: Integer) -> bool {
         if num_of_lines == 0 {
             return true;
         }
@@ -309,8 +508,246 @@ impl RootModel {
         false
     }
 
+    /// Scrolls viewport by `n` visual rows of wrapped lines, keeping it full at the end of source
+    #[profiling::function]
+    fn scroll_rows(&mut self, n: Integer, width: usize) -> bool {
+        if n == 0 {
+            return true;
+        }
+        let Some(first_line) = self.data.as_ref().and_then(|data| data.lines.first()).cloned() else {
+            log::warn!("Scroll {} rows failed, no first line", n);
+            return false;
+        };
+        let Some(datasource) = self.datasource.clone() else {
+            log::warn!("Scroll {} rows failed: no datasource", n);
+            return false;
+        };
+        let first_row = self.get_first_row();
+        let first_line_rows = first_line.row_count(width);
+        let line_renderer = self.line_renderer();
+        let rows_of = |lines: Vec<LineRender>| -> Vec<(Integer, usize)> {
+            lines.into_iter()
+                .flat_map(|line| (0..line.row_count(width)).map(move |row| (line.start, row)))
+                .collect()
+        };
+        let (line_start, row) = if n > 0 {
+            let h = self.viewport_height;
+            let lines = datasource.get_mut_ref().read_lines(first_line.start, n + h).lines;
+            let rows = rows_of(lines.into_iter().map(|line| line_renderer.render(line)).collect());
+            let rows = &rows[first_row.min(rows.len())..];
+            let target = n.as_usize().min(rows.len().saturating_sub(h.as_usize()));
+            match rows.get(target) {
+                Some(position) => *position,
+                None => return false,
+            }
+        } else {
+            let mut lines: Vec<LineRender> = if first_line.start > 0 {
+                datasource.get_mut_ref().read_lines(first_line.start - 1, n).lines
+                    .into_iter()
+                    .map(|line| line_renderer.render(line))
+                    .collect()
+            } else {
+                vec![]
+            };
+            lines.push(first_line);
+            let rows = rows_of(lines);
+            let current = rows.len() - (first_line_rows - first_row);
+            rows[current.saturating_sub((-n).as_usize())]
+        };
+        self.set_scroll_position(ScrollPosition::from_beginning(line_start).with_row(row))
+    }
+
+    /// Switches between soft wrap of long lines and horizontal scrolling
+    pub fn toggle_soft_wrap(&mut self) {
+        self.soft_wrap = !self.soft_wrap;
+        self.horizontal_scroll = 0.into();
+        self.scroll_position = self.scroll_position.with_row(0);
+        if self.datasource.is_some() {
+            self.update_viewport_content();
+            self.bring_cursor_into_view();
+        }
+        self.model_sender.emit_event(DataUpdated);
+        let hint = if self.soft_wrap { "Soft wrap is on" } else { "Soft wrap is off" };
+        self.model_sender.emit_event(Hint(String::from(hint)));
+    }
+
+    /// Switches the zone dates are shown in: as written, UTC or local. Go to date compares dates
+    /// in the same zone.
+    pub fn switch_display_zone(&mut self) {
+        self.display_zone = self.display_zone.next();
+        self.build_time_index();
+        self.refresh_data();
+        self.model_sender.emit_event(DataUpdated);
+        let hint = match self.date_format {
+            Some(_) => format!("Dates are shown in {}", self.display_zone),
+            None => format!("Dates are shown in {}, but date format is not recognized", self.display_zone),
+        };
+        self.model_sender.emit_event(Hint(hint));
+    }
+
+    /// Switches between text and hex view. Both views share cursor and selection. Hex view shows
+    /// the original content, decoded files as they are on disk, so it is not available while a
+    /// filter is applied.
+    pub fn toggle_hex_view(&mut self) {
+        if self.hex_view.take().is_some() {
+            if self.datasource.is_some() {
+                self.bring_cursor_into_view();
+            }
+            self.model_sender.emit_event(Hint(String::from("Hex view is off")));
+        } else {
+            let Some(ds) = &self.datasource else { return };
+            if matches!(&*ds.get_ref(), LineSourceHolder::Filtered(_)) {
+                self.model_sender.emit_event(Hint(String::from("Hex view is not available while filter is applied")));
+                return;
+            }
+            // keep the cursor at the same screen row
+            let cursor_row = self.get_cursor_on_screen()
+                .map(|pos| pos.height.as_usize())
+                .unwrap_or(0);
+            let first_row = HexView::row_start(self.get_hex_cursor()) - cursor_row * BYTES_PER_ROW;
+            self.hex_view = Some(HexView::new(first_row.max(Integer::zero())));
+            self.update_hex_view();
+            self.model_sender.emit_event(Hint(String::from("Hex view is on")));
+        }
+        self.search_model.get_mut_ref().set_hex(self.hex_view.is_some());
+        self.model_sender.emit_event(DataUpdated);
+    }
+
+    pub fn get_hex_view(&self) -> Option<&HexView> {
+        self.hex_view.as_ref()
+    }
+
+    /// Reads bytes that fit into viewport of hex view
+    fn update_hex_view(&mut self) {
+        let (Some(hex_view), Some(length)) = (&self.hex_view, self.get_hex_length()) else { return };
+        let start = hex_view.get_first_row();
+        let end = (start + self.viewport_height * BYTES_PER_ROW).min(length).max(start);
+        match self.read_hex_bytes(start, end) {
+            Ok(bytes) => self.hex_view.as_mut().unwrap().set_bytes(bytes),
+            Err(err) => log::error!("Failed to read bytes {}..{}: {}", start, end, err),
+        }
+    }
+
+    /// Bytes between `start` and `end` offsets of hex view. Decoded files are shown as they are
+    /// on disk, other sources as they are read.
+    fn read_hex_bytes(&self, start: Integer, end: Integer) -> std::io::Result<Vec<u8>> {
+        if let Some(transcoded) = &self.transcoded {
+            let mut file = File::open(transcoded.path())?;
+            file.seek(SeekFrom::Start(start.as_u64()))?;
+            let mut bytes = vec![];
+            file.take((end - start).as_u64()).read_to_end(&mut bytes)?;
+            return Ok(bytes);
+        }
+        let Some(ds) = self.get_datasource_ref() else { return Ok(vec![]) };
+        ds.get_original().read_bytes(start, end)
+    }
+
+    /// Length of content shown in hex view, see [Self::read_hex_bytes]
+    fn get_hex_length(&self) -> Option<Integer> {
+        match &self.transcoded {
+            Some(transcoded) => Some(transcoded.file_length().into()),
+            None => self.get_datasource_ref().map(|ds| ds.get_original().get_length()),
+        }
+    }
+
+    /// Offset of the cursor in hex view, which shows decoded files as they are on disk
+    pub fn get_hex_cursor(&self) -> Integer {
+        self.to_file_offset(self.cursor)
+    }
+
+    /// Selection in offsets of hex view, see [Self::get_hex_cursor]
+    pub fn get_hex_selection(&self) -> Interval<Integer> {
+        self.selection.map(|offset| self.to_file_offset(*offset))
+    }
+
+    /// Cursor offset of byte at `offset` in hex view. Moving `forward`, bytes of the char under
+    /// cursor are skipped, as they all map to the same cursor offset.
+    fn hex_offset_to_cursor(&self, offset: Integer, forward: bool) -> Integer {
+        let Some(transcoded) = &self.transcoded else { return offset };
+        let length = transcoded.file_length();
+        let mut offset = offset.as_u64();
+        let mut cursor = transcoded.to_decoded_offset(offset);
+        while forward && cursor <= self.cursor.as_u64() && offset < length {
+            offset += 1;
+            cursor = transcoded.to_decoded_offset(offset);
+        }
+        cursor.into()
+    }
+
+    /// Decoded file, if hex search looks for bytes in the file instead of the decoded content
+    pub fn get_hex_search_file(&self, is_hex: bool) -> Option<TranscodedBackend> {
+        self.transcoded.clone().filter(|_| is_hex && self.hex_view.is_some())
+    }
+
+    /// Reader of content to search in: the file itself for hex search in a decoded file
+    pub fn get_search_reader_factory(&self, is_hex: bool) -> Option<Box<dyn ReaderFactory>> {
+        match self.get_hex_search_file(is_hex) {
+            Some(transcoded) => Some(Box::new(FileBasedReaderFactory::new(transcoded.path().to_path_buf()))),
+            None => self.get_datasource_ref().map(|ds| ds.reader_factory()),
+        }
+    }
+
+    /// Offset search starts from, when it starts from cursor
+    pub fn get_search_cursor(&self, is_hex: bool) -> Integer {
+        match self.get_hex_search_file(is_hex) {
+            Some(_) => self.get_hex_cursor(),
+            None => self.cursor,
+        }
+    }
+
+    /// Scrolls hex view by `n` rows
+    fn scroll_hex_rows(&mut self, n: Integer) -> bool {
+        let Some(length) = self.get_hex_length() else { return false };
+        let height = self.viewport_height.as_usize();
+        let Some(hex_view) = &mut self.hex_view else { return false };
+        let max_first_row = HexView::max_first_row(length, height);
+        let first_row = (hex_view.get_first_row() + n * BYTES_PER_ROW)
+            .min(max_first_row)
+            .max(Integer::zero());
+        if first_row == hex_view.get_first_row() {
+            return false;
+        }
+        hex_view.set_first_row(first_row);
+        self.update_hex_view();
+        self.model_sender.emit_event(DataUpdated);
+        true
+    }
+
+    /// Number of graphemes a line is wrapped at, if soft wrap is on
+    pub fn get_wrap_width(&self) -> Option<usize> {
+        self.soft_wrap.then(|| self.viewport_width.as_usize().max(1))
+    }
+
+    /// Number of rows of the first line in viewport that are scrolled out of view
+    fn get_first_row(&self) -> usize {
+        let rows = self.get_wrap_width()
+            .zip(self.data.as_ref().and_then(|data| data.lines.first()))
+            .map(|(width, line)| line.row_count(width))
+            .unwrap_or(1);
+        self.scroll_position.row().min(rows - 1)
+    }
+
+    /// Screen rows of cached lines that fit into viewport as pairs of line index in cache and
+    /// row index in the line. Row index is always 0 unless soft wrap is on.
+    pub fn get_visible_rows(&self) -> Vec<(usize, usize)> {
+        let Some(data) = &self.data else { return vec![] };
+        let height = self.viewport_height.as_usize();
+        match self.get_wrap_width() {
+            Some(width) => data.lines.iter()
+                .enumerate()
+                .flat_map(|(i, line)| (0..line.row_count(width)).map(move |row| (i, row)))
+                .skip(self.get_first_row())
+                .take(height)
+                .collect(),
+            None => (0..data.lines.len().min(height)).map(|i| (i, 0)).collect(),
+        }
+    }
+
     pub fn set_horizontal_scroll(&mut self, horizontal_scroll: Integer) -> bool {
         log::trace!("set_horizontal_scroll {}", horizontal_scroll);
+        if self.soft_wrap {
+            return false;
+        }
         match self.horizontal_scroll.cmp(&horizontal_scroll) {
             Ordering::Less => {
                 if let Some(data) = &self.data {
@@ -354,6 +791,11 @@ impl RootModel {
     #[profiling::function]
     pub fn move_cursor(&mut self, delta: CursorShift, adjust_selection: bool) {
         log::trace!("move_cursor: delta = {:?}", delta);
+        if self.hex_view.is_some() {
+            let new_cursor_offset = self.move_cursor_in_hex_view(delta);
+            self.move_cursor_to_offset(new_cursor_offset, adjust_selection);
+            return;
+        }
         let current_pos = self.get_cursor_in_cache(); // TODO
         log::trace!("move_cursor: pos = {} -> on_screen = {:?}", self.cursor, current_pos);
 
@@ -402,6 +844,9 @@ impl RootModel {
 
     fn move_cursor_vertically(&mut self, dy: Integer, pos: Dimension) -> Integer {
         log::trace!("move_cursor_vertically current_pos = {:?}, deltaY = {}", pos, dy);
+        if let Some(width) = self.get_wrap_width() {
+            return self.move_cursor_over_rows(dy, pos, width);
+        }
         let calc_offset_in_line = |line: &LineRender| {
             let graphemes: Vec<&GraphemeRender> = line.render.iter().collect();
             let g = graphemes.get(pos.width.as_usize())
@@ -412,6 +857,7 @@ impl RootModel {
         };
 
         let y = pos.height + dy;
+        let line_renderer = self.line_renderer();
         let data = self.data.as_ref().unwrap();
         let n = data.lines.len();
         if y >= 0 && y < n {
@@ -424,11 +870,11 @@ impl RootModel {
                 let offset = data.end.map_or(0.into(), |x| x + 1);
                 let mut new_lines = datasource.read_lines(offset, y + 1).lines;
                 if y < new_lines.len() {
-                    let line = LineRender::new(new_lines.remove(y.as_usize()));
+                    let line = line_renderer.render(new_lines.remove(y.as_usize()));
                     calc_offset_in_line(&line)
                 } else {
                     new_lines.pop()
-                        .map(LineRender::new)
+                        .map(|line| line_renderer.render(line))
                         .as_ref()
                         .or_else(|| data.lines.last())
                         .map(calc_offset_in_line)
@@ -440,7 +886,7 @@ impl RootModel {
                     .map(|i| datasource.read_lines(*i - 1, y).lines)
                     .unwrap_or_default();
                 if !new_lines.is_empty() {
-                    calc_offset_in_line(&LineRender::new(new_lines.remove(0)))
+                    calc_offset_in_line(&line_renderer.render(new_lines.remove(0)))
                 } else {
                     calc_offset_in_line(data.lines.first().unwrap())
                 }
@@ -448,6 +894,79 @@ impl RootModel {
         }
     }
 
+    /// Same as [Self::move_cursor_vertically], but `dy` denotes number of screen rows of wrapped
+    /// lines. The cursor keeps its column within a row, if possible.
+    fn move_cursor_over_rows(&self, dy: Integer, pos: Dimension, width: usize) -> Integer {
+        let direction = Direction::from(dy >= 0);
+        let mut line_iterator = cursor_helper::LineIterator::new(
+            self.data.as_ref().unwrap(),
+            self.datasource.as_ref().unwrap().get_mut_ref(),
+            self.line_renderer(),
+            direction,
+            pos.height
+        );
+        let Some(mut line) = line_iterator.next() else { return self.cursor };
+        let column = pos.width.as_usize() % width;
+        let mut row = pos.width.as_usize() / width;
+        let mut remaining = dy.abs().as_usize();
+        loop {
+            let rows = line.row_count(width);
+            match direction {
+                Direction::Forward => {
+                    if row + remaining < rows {
+                        row += remaining;
+                        break;
+                    }
+                    match line_iterator.next() {
+                        Some(next_line) => {
+                            remaining -= rows - row;
+                            line = next_line;
+                            row = 0;
+                        },
+                        None => {
+                            row = rows - 1;
+                            break;
+                        }
+                    }
+                },
+                Direction::Backward => {
+                    if remaining <= row {
+                        row -= remaining;
+                        break;
+                    }
+                    match line_iterator.next() {
+                        Some(prev_line) => {
+                            remaining -= row + 1;
+                            row = prev_line.row_count(width) - 1;
+                            line = prev_line;
+                        },
+                        None => {
+                            row = 0;
+                            break;
+                        }
+                    }
+                },
+            }
+        }
+        let index = (row * width + column).min(line.render.len().saturating_sub(1));
+        line.start + line.render.get(index).map(|g| g.original_offset).unwrap_or(0)
+    }
+
+    /// Calculates new cursor offset in hex view, where `X` moves over bytes and `Y` over rows
+    fn move_cursor_in_hex_view(&self, delta: CursorShift) -> Integer {
+        let Some(length) = self.get_hex_length() else { return self.cursor };
+        let Some(mut ds) = self.get_datasource_ref() else { return self.cursor };
+        let hex_cursor = self.get_hex_cursor();
+        let offset = match delta {
+            CursorShift::X(x) => hex_cursor + x,
+            CursorShift::Y(y) => hex_cursor + y * BYTES_PER_ROW,
+            CursorShift::TokenForward => return ds.skip_token(self.cursor, Direction::Forward).unwrap_or(self.cursor),
+            CursorShift::TokenBackward => return ds.skip_token(self.cursor, Direction::Backward).unwrap_or(self.cursor),
+        };
+        let offset = offset.min(length).max(Integer::zero());
+        self.hex_offset_to_cursor(offset, offset > hex_cursor)
+    }
+
     /// Calculates new cursor offset and assigns it to model. `dx` denotes number of *graphemes*
     /// to move over. `pos` denotes cursor position in cache.
     fn move_cursor_horizontally(&mut self, mut dx: Integer, pos: Dimension) -> Integer {
@@ -460,6 +979,7 @@ impl RootModel {
         let mut line_iterator = cursor_helper::LineIterator::new(
             self.data.as_ref().unwrap(),
             self.datasource.as_ref().unwrap().get_mut_ref(),
+            self.line_renderer(),
             direction,
             pos.height
         );
@@ -516,13 +1036,36 @@ impl RootModel {
         self.cursor
     }
 
+    /// Start and end offsets of the line that contains the cursor, or of the row in hex view
+    pub fn get_cursor_line_bounds(&self) -> Option<(Integer, Integer)> {
+        if self.hex_view.is_some() {
+            let length = self.get_hex_length()?;
+            let start = HexView::row_start(self.get_hex_cursor());
+            let end = (start + BYTES_PER_ROW).min(length);
+            return Some((self.hex_offset_to_cursor(start, false), self.hex_offset_to_cursor(end, false)));
+        }
+        self.get_cursor_line().map(|line| (line.start, line.end))
+    }
+
+    /// Cached line that contains the cursor
+    pub fn get_cursor_line(&self) -> Option<&LineRender> {
+        self.get_cursor_in_cache()
+            .zip(self.data.as_ref())
+            .and_then(|(pos, data)| data.lines.get(pos.height.as_usize()))
+    }
+
     /// Calculates cursor position in terms of screen coordinates. Returns `None` if cursor is
     /// outside the cache. The method does not guarantee that the result is inside viewport.
     ///
-    /// Result's `height` is number of line.
+    /// Result's `height` is number of line, or number of screen row if soft wrap is on.
     ///
     /// Result's `width` is given in terms of *graphemes*.
     pub fn get_cursor_on_screen(&self) -> Option<Dimension> {
+        if let Some(width) = self.get_wrap_width() {
+            return self.get_cursor_in_cache()
+                .map(|pos| self.to_screen_position(pos, width))
+                .filter(|pos| pos.height >= 0);
+        }
         let horizontal_scroll = self.horizontal_scroll.as_usize();
         let result = self.get_cursor_in_cache().zip(self.data.as_ref())
             .and_then(|(p, data)|
@@ -538,24 +1081,38 @@ impl RootModel {
         result
     }
 
-    pub fn quit(&self) {
+    pub fn quit(&mut self) {
         // TODO: close datasource
+        self.save_session();
         self.model_sender.emit_event(Quit);
     }
 
+    /// Switches to the next available theme
     pub fn trigger_theme_switch(&self) {
-        let new_name = match &self.app_theme.name {
-            AppThemeName::SolarizedLight => AppThemeName::SolarizedDark,
-            AppThemeName::SolarizedDark => AppThemeName::SolarizedLight,
-        };
-        self.model_sender.emit_event(ThemeEvent(new_name));
+        let themes = AppThemeName::available();
+        let next = themes.iter()
+            .position(|name| *name == self.app_theme.name)
+            .map_or(0, |i| (i + 1) % themes.len());
+        self.set_theme(themes[next].clone());
+    }
+
+    pub fn set_theme(&self, name: AppThemeName) {
+        self.model_sender.emit_event(Hint(format!("Theme {}", name)));
+        self.model_sender.emit_event(ThemeEvent(name));
+    }
+
+    pub fn get_theme_model(&self) -> RefMut<ThemeModel> {
+        self.theme_model.get_mut_ref()
+    }
+
+    pub fn open_theme_dialog(&mut self) {
+        self.theme_model.get_mut_ref().set_open(true);
     }
 
     pub fn is_show_line_numbers(&self) -> bool {
         self.show_line_numbers
     }
 
-    #[allow(dead_code)]
     pub fn set_show_line_numbers(&mut self, show_line_numbers: bool) {
         self.show_line_numbers = show_line_numbers;
         if let Some(ds) = &self.datasource {
@@ -645,32 +1202,196 @@ impl RootModel {
 
     fn load_file(&mut self) {
         self.reset(true);
+        self.concat = None;
+        self.transcoded = None;
+        if let Some(handler) = self.decoding_handler.take() {
+            handler.interrupt();
+        }
         if let Some(path) = self.resolve_file_name() {
-            let line_source = LineSourceImpl::<File, FileBackend>::from_file_name(path.clone());
-            let backend = FileBackend::new(path.clone());
-            self.guess_date_format(&path);
-            self.do_load_file(ConcreteLineSourceHolder::from(line_source), backend, self.file_name.as_ref().unwrap().to_string())
+            if file_set::is_file_set(&path) {
+                self.load_file_set(&path);
+                return;
+            }
+            let file_name = self.file_name.as_ref().unwrap().to_string();
+            let encoding = self.encoding.unwrap_or_else(|| Encoding::detect_file(&path)
+                .unwrap_or_else(|err| {
+                    log::warn!("Failed to detect encoding of {:?}: {}", path, err);
+                    Encoding::Utf8
+                }));
+            let delimiter = self.delimiter.clone().unwrap_or_else(|| Delimiter::detect_file(&path)
+                .unwrap_or_else(|err| {
+                    log::warn!("Failed to detect delimiter of {:?}: {}", path, err);
+                    Delimiter::NewLine
+                }));
+            if encoding.needs_decoding() || !delimiter.is_new_line() {
+                self.load_transcoded(path, encoding, delimiter, file_name);
+                return;
+            }
+            self.guess_date_format();
+            let mmap_backend = Some(&path)
+                .filter(|_| self.use_mmap)
+                .and_then(|path| MmapBackend::new(path)
+                    .map_err(|err| log::info!("Failed to map {:?}, falling back to reading: {}", path, err))
+                    .ok());
+            if let Some(backend) = mmap_backend {
+                let line_source = LineSourceImpl::new(backend.clone());
+                self.do_load_file(ConcreteLineSourceHolder::from(line_source), backend, file_name, Some(path))
+            } else {
+                let line_source = LineSourceImpl::<File, FileBackend>::from_file_name(path.clone());
+                let backend = FileBackend::new(path.clone());
+                self.do_load_file(ConcreteLineSourceHolder::from(line_source), backend, file_name, Some(path))
+            }
         } else {
-            let welcome: &'static str = &crate::welcome::WELCOME;
-            let line_source = LineSourceImpl::from_str(welcome);
-            let backend = StrBackend::new(welcome);
-            self.do_load_file(ConcreteLineSourceHolder::from(line_source), backend, String::from("welcome"))
+            self.load_welcome();
         };
 
     }
 
+    fn load_welcome(&mut self) {
+        let welcome: &'static str = &crate::welcome::WELCOME;
+        let line_source = LineSourceImpl::from_str(welcome);
+        let backend = StrBackend::new(welcome);
+        self.do_load_file(ConcreteLineSourceHolder::from(line_source), backend, String::from("welcome"), None)
+    }
+
+    /// Shows files of a directory or matching a glob one after another. Their encoding is not
+    /// detected, they are read as UTF-8 lines.
+    fn load_file_set(&mut self, path: &Path) {
+        let loaded = file_set::list_files(path)
+            .and_then(|file_set| Ok((ConcatBackend::new(&file_set.files)?, file_set.skipped_warning())));
+        let (backend, skipped_warning) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                log::warn!("Failed to open {:?}: {:?}", path, err);
+                self.set_error(Box::new(err));
+                self.load_welcome();
+                return;
+            }
+        };
+        let file_count = backend.parts().len();
+        self.concat = Some(backend.clone());
+        self.guess_date_format();
+        let file_name = format!("{} ({} files)", self.file_name.as_deref().unwrap_or_default(), file_count);
+        let line_source = LineSourceImpl::new(backend.clone());
+        self.do_load_file(ConcreteLineSourceHolder::from(line_source), backend, file_name, None);
+        if let Some(warning) = skipped_warning {
+            self.model_sender.emit_event(Hint(warning));
+        }
+    }
+
+    /// Opens a file in other encoding than UTF-8 or with records that are not delimited by line
+    /// breaks. Its first chunk is shown at once, while offsets of the rest of decoded content are
+    /// found out in background. Meanwhile the content grows, and once the whole file is decoded,
+    /// it is loaded anew, so that line numbers, the minimap and the time index cover it.
+    fn load_transcoded(&mut self, path: PathBuf, encoding: Encoding, delimiter: Delimiter, file_name: String) {
+        let backend = match TranscodedBackend::open_first_chunk(&path, encoding, delimiter) {
+            Ok(backend) => backend,
+            Err(err) => {
+                log::warn!("Failed to decode {}: {}", file_name, err);
+                self.set_error(Box::new(err));
+                self.load_welcome();
+                return;
+            }
+        };
+        self.transcoded = Some(backend.clone());
+        let line_source = LineSourceImpl::new(backend.clone());
+        self.do_load_file(ConcreteLineSourceHolder::from(line_source), backend.clone(), file_name.clone(), None);
+        let length = backend.file_length();
+        let description = format!("Decode {:?} from {} delimited by {}", path, encoding, delimiter);
+        let hint = match (encoding.needs_decoding(), delimiter.is_new_line()) {
+            (true, true) => format!("Decoded from {}", encoding),
+            (true, false) => format!("Decoded from {}, records are delimited by {}", encoding, delimiter),
+            (false, _) => format!("Records are delimited by {}", delimiter),
+        };
+
+        struct ChunksDecoded;
+
+        let task_backend = backend.clone();
+        let handler = self.background_process_builder()
+            .with_title("Decoding")
+            .with_description(description)
+            .with_task(move |ctx| {
+                let last_update = Cell::new(Instant::now());
+                task_backend.index_rest(|| ctx.interrupted(), |b| {
+                    ctx.update_progress_u64(b, length);
+                    if last_update.get().elapsed() >= DECODING_UPDATE_RATE {
+                        last_update.set(Instant::now());
+                        ctx.send_message(ChunksDecoded).expect("Failed to send update");
+                    }
+                })
+            })
+            .with_listener(move |model, signal, _| {
+                match signal {
+                    // lines at the end of decoded content may be shown
+                    Signal::Custom(ChunksDecoded) => {
+                        model.update_viewport_content();
+                        model.model_sender.emit_event(Repaint);
+                    }
+                    Signal::Complete(result) => {
+                        model.decoding_handler = None;
+                        match result {
+                            Ok(()) => {
+                                let line_source = LineSourceImpl::new(backend.clone());
+                                // offsets of decoded content differ from file offsets, so line index is not cached
+                                model.do_load_file(ConcreteLineSourceHolder::from(line_source), backend.clone(), file_name.clone(), None);
+                                // dates are looked for in decoded content, once all of it is known
+                                model.guess_date_format();
+                                model.model_sender.emit_event(Hint(hint.clone()));
+                                model.restore_session();
+                            }
+                            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                            Err(err) => {
+                                log::warn!("Failed to decode {}: {}", file_name, err);
+                                model.set_error(Box::new(err));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            })
+            .run();
+        self.decoding_handler = Some(handler);
+    }
+
+    /// Offset in the file of `offset` in the original source. They differ for files in other
+    /// encoding than UTF-8.
+    pub fn to_file_offset(&self, offset: Integer) -> Integer {
+        match &self.transcoded {
+            Some(transcoded) => transcoded.to_file_offset(offset.as_u64()).into(),
+            None => offset,
+        }
+    }
+
+    /// Offset in the original source of `file_offset`, see [Self::to_file_offset]
+    pub fn to_decoded_offset(&self, file_offset: Integer) -> Integer {
+        match &self.transcoded {
+            Some(transcoded) => transcoded.to_decoded_offset(file_offset.as_u64()).into(),
+            None => file_offset,
+        }
+    }
+
     fn do_load_file<R: Read + Seek + 'static, B: LineSourceBackend<R> + Send + 'static>(
         &mut self,
         mut line_source: ConcreteLineSourceHolder,
         backend: B,
-        file_name: String)
+        file_name: String,
+        path: Option<PathBuf>)
     {
         if self.show_line_numbers {
             line_source.track_line_number(true);
         }
         let file_size = line_source.get_length();
         self.datasource = Some(Shared::new(line_source.into()));
-        self.build_line_registry(backend, file_size);
+        self.build_line_registry(backend, file_size, path);
+        self.bookmarks.clear();
+        self.time_reference = None;
+        self.filter_spec = None;
+        self.current_part.set(None);
+        self.minimap_model.get_mut_ref().reset(file_size);
+        self.clustering_model.get_mut_ref().reset();
+        self.date_format_model.get_mut_ref().reset();
+        // the format may be pinned, otherwise the index is built once it is guessed
+        self.build_time_index();
 
         let event = FileName(file_name, file_size.as_u64());
         self.model_sender.emit_event(event);
@@ -697,6 +1418,9 @@ impl RootModel {
         self.scroll_position = ScrollPosition::default();
         self.horizontal_scroll = 0.into();
         self.datasource = None;
+        if self.hex_view.take().is_some() {
+            self.search_model.get_mut_ref().set_hex(false);
+        }
         self.reset_selection();
         if reset_date_format {
             self.date_format = None;
@@ -717,8 +1441,18 @@ impl RootModel {
             log::trace!("update_viewport_content data: {:?}", &data.lines[..min(3, data.lines.len())]);
 
             drop(datasource);
+            let data = DataRender::new(data, &self.line_renderer());
             // check if EOF is reached and viewport is not full
-            if data.lines.len() < self.viewport_height && offset > 0 {
+            let rows = match self.get_wrap_width() {
+                Some(width) => {
+                    let rows: usize = data.lines.iter()
+                        .map(|line| line.row_count(width))
+                        .sum();
+                    rows.saturating_sub(self.scroll_position.row())
+                },
+                None => data.lines.len(),
+            };
+            if rows < self.viewport_height && (offset > 0 || self.scroll_position.row() > 0) {
                 false
             } else {
                 self.set_data(data);
@@ -735,17 +1469,22 @@ impl RootModel {
     ///
     /// Result's `width` is a *rendered grapheme* index
     fn get_cursor_in_cache(&self) -> Option<Dimension> {
+        self.get_position_in_cache(self.cursor)
+    }
+
+    /// Same as [Self::get_cursor_in_cache] for arbitrary offset
+    fn get_position_in_cache(&self, offset: Integer) -> Option<Dimension> {
         let result = if let Some(data) = &self.data {
             let line_count = data.lines.len();
             let search = data.lines
-                .binary_search_by(|probe| probe.start.cmp(&self.cursor));
+                .binary_search_by(|probe| probe.start.cmp(&offset));
             match search {
                 Ok(n) => Some(Dimension::new(0, n)),
                 Err(0) => None,
                 Err(n) => {
                     let line = data.lines.get(n - 1).unwrap();
-                    if n < line_count || (n == line_count && self.cursor <= line.end) {
-                        let raw_offset = self.cursor - line.start;
+                    if n < line_count || (n == line_count && offset <= line.end) {
+                        let raw_offset = offset - line.start;
                         let grapheme_index = line.find_grapheme_index_by_offset(raw_offset);
                         Some(Dimension::new(grapheme_index.unwrap_or(0), n - 1))
                     } else {
@@ -756,23 +1495,82 @@ impl RootModel {
         } else {
             None
         };
-        log::trace!("get_cursor_in_cache for offset {} returned {:?}", self.cursor, result);
+        log::trace!("get_position_in_cache for offset {} returned {:?}", offset, result);
         result
     }
 
+    /// Converts position in cache to row and column on screen in soft wrap mode. Rows above
+    /// viewport are negative.
+    fn to_screen_position(&self, pos: Dimension, width: usize) -> Dimension {
+        let rows_above: usize = self.data.as_ref()
+            .map(|data| data.lines.iter()
+                .take(pos.height.as_usize())
+                .map(|line| line.row_count(width))
+                .sum())
+            .unwrap_or(0);
+        let index = pos.width.as_usize();
+        let row = Integer::from(rows_above + index / width) - self.get_first_row();
+        Dimension::new(Integer::from(index % width), row)
+    }
+
     /// Makes viewport fit the cursor, adjusting vertical and horizontal scroll if necessary
     fn bring_cursor_into_view(&mut self) -> bool {
         self.bring_into_view(self.cursor)
     }
 
     /// Makes `offset` visible, adjusting vertical and horizontal scroll if necessary
-    #[profiling::function]
     fn bring_into_view(&mut self, offset: Integer) -> bool {
+        if self.hex_view.is_some() {
+            return self.bring_hex_row_into_view(offset);
+        }
+        let result = self.bring_line_into_view(offset);
+        match self.get_wrap_width() {
+            Some(width) => self.bring_row_into_view(offset, width) || result,
+            None => result,
+        }
+    }
+
+    /// Scrolls hex view, so that the row containing `offset` is visible
+    fn bring_hex_row_into_view(&mut self, offset: Integer) -> bool {
+        let height = self.viewport_height.as_usize();
+        let offset = self.to_file_offset(offset);
+        let Some(hex_view) = &mut self.hex_view else { return false };
+        let first_row = hex_view.first_row_to_fit(offset, height);
+        if first_row != hex_view.get_first_row() {
+            hex_view.set_first_row(first_row);
+            self.update_hex_view();
+        }
+        self.model_sender.emit_event(DataUpdated);
+        true
+    }
+
+    /// Scrolls over rows of wrapped lines, so that `offset`, which is already in cache, is visible
+    fn bring_row_into_view(&mut self, offset: Integer, width: usize) -> bool {
+        let Some(pos) = self.get_position_in_cache(offset) else { return false };
+        let row = self.to_screen_position(pos, width).height;
+        let h = self.viewport_height;
+        if row < 0 {
+            self.scroll_rows(row, width)
+        } else if row >= h {
+            self.scroll_rows(row - h + 1, width)
+        } else {
+            false
+        }
+    }
+
+    /// Makes the line containing `offset` visible, adjusting vertical and horizontal scroll if
+    /// necessary
+    #[profiling::function]
+    fn bring_line_into_view(&mut self, offset: Integer) -> bool {
         log::trace!("bring_into_view(offset={})", offset);
         // if let Some(mut datasource) = self.get_datasource_ref() {
 
+        let line_renderer = self.line_renderer();
         let mut datasource = self.get_datasource_ref().unwrap();
         let calc_horizontal_scroll = |line: &LineRender, off: Integer| {
+            if self.soft_wrap {
+                return Integer::zero();
+            }
             let h = self.horizontal_scroll;
             let w = self.viewport_width;
             let local_offset = off - line.start;
@@ -824,7 +1622,7 @@ impl RootModel {
                     };
                     if success {
                         drop(datasource);
-                        self.scroll(i)
+                        self.scroll_lines(i)
                     } else {
                         false
                     }
@@ -855,7 +1653,7 @@ impl RootModel {
                     if success {
                         drop(datasource);
                         log::trace!("bring_into_view 3rd case. scroll {} lines", i);
-                        self.scroll(i)
+                        self.scroll_lines(i)
                     } else {
                         false
                     }
@@ -867,7 +1665,7 @@ impl RootModel {
         } else {
             log::trace!("bring_into_view. Raw case.");
             let (line_offset, horizontal_scroll) = datasource.read_next_line(offset)
-                .map(|line| (line.start, calc_horizontal_scroll(&LineRender::new(line), offset)))
+                .map(|line| (line.start, calc_horizontal_scroll(&line_renderer.render(line), offset)))
                 .unwrap_or((Integer::zero(), Integer::zero()));
             drop(datasource);
             let scroll_position = ScrollPosition::from_beginning(line_offset);
@@ -886,6 +1684,22 @@ impl RootModel {
         self.get_datasource_ref().map(|ds| ds.get_line_registry())
     }
 
+    /// Makes copies of the original source, i.e. the one that is not filtered, for background
+    /// tasks
+    pub fn get_line_source_factory(&self) -> Option<LineSourceFactory> {
+        self.get_datasource_ref().map(|ds| ds.get_original().factory())
+    }
+
+    /// Decoded file, if the file is not in UTF-8
+    pub fn get_transcoded(&self) -> Option<TranscodedBackend> {
+        self.transcoded.clone()
+    }
+
+    /// Dates of the original source, as far as they are indexed
+    pub fn get_time_index(&self) -> Option<Arc<TimeIndex>> {
+        self.time_index.clone()
+    }
+
     #[profiling::function]
     fn scroll_forcibly(&mut self, offset: Integer) -> bool {
         let mut datasource = self.get_datasource_ref().unwrap();
@@ -923,6 +1737,13 @@ impl RootModel {
         }
 
         let hint = r.as_ref().map(Search::get_hint).unwrap_or_default();
+        let query = r.as_ref().and_then(|_| {
+            let search_model = self.search_model.get_ref();
+            // minimap searches for text, so hex patterns are not shown there
+            (!search_model.is_hex()).then(|| (search_model.get_pattern().to_string(), search_model.is_regexp()))
+        });
+        drop(r);
+        self.update_minimap(HitKind::Search, query);
         self.model_sender.emit_event(Hint(hint));
     }
 
@@ -939,21 +1760,67 @@ impl RootModel {
     }
 
     pub fn filter(&mut self) -> anyhow::Result<()> {
-        let (pattern, neighbourhood) = {
+        let (pattern, neighbourhood, is_field_filter) = {
             let filter_dialog_model = &*self.filter_dialog_model.get_ref();
             let pattern = filter_dialog_model.get_pattern();
             let neighbourhood = u8::from_str(filter_dialog_model.get_neighbourhood())?;
-            (pattern.to_string(), neighbourhood)
+            (pattern.to_string(), neighbourhood, filter_dialog_model.is_field_filter())
         };
 
+        if is_field_filter {
+            return self.filter_by_field(&pattern, neighbourhood);
+        }
+        self.filter_by_substring(&pattern, neighbourhood)
+    }
+
+    /// Leaves only lines containing `pattern` and `neighbourhood` lines around them
+    pub fn filter_by_substring(&mut self, pattern: &str, neighbourhood: u8) -> anyhow::Result<()> {
+        let spec = FilterSpec::Substring { pattern: pattern.to_string(), neighbourhood };
+        self.apply_filter(
+            |base_ds| FilteredLineSource::with_substring(base_ds, pattern, neighbourhood),
+            spec,
+        )
+    }
+
+    /// Leaves only logfmt records matching `expression` (`key=value` or `key~regex`)
+    pub fn filter_by_field(&mut self, expression: &str, neighbourhood: u8) -> anyhow::Result<()> {
+        let field_filter = FieldFilter::parse(expression)?;
+        let spec = FilterSpec::Field { expression: expression.to_string(), neighbourhood };
+        let mapper = Arc::new(move |s: &str|
+            field_filter.find(s)
+                .map(|(start, end)| CustomHighlight::new(start, end))
+                .into_iter()
+                .collect()
+        );
+        self.apply_filter(
+            |base_ds| FilteredLineSource::new(base_ds, mapper, neighbourhood),
+            spec,
+        )
+    }
+
+    /// Leaves only lines matching regular expression `pattern`
+    pub fn filter_by_regex(&mut self, pattern: &str) -> anyhow::Result<()> {
+        let regex = Regex::new(pattern)?;
+        self.apply_filter(
+            |base_ds| FilteredLineSource::with_regex(base_ds, &regex, 0),
+            FilterSpec::Regex(pattern.to_string()),
+        )
+    }
+
+    fn apply_filter<F>(&mut self, create_filtered: F, spec: FilterSpec) -> anyhow::Result<()>
+    where
+        F: FnOnce(ConcreteLineSourceHolder) -> FilteredLineSource
+    {
         let ds = self.datasource.take().ok_or(anyhow!("DataSource not set"))?.into_inner();
         let base_ds = match ds {
             LineSourceHolder::Concrete(ds) => ds,
             LineSourceHolder::Filtered(f) => f.destroy(),
         };
-        let filtered = FilteredLineSource::with_substring(base_ds, &pattern, neighbourhood);
+        let filtered = create_filtered(base_ds);
         self.reset(false);
+        self.minimap_model.get_mut_ref().update_filtered(filtered.get_original(), filtered.get_filter());
         self.datasource = Some(Shared::new(LineSourceHolder::from(filtered)));
+        self.filter_spec = Some(spec);
         self.update_viewport_content();
         self.model_sender.emit_event(ModelEvent::Repaint);
         self.model_sender.emit_event(ModelEvent::Hint(String::from("Press ESC to return to original")));
@@ -980,6 +1847,8 @@ impl RootModel {
             };
             self.reset(false);
             self.datasource.replace(Shared::new(filtered.destroy().into()));
+            self.filter_spec = None;
+            self.update_minimap(HitKind::Filter, None);
             self.update_viewport_content();
             self.model_sender.emit_event(ModelEvent::Repaint);
             self.model_sender.emit_event(ModelEvent::Hint(String::new()));
@@ -1003,17 +1872,409 @@ impl RootModel {
         self.bgp_model.get_mut_ref()
     }
 
+    pub fn get_minimap_model(&self) -> RefMut<MinimapModel> {
+        self.minimap_model.get_mut_ref()
+    }
+
+    fn update_minimap(&self, kind: HitKind, query: Option<(String, bool)>) {
+        let Some(ds) = &self.datasource else { return; };
+        let ds = ds.get_ref();
+        self.minimap_model.get_mut_ref().update(kind, ds.get_original(), query);
+    }
+
+    pub fn get_clustering_model(&self) -> RefMut<ClusteringModel> {
+        self.clustering_model.get_mut_ref()
+    }
+
+    /// Opens list of message templates, starting analysis if necessary
+    pub fn open_clustering(&mut self) {
+        let Some(ds) = &self.datasource else { return; };
+        let mut clustering_model = self.clustering_model.get_mut_ref();
+        clustering_model.analyze(ds.get_ref().get_original());
+        clustering_model.set_open(true);
+    }
+
+    pub fn get_date_format_model(&self) -> RefMut<DateFormatModel> {
+        self.date_format_model.get_mut_ref()
+    }
+
+    /// Opens list of date formats that match the file, starting analysis if necessary
+    pub fn open_date_format_dialog(&mut self) {
+        let Some(sample) = self.date_sample() else { return; };
+        let mut date_format_model = self.date_format_model.get_mut_ref();
+        date_format_model.analyze(sample);
+        date_format_model.set_open(true);
+    }
+
+    /// Pins date format for the current file, so that it is used instead of the guessed one
+    /// whenever the file is opened. `None` brings back guessing.
+    pub fn pin_date_format(&mut self, date_format: Option<&'static KnownDateFormat>) {
+        let Some(path) = self.resolve_file_name() else { return; };
+        let result = self.date_format_model.get_mut_ref().pin(&path, date_format);
+        if let Err(err) = result {
+            self.set_error(Box::new(err));
+            return;
+        }
+        let hint = match date_format {
+            Some(kdf) => {
+                self.date_format = Some(kdf);
+                self.refresh_data();
+                self.update_time_deltas();
+                self.build_time_index();
+                self.model_sender.emit_event(Repaint);
+                format!("Date format {} is pinned for this file", kdf.get_definition())
+            }
+            None => {
+                self.guess_date_format();
+                String::from("Date format is guessed from content")
+            }
+        };
+        self.model_sender.emit_event(Hint(hint));
+    }
+
+    /// Date format pinned for the current file, if any
+    pub fn get_pinned_date_format(&self) -> Option<&'static KnownDateFormat> {
+        let path = self.resolve_file_name()?;
+        self.date_format_model.get_ref().get_pinned(&path)
+    }
+
+    pub fn get_field_table_model(&self) -> RefMut<FieldTableModel> {
+        self.field_table_model.get_mut_ref()
+    }
+
+    /// Opens table of fields starting from the first visible line
+    pub fn open_field_table(&mut self) {
+        let Some(mut ds) = self.get_datasource_ref() else { return; };
+        let start = self.data.as_ref()
+            .and_then(|data| data.start)
+            .unwrap_or_default();
+        let mut field_table_model = self.field_table_model.get_mut_ref();
+        field_table_model.reset_pages(&mut ds, start);
+        field_table_model.set_open(true);
+    }
+
+    pub fn set_parse_rule(&mut self, rule: &str) -> Result<(), regex::Error> {
+        let Some(mut ds) = self.get_datasource_ref() else { return Ok(()); };
+        self.field_table_model.get_mut_ref().set_rule(rule, &mut ds)
+    }
+
+    pub fn field_table_next_page(&mut self) {
+        let Some(mut ds) = self.get_datasource_ref() else { return; };
+        self.field_table_model.get_mut_ref().next_page(&mut ds);
+    }
+
+    pub fn field_table_prev_page(&mut self) {
+        let Some(mut ds) = self.get_datasource_ref() else { return; };
+        self.field_table_model.get_mut_ref().prev_page(&mut ds);
+    }
+
+    pub fn get_bookmarks(&self) -> &BTreeSet<Integer> {
+        &self.bookmarks
+    }
+
+    /// Sets or removes bookmark at the beginning of current line
+    pub fn toggle_bookmark(&mut self) -> bool {
+        let Some(line_start) = self.get_cursor_in_cache()
+            .zip(self.data.as_ref())
+            .and_then(|(p, data)| data.lines.get(p.height.as_usize()))
+            .map(|line| line.start)
+            else { return false; };
+        let Some(offset) = self.get_datasource_ref()
+            .and_then(|ds| ds.to_original_offset(line_start))
+            else { return false; };
+        let hint = if self.bookmarks.remove(&offset) {
+            "Bookmark removed"
+        } else {
+            self.bookmarks.insert(offset);
+            "Bookmark set"
+        };
+        self.model_sender.emit_event(Hint(String::from(hint)));
+        self.model_sender.emit_event(Repaint);
+        true
+    }
+
+    pub fn get_details_model(&self) -> RefMut<DetailsModel> {
+        self.details_model.get_mut_ref()
+    }
+
+    /// Shows fields of the logfmt record under cursor
+    pub fn show_record_details(&mut self) {
+        let Some(line) = self.get_cursor_in_cache()
+            .zip(self.data.as_ref())
+            .and_then(|(p, data)| data.lines.get(p.height.as_usize()))
+            else { return; };
+        let fields: Vec<(String, String)> = logfmt::parse(&line.content).into_iter()
+            .map(|pair| (pair.key.to_string(), pair.value.into_owned()))
+            .collect();
+        if fields.is_empty() {
+            self.model_sender.emit_event(Hint(String::from("Current line is not a logfmt record")));
+            return;
+        }
+        self.details_model.get_mut_ref().show("Record", fields, vec![]);
+    }
+
+    /// Shows the record under cursor with embedded JSON/XML pretty-printed. Lines without date
+    /// that follow the current one are considered continuation of the same record.
+    pub fn show_line_details(&mut self) {
+        let Some((p, data)) = self.get_cursor_in_cache().zip(self.data.as_ref()) else { return; };
+        let Some(line) = data.lines.get(p.height.as_usize()) else { return; };
+        let ctx = self.get_date_guess_context();
+        let timestamp = self.date_format.and_then(|kdf| kdf.parse(&line.content, &ctx));
+        let continuation = data.lines.iter()
+            .skip(p.height.as_usize() + 1)
+            .take_while(|l| timestamp.is_some()
+                && self.date_format.and_then(|kdf| kdf.parse(&l.content, &ctx)).is_none());
+        let content = std::iter::once(line)
+            .chain(continuation)
+            .map(|l| l.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut fields = vec![(String::from("Offset"), line.start.to_string())];
+        if let Ok(line_no) = &line.line_no {
+            fields.push((String::from("Line"), (line_no + 1).to_string()));
+        }
+        if let Some(timestamp) = timestamp {
+            fields.push((String::from("Timestamp"), timestamp.to_string()));
+        }
+        self.details_model.get_mut_ref().show("Line details", fields, pretty_print(&content));
+    }
+
     pub fn get_date_format(&self) -> Option<&'static KnownDateFormat> {
         self.date_format
     }
 
+    /// Date of the line under cursor, or of the first line in viewport that has one
+    pub fn get_cursor_date(&self) -> Option<NaiveDateTime> {
+        let known_date_format = self.date_format?;
+        let ctx = self.get_date_guess_context();
+        self.get_cursor_line()
+            .and_then(|line| known_date_format.parse(&line.content, &ctx))
+            .or_else(|| self.data.as_ref()?.lines.iter()
+                .find_map(|line| known_date_format.parse(&line.content, &ctx)))
+    }
+
     pub fn get_date_guess_context(&self) -> GuessContext {
-        let time = self.resolve_file_name()
-            .and_then(|p| std::fs::metadata(p).ok())
+        let time = self.date_sample()
+            .and_then(|sample| std::fs::metadata(sample.path()).ok())
             .and_then(|m| m.created().ok())
             .unwrap_or(SystemTime::now());
         let dt: DateTime<Utc> = time.into();
         GuessContext::with_year(dt.year() as u16)
+            .with_zones(self.assumed_zone, self.display_zone)
+    }
+
+    pub fn set_gap_threshold(&mut self, gap_threshold: Duration) {
+        self.gap_threshold = gap_threshold;
+    }
+
+    /// Time elapsed by visible lines that have a date, by line start. `None` if the time delta
+    /// column is hidden.
+    pub fn get_time_deltas(&self) -> Option<&BTreeMap<Integer, Duration>> {
+        self.time_deltas.as_ref()
+    }
+
+    /// Shows or hides the column with time elapsed since the previous line with a date, or since
+    /// the reference mark
+    pub fn toggle_time_deltas(&mut self) {
+        let hint = if self.time_deltas.take().is_some() {
+            "Time deltas are hidden"
+        } else if self.date_format.is_none() {
+            "Date format is not recognized"
+        } else {
+            self.time_deltas = Some(BTreeMap::new());
+            self.update_time_deltas();
+            "Time deltas are shown"
+        };
+        self.model_sender.emit_event(DataUpdated);
+        self.model_sender.emit_event(Hint(String::from(hint)));
+    }
+
+    /// Sets the reference mark at the date of the current line, so that time deltas are counted
+    /// from it, or removes the mark
+    pub fn toggle_time_reference(&mut self) {
+        let hint = if self.time_reference.take().is_some() {
+            String::from("Time deltas are counted from the previous line")
+        } else if let Some(dt) = self.get_cursor_date() {
+            self.time_reference = Some(dt);
+            format!("Time deltas are counted from {}", dt)
+        } else {
+            String::from("Current line has no date")
+        };
+        if self.time_deltas.is_none() && self.time_reference.is_some() {
+            self.time_deltas = Some(BTreeMap::new());
+        }
+        self.update_time_deltas();
+        self.model_sender.emit_event(DataUpdated);
+        self.model_sender.emit_event(Hint(hint));
+    }
+
+    /// Moves cursor to the first line after the current one that is dated more than gap
+    /// threshold later than the previous line with a date
+    pub fn jump_to_next_gap(&mut self) {
+        let Some(date_format) = self.date_format else {
+            self.model_sender.emit_event(Hint(String::from("Date format is not recognized")));
+            return;
+        };
+        let Some(ds) = &self.datasource else { return; };
+        let Some((start, _)) = self.get_cursor_line_bounds() else { return; };
+        let reader_factory = ds.get_ref().reader_factory();
+        let guess_context = self.get_date_guess_context();
+        let threshold = self.gap_threshold;
+        self.background_process_builder::<(), _, _, _>()
+            .with_title("Find time gap")
+            .with_description(format!("Find gap longer than {}", format_delta(threshold)))
+            .with_task(move |ctx| find_gap(ctx, reader_factory, start, date_format, &guess_context, threshold))
+            .with_listener(move |model, signal, _| {
+                let Signal::Complete(result) = signal else { return; };
+                match result {
+                    Ok(Some(offset)) => {
+                        model.move_cursor_to_offset(offset, false);
+                    }
+                    Ok(None) => {
+                        let hint = format!("No gap longer than {} below", format_delta(threshold));
+                        model.model_sender.emit_event(Hint(hint));
+                    }
+                    Err(err) => log::warn!("Failed to find time gap: {:?}", err),
+                }
+            })
+            .run();
+    }
+
+    /// Recalculates time deltas of visible lines, if the column is shown. The first visible line
+    /// is compared with the last dated line above it, which is looked up within a limited number
+    /// of lines.
+    fn update_time_deltas(&mut self) {
+        if self.time_deltas.is_none() {
+            return;
+        }
+        let Some((date_format, data)) = self.date_format.zip(self.data.as_ref()) else {
+            self.time_deltas = Some(BTreeMap::new());
+            return;
+        };
+        let ctx = self.get_date_guess_context();
+        let previous = match (self.time_reference, data.lines.first(), &self.datasource) {
+            (None, Some(first), Some(ds)) if first.start > 0 => {
+                let lines = ds.get_mut_ref().read_lines(first.start - 1, (-TIME_DELTA_LOOKBACK).into()).lines;
+                lines.iter().rev().find_map(|line| date_format.parse(&line.content, &ctx))
+            }
+            _ => None,
+        };
+        let lines = data.lines.iter().map(|line| (line.start, line.content.as_str()));
+        self.time_deltas = Some(time_deltas(lines, date_format, &ctx, previous, self.time_reference));
+    }
+
+    /// Remembers position, search and filter of the current file, so that they are restored
+    /// when the file is opened next time
+    fn save_session(&mut self) {
+        // offsets in concatenated files are not stable, as files are rotated
+        if self.concat.is_some() {
+            return;
+        }
+        // the file is not decoded yet, so the session it has been opened with is kept
+        if self.decoding_handler.is_some() {
+            return;
+        }
+        let Some(path) = self.resolve_file_name() else { return; };
+        let Some((file_size, modified)) = file_stamp(&path) else { return; };
+        let search = self.current_search.get_ref().as_ref().and_then(|_| {
+            let search_model = self.search_model.get_ref();
+            // hex view is not restored, so hex patterns are not either
+            (!search_model.is_hex()).then(|| SearchSpec {
+                pattern: search_model.get_pattern().to_string(),
+                is_regexp: search_model.is_regexp(),
+            })
+        });
+        let session = Session {
+            file_size,
+            modified,
+            checksum: file_checksum(&path, file_size),
+            last_opened: now(),
+            cursor: self.convert_unfiltered(self.cursor, Self::to_file_offset),
+            scroll_position: self.session_scroll_position(self.scroll_position, Self::to_file_offset),
+            horizontal_scroll: self.horizontal_scroll,
+            search,
+            filter: self.filter_spec.clone(),
+        };
+        self.sessions.set(&path, session);
+        if let Err(err) = self.sessions.save() {
+            log::warn!("{:?}", err);
+        }
+        self.open_file_model.get_mut_ref().set_recent_files(self.sessions.recent_files());
+    }
+
+    /// Brings back the state saved by [Self::save_session], unless the file has been truncated
+    /// or rewritten since then
+    fn restore_session(&mut self) {
+        if self.decoding_handler.is_some() {
+            // restored once the file is decoded
+            return;
+        }
+        let Some(path) = self.resolve_file_name() else { return; };
+        let Some(session) = self.sessions.get(&path).cloned() else { return; };
+        if !session.is_valid_for(&path) {
+            log::info!("Session of {:?} is outdated", path);
+            return;
+        }
+        if let Some(filter) = &session.filter {
+            let result = match filter {
+                FilterSpec::Substring { pattern, neighbourhood } => self.filter_by_substring(pattern, *neighbourhood),
+                FilterSpec::Field { expression, neighbourhood } => self.filter_by_field(expression, *neighbourhood),
+                FilterSpec::Regex(pattern) => self.filter_by_regex(pattern),
+            };
+            if let Err(err) = result {
+                log::warn!("Failed to restore filter {:?}: {:?}", filter, err);
+                return;
+            }
+        }
+        self.cursor = self.convert_unfiltered(session.cursor, Self::to_decoded_offset);
+        self.scroll_position = self.session_scroll_position(session.scroll_position, Self::to_decoded_offset);
+        self.horizontal_scroll = if self.soft_wrap { Integer::zero() } else { session.horizontal_scroll };
+        if !self.update_viewport_content() {
+            log::warn!("Failed to restore scroll position {}", session.scroll_position);
+            self.scroll_position = ScrollPosition::default();
+            self.update_viewport_content();
+        }
+        self.emit_cursor_moved();
+        if let Some(search) = &session.search {
+            self.restore_search(search);
+        }
+        if session.filter.is_none() {
+            self.model_sender.emit_event(Hint(String::from("Position is restored from the last session")));
+        }
+    }
+
+    /// Converts `offset` with `convert` unless content is filtered. Offsets in the original source
+    /// are shown and saved in sessions as file offsets, so that they do not depend on decoding.
+    fn convert_unfiltered(&self, offset: Integer, convert: fn(&Self, Integer) -> Integer) -> Integer {
+        match self.filter_spec {
+            None => convert(self, offset),
+            Some(_) => offset,
+        }
+    }
+
+    fn session_scroll_position(&self, position: ScrollPosition, convert: fn(&Self, Integer) -> Integer) -> ScrollPosition {
+        match position {
+            ScrollPosition::FromBeginning { shift, row } =>
+                ScrollPosition::from_beginning(self.convert_unfiltered(shift, convert)).with_row(row),
+            ScrollPosition::FromEnd { .. } => position,
+        }
+    }
+
+    /// Makes `search` current without moving cursor, so that next/previous occurrence is found
+    /// from where the session ended
+    fn restore_search(&mut self, search: &SearchSpec) {
+        let Some(reader_factory) = self.get_datasource_ref().map(|ds| ds.reader_factory()) else { return; };
+        let result = {
+            let mut search_model = self.search_model.get_mut_ref();
+            search_model.set_pattern(search.pattern.as_str());
+            search_model.set_regexp(search.is_regexp);
+            search_model.resume_search(reader_factory, self.cursor)
+        };
+        match result {
+            Ok(search) => self.set_current_search(Some(search)),
+            Err(err) => log::warn!("Failed to restore search for {:?}: {:?}", search.pattern, err),
+        }
     }
 
     pub fn on_esc(&mut self) {
@@ -1029,7 +2290,8 @@ impl RootModel {
     fn build_line_registry<R: Read + Seek + 'static, B: LineSourceBackend<R> + Send + 'static>(
         &mut self,
         backend: B,
-        file_size: Integer
+        file_size: Integer,
+        path: Option<PathBuf>,
     ) {
         if !self.show_line_numbers {
             return;
@@ -1042,18 +2304,36 @@ impl RootModel {
 
         let line_registry = ds.get_line_registry();
         drop(ds);
+        let reader_factory = backend.reader_factory();
         let bgp_model = &mut *self.bgp_model.get_mut_ref();
         bgp_model.background_process_builder()
             .with_title("Indexing")
             .with_description(format!("Build internal registries for {:?}", self.file_name))
             .with_task(move |ctx| {
                 let is_interrupted = || ctx.interrupted();
-                let mut reader = backend.new_reader();
-                line_registry.build(&mut reader, is_interrupted, |b| {
+                let cache = path.as_deref().and_then(LineIndexCache::for_file);
+                let start = match cache.as_ref().and_then(LineIndexCache::load) {
+                    Some((line_breaks, length)) => {
+                        line_registry.restore(line_breaks, length.into());
+                        length as usize
+                    },
+                    None => 0,
+                };
+                let indexed = Cell::new(start);
+                let new_reader = || reader_factory.new_reader();
+                line_registry.build_parallel(new_reader, start as u64, file_size.as_u64(), is_interrupted, |b| {
+                    let b = start + b;
+                    indexed.set(b);
                     ctx.send_message(BytesRead(b)).expect("Failed to send update");
                     let progress = (Ratio::new(b, file_size.as_usize()) * 100).to_integer() as u8;
                     ctx.update_progress(progress);
-                })
+                })?;
+                if let Some(cache) = cache {
+                    if let Err(err) = cache.save(&line_registry, indexed.get() as u64) {
+                        log::warn!("Failed to save line index: {}", err);
+                    }
+                }
+                Ok::<_, LineRegistryError>(())
             })
             .with_listener(|model, signal, _id| {
                 let Signal::Custom(BytesRead(b)) = signal
@@ -1078,6 +2358,44 @@ impl RootModel {
             .run();
     }
 
+    /// Indexes dates of the original source in background. Dates depend on the date format and
+    /// the display zone, so the index is built anew when they change.
+    fn build_time_index(&mut self) {
+        if let Some(handler) = self.time_index_handler.take() {
+            handler.interrupt();
+        }
+        self.time_index = None;
+        let Some(known_date_format) = self.date_format else { return; };
+        let Some((reader_factory, length)) = self.get_datasource_ref()
+            .map(|ds| (ds.get_original().reader_factory(), ds.get_original().get_length().as_u64()))
+            else { return; };
+        let guess_ctx = self.get_date_guess_context();
+        let time_index = Arc::new(TimeIndex::new(length));
+        self.time_index = Some(Arc::clone(&time_index));
+        let description = format!("Index dates of {:?}", self.file_name);
+        let handler = self.background_process_builder::<(), _, _, _>()
+            .with_title("Time index")
+            .with_description(description)
+            .with_task(move |ctx| {
+                let mut reader = BufReader::new(reader_factory.new_reader()?);
+                time_index.build(
+                    &mut reader,
+                    |line| known_date_format.parse(line, &guess_ctx),
+                    || ctx.interrupted(),
+                    |b| ctx.update_progress_u64(b, length),
+                )
+            })
+            .with_listener(|_, signal, _| {
+                match signal {
+                    Signal::Complete(Err(LineRegistryError::Cancelled)) => {}
+                    Signal::Complete(Err(err)) => log::warn!("Failed to index dates: {:?}", err),
+                    _ => {}
+                }
+            })
+            .run();
+        self.time_index_handler = Some(handler);
+    }
+
     fn emit_cursor_moved(&self) {
         if let Some(cp) = &self.get_cursor_in_cache() {
             let i = cp.height.as_usize();
@@ -1089,20 +2407,77 @@ impl RootModel {
             let event = CursorMoved(CursorPosition {
                 line_no,
                 position_in_line: cp.width.as_u64(),
-                offset: self.cursor.as_u64(),
+                offset: self.convert_unfiltered(self.cursor, Self::to_file_offset).as_u64(),
             });
             self.model_sender.emit_event(event);
+            self.emit_current_part();
+        }
+    }
+
+    /// Shows name of the concatenated file the cursor is in, when it changes
+    fn emit_current_part(&self) {
+        let Some(concat) = &self.concat else { return; };
+        let Some(offset) = self.get_datasource_ref().and_then(|ds| ds.to_original_offset(self.cursor)) else { return; };
+        let index = concat.part_index(offset.as_u64());
+        if index == self.current_part.get() {
+            return;
+        }
+        self.current_part.set(index);
+        let Some(index) = index else { return; };
+        let parts = concat.parts();
+        let part_name = parts[index].path.file_name().unwrap_or_default().to_string_lossy();
+        let file_name = format!("{} [{}/{}: {}]", self.file_name.as_deref().unwrap_or_default(), index + 1, parts.len(), part_name);
+        self.model_sender.emit_event(FileName(file_name, concat.get_length()));
+    }
+
+    /// Starts of visible lines that are the first ones of a concatenated file, except the first
+    /// file
+    pub fn get_file_boundaries(&self) -> BTreeSet<Integer> {
+        let mut boundaries = BTreeSet::new();
+        let (Some(concat), Some(data)) = (&self.concat, &self.data) else { return boundaries; };
+        let Some(ds) = self.get_datasource_ref() else { return boundaries; };
+        let mut previous = None;
+        for line in &data.lines {
+            let Some(offset) = ds.to_original_offset(line.start).map(|offset| offset.as_u64()) else { continue; };
+            let index = concat.part_index(offset);
+            let is_boundary = match previous {
+                Some(previous) => index != previous,
+                // lines of a file may be filtered out, so the first visible line is a boundary
+                // only if it is the first line of the file
+                None => index.is_some_and(|i| i > 0 && concat.parts()[i].start == offset),
+            };
+            if is_boundary {
+                boundaries.insert(line.start);
+            }
+            previous = Some(index);
         }
+        boundaries
     }
 
-    fn guess_date_format(&mut self, path: &Path) {
-        let path = path.to_path_buf();
+    /// File that is looked through to guess date format: the current file, which may be
+    /// decoded, or the newest one of concatenated files
+    fn date_sample(&self) -> Option<DateSample> {
+        match (&self.concat, &self.transcoded) {
+            (Some(concat), _) => concat.parts().last().map(|part| DateSample::File(part.path.clone())),
+            (None, Some(transcoded)) => Some(DateSample::Transcoded(transcoded.clone())),
+            (None, None) => self.resolve_file_name().map(DateSample::File),
+        }
+    }
+
+    fn guess_date_format(&mut self) {
+        if let Some(kdf) = self.get_pinned_date_format() {
+            log::info!("DateTime format is pinned as {:?} for {:?}", kdf, self.file_name);
+            self.date_format = Some(kdf);
+            return;
+        }
+        let Some(sample) = self.date_sample() else { return; };
+        let path = sample.path().to_path_buf();
         let path2 = path.clone();
         self.background_process_builder::<(), _, _, _>()
             .with_title("Guess date format")
             .with_description(format!("Guess date format for {:?}", &path))
             .with_task(move |_| {
-                guess_date_format(path.to_path_buf())
+                guess_date_format(sample)
             })
             .with_listener(move |model, signal, _| {
                 match signal {
@@ -1114,7 +2489,11 @@ impl RootModel {
                         } else {
                             log::info!("DateTime format has not been recognized for {:?}", path2);
                         }
-                        model.date_format = s;
+                        // user may have pinned a format meanwhile
+                        model.date_format = model.get_pinned_date_format().or(s);
+                        model.refresh_data();
+                        model.update_time_deltas();
+                        model.build_time_index();
                         model.model_sender.emit_event(Repaint);
                     }
                 }
@@ -1136,4 +2515,9 @@ impl RunInBackground for RootModel {
         let mut registry = self.background_process_registry.get_mut_ref();
         registry.run_in_background(title, description, task, listener)
     }
-}
\ No newline at end of file
+}
+
+// Tests are included according to http://xion.io/post/code/rust-unit-test-placement.html
+#[cfg(test)]
+#[path = "./model_tests.rs"]
+mod model_tests;
@@ -0,0 +1,196 @@
+use chrono::FixedOffset;
+use crossbeam_channel::Receiver;
+use fluent_integer::Integer;
+use spectral::prelude::*;
+
+use crate::app_theme::app_theme::{AppTheme, AppThemeName};
+use crate::background_process::background_process_registry::BackgroundProcessRegistry;
+use crate::data_source::delimiter::Delimiter;
+use crate::data_source::encoding::Encoding;
+use crate::data_source::line_source_holder::ConcreteLineSourceHolder;
+use crate::data_source::transcoded::TranscodedBackend;
+use crate::data_source::{LineSourceImpl, StrBackend};
+use crate::model::cursor_shift::CursorShift;
+use crate::model::dimension::Dimension;
+use crate::model::guess_date_format::find_or_create_format;
+use crate::model::model::{ModelEvent, RootModel};
+use crate::model::scroll_position::ScrollPosition;
+use crate::model::time_zone::AssumedZone;
+use crate::profiles::OS_PROFILE;
+use crate::shared::Shared;
+use crate::test_extensions::TempFile;
+
+// lines start at 0, 11, 14, 40 and 43; the third one takes 3 rows of 10 columns
+const TEXT: &str = "0123456789\nab\n0123456789abcdefghij01234\nxy\nlast";
+
+const WIDTH: usize = 10;
+
+/// Model that shows [TEXT] in soft wrap mode in a viewport of 3 rows. The receiver has to be
+/// kept, as the model fails once nobody listens to its events.
+fn create_model() -> (Shared<RootModel>, Receiver<ModelEvent>) {
+    create_model_with(TEXT, WIDTH)
+}
+
+fn create_model_with(text: &'static str, width: usize) -> (Shared<RootModel>, Receiver<ModelEvent>) {
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let registry = Shared::new(BackgroundProcessRegistry::new());
+    let (app_theme, _) = AppTheme::load(&AppThemeName::SolarizedLight).unwrap();
+    let model = RootModel::new(sender, registry, None, app_theme, &OS_PROFILE);
+    {
+        let mut model = model.get_mut_ref();
+        let line_source = ConcreteLineSourceHolder::from(LineSourceImpl::from_str(text));
+        model.do_load_file(line_source, StrBackend::new(text), String::from("test"), None);
+        model.set_viewport_width(width);
+        model.set_viewport_height(3);
+        model.toggle_soft_wrap();
+    }
+    (model, receiver)
+}
+
+fn scroll_position(model: &RootModel) -> ScrollPosition {
+    model.scroll_position
+}
+
+/// Moves cursor by `dy` screen rows and returns its offset
+fn move_down(model: &mut RootModel, dy: i32) -> u64 {
+    model.move_cursor(CursorShift::Y(dy.into()), false);
+    model.get_cursor().as_u64()
+}
+
+#[test]
+fn test_visible_rows() {
+    let (model, _receiver) = create_model();
+    let mut model = model.get_mut_ref();
+    assert_that!(model.get_visible_rows()).is_equal_to(vec![(0, 0), (1, 0), (2, 0)]);
+
+    assert_that!(model.scroll(1.into())).is_true();
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(11));
+    assert_that!(model.get_visible_rows()).is_equal_to(vec![(0, 0), (1, 0), (1, 1)]);
+
+    // the first line is scrolled partially out of view
+    assert_that!(model.scroll(2.into())).is_true();
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(14).with_row(1));
+    assert_that!(model.get_visible_rows()).is_equal_to(vec![(0, 1), (0, 2), (1, 0)]);
+}
+
+#[test]
+fn test_scroll_to_last_row() {
+    let (model, _receiver) = create_model();
+    let mut model = model.get_mut_ref();
+    model.scroll(3.into());
+    // viewport stays full at the end of the source
+    model.scroll(10.into());
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(14).with_row(2));
+    assert_that!(model.get_visible_rows()).is_equal_to(vec![(0, 2), (1, 0), (2, 0)]);
+}
+
+#[test]
+fn test_scroll_up_across_wrapped_line() {
+    let (model, _receiver) = create_model();
+    let mut model = model.get_mut_ref();
+    model.scroll(3.into());
+    model.scroll(10.into());
+
+    assert_that!(model.scroll((-1).into())).is_true();
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(14).with_row(1));
+    // from the second row of the wrapped line to the line above it
+    assert_that!(model.scroll((-2).into())).is_true();
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(11));
+    assert_that!(model.get_visible_rows()).is_equal_to(vec![(0, 0), (1, 0), (1, 1)]);
+}
+
+#[test]
+fn test_to_screen_position() {
+    let (model, _receiver) = create_model();
+    let mut model = model.get_mut_ref();
+    model.scroll(3.into());
+    // the third line is the first one in cache, its first row is above viewport
+    assert_that!(model.to_screen_position(Dimension::new(15, 0), WIDTH)).is_equal_to(Dimension::new(5, 0));
+    assert_that!(model.to_screen_position(Dimension::new(3, 0), WIDTH)).is_equal_to(Dimension::new(3, -1));
+    assert_that!(model.to_screen_position(Dimension::new(1, 1), WIDTH)).is_equal_to(Dimension::new(1, 2));
+}
+
+#[test]
+fn test_bring_row_into_view() {
+    let (model, _receiver) = create_model();
+    let mut model = model.get_mut_ref();
+    model.scroll(3.into());
+    assert_that!(model.bring_row_into_view(16.into(), WIDTH)).is_true();
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(14));
+    // already visible
+    assert_that!(model.bring_row_into_view(25.into(), WIDTH)).is_false();
+}
+
+#[test]
+fn test_move_cursor_over_rows() {
+    let (model, _receiver) = create_model();
+    let mut model = model.get_mut_ref();
+    model.move_cursor_to_offset(3.into(), false);
+    // the column is kept within rows of the wrapped line
+    assert_that!(move_down(&mut model, 2)).is_equal_to(17);
+    assert_that!(move_down(&mut model, 1)).is_equal_to(27);
+    assert_that!(move_down(&mut model, 1)).is_equal_to(37);
+    assert_that!(move_down(&mut model, 1)).is_equal_to(41);
+    assert_that!(move_down(&mut model, 1)).is_equal_to(44);
+    // the last row
+    assert_that!(move_down(&mut model, 1)).is_equal_to(44);
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(14).with_row(2));
+    assert_that!(model.get_cursor_on_screen()).is_equal_to(Some(Dimension::new(1, 2)));
+
+    assert_that!(move_down(&mut model, -1)).is_equal_to(41);
+    assert_that!(move_down(&mut model, -1)).is_equal_to(35);
+    // scrolls up within the wrapped line
+    assert_that!(move_down(&mut model, -1)).is_equal_to(25);
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(14).with_row(1));
+    assert_that!(move_down(&mut model, -2)).is_equal_to(12);
+    assert_that!(scroll_position(&model)).is_equal_to(ScrollPosition::from_beginning(11));
+}
+
+#[test]
+fn test_rows_of_date_in_display_zone() {
+    let (model, _receiver) = create_model_with("2024-01-31 10:00:00 x\nnext", 25);
+    let mut model = model.get_mut_ref();
+    model.assumed_zone = AssumedZone::Fixed(FixedOffset::east_opt(2 * 3600).unwrap());
+    model.date_format = Some(find_or_create_format("%Y-%m-%d %H:%M:%S").unwrap());
+    assert_that!(model.get_visible_rows()).is_equal_to(vec![(0, 0), (1, 0)]);
+
+    // shown as "2024-01-31 08:00:00 +00:00 x", which takes 2 rows
+    model.switch_display_zone();
+    assert_that!(model.get_visible_rows()).is_equal_to(vec![(0, 0), (0, 1), (1, 0)]);
+    model.move_cursor_to_offset(20.into(), false);
+    assert_that!(model.get_cursor_on_screen()).is_equal_to(Some(Dimension::new(2, 1)));
+    assert_that!(move_down(&mut model, -1)).is_equal_to(2);
+    assert_that!(move_down(&mut model, 1)).is_equal_to(20);
+    assert_that!(move_down(&mut model, 1)).is_equal_to(24);
+}
+
+#[test]
+fn test_hex_view_of_decoded_file() {
+    // "ab\ncd" in UTF-16LE with BOM
+    let bytes: Vec<u8> = [0xFF, 0xFE].into_iter()
+        .chain("ab\ncd".encode_utf16().flat_map(|u| u.to_le_bytes()))
+        .collect();
+    let file = TempFile::new("model-hex", &bytes);
+    let (model, _receiver) = create_model();
+    let mut model = model.get_mut_ref();
+    let backend = TranscodedBackend::open(file.path(), Encoding::Utf16Le, Delimiter::NewLine, || false, |_| {}).unwrap();
+    model.transcoded = Some(backend.clone());
+    let line_source = ConcreteLineSourceHolder::from(LineSourceImpl::new(backend.clone()));
+    model.do_load_file(line_source, backend, String::from("test"), None);
+
+    // bytes are shown as they are in the file
+    model.toggle_hex_view();
+    let rows: Vec<(Integer, Vec<u8>)> = model.get_hex_view().unwrap().rows()
+        .map(|(offset, row)| (offset, row.to_vec()))
+        .collect();
+    assert_that!(rows).is_equal_to(vec![(Integer::from(0), bytes)]);
+    assert_that!(model.get_hex_cursor()).is_equal_to(Integer::from(2));
+
+    // both bytes of "a" are skipped
+    model.move_cursor(CursorShift::X(1.into()), false);
+    assert_that!(model.get_cursor()).is_equal_to(Integer::from(1));
+    assert_that!(model.get_hex_cursor()).is_equal_to(Integer::from(4));
+    model.move_cursor(CursorShift::X((-1).into()), false);
+    assert_that!(model.get_cursor()).is_equal_to(Integer::from(0));
+    assert_that!(model.get_cursor_line_bounds()).is_equal_to(Some((Integer::from(0), Integer::from(5))));
+}
@@ -1,5 +1,4 @@
 use std::fmt::{Display, Formatter};
-use std::io::BufReader;
 use derive_builder::Builder;
 use thiserror::Error;
 use fluent_integer::Integer;
@@ -7,7 +6,7 @@ use crate::data_source::Direction;
 use crate::data_source::reader_factory::ReaderFactory;
 use crate::search::navigable_searcher::NavigableSearcher;
 use crate::search::navigable_searcher_impl::NavigableSearcherImpl;
-use crate::search::searcher::create_searcher;
+use crate::search::searcher::{create_byte_searcher_for, create_searcher_for, parse_hex_bytes};
 
 #[derive(Builder, Debug)]
 #[builder(pattern = "owned")]
@@ -16,6 +15,8 @@ pub struct NavigableSearcherConstructor
     reader_factory: Box<dyn ReaderFactory>,
     pattern: String,
     is_regexp: bool,
+    /// Pattern is a sequence of bytes written in hex
+    is_hex: bool,
     initial_offset: Option<Integer>,
     is_backward: bool,
 }
@@ -23,8 +24,13 @@ pub struct NavigableSearcherConstructor
 impl NavigableSearcherConstructor {
     pub fn construct_searcher(self) -> Result<Box<dyn NavigableSearcher>, NavigableSearcherConstructorError> {
         if !self.pattern.is_empty() {
-            let reader = self.reader_factory.new_reader()?;
-            let searcher = create_searcher(BufReader::new(reader), self.pattern.clone(), self.is_regexp);
+            let searcher = if self.is_hex {
+                let bytes = parse_hex_bytes(&self.pattern)
+                    .ok_or(NavigableSearcherConstructorError::InvalidHexPattern)?;
+                create_byte_searcher_for(self.reader_factory.as_ref(), bytes)?
+            } else {
+                create_searcher_for(self.reader_factory.as_ref(), self.pattern.clone(), self.is_regexp)?
+            };
             let mut navigable_searcher = NavigableSearcherImpl::new(searcher);
             if let Some(initial_offset) = &self.initial_offset {
                 let direction = Direction::from(!self.is_backward);
@@ -42,6 +48,7 @@ impl NavigableSearcherConstructor {
 pub enum NavigableSearcherConstructorError {
     FileNotSet,
     PatternIsEmpty,
+    InvalidHexPattern,
     IO(#[from] std::io::Error),
 }
 
@@ -49,6 +56,7 @@ impl Display for NavigableSearcherConstructorError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let str = match self {
             NavigableSearcherConstructorError::PatternIsEmpty => "Pattern is empty",
+            NavigableSearcherConstructorError::InvalidHexPattern => "Pattern is not a sequence of hex bytes, e.g. 'de ad be ef'",
             NavigableSearcherConstructorError::FileNotSet => "File (data source) not specified",
             NavigableSearcherConstructorError::IO(err) => &format!("{:?}", err),
         };
@@ -58,7 +66,9 @@ impl Display for NavigableSearcherConstructorError {
 
 impl Display for NavigableSearcherConstructor {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let as_regexp = if self.is_regexp {
+        let as_regexp = if self.is_hex {
+            " (hex)"
+        } else if self.is_regexp {
             " (regexp)"
         } else {
             ""
@@ -1,33 +1,50 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::fmt::Display;
 use std::fs::{DirEntry, Metadata};
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
 use std::time::SystemTime;
 use anyhow::anyhow;
 use crossbeam_channel::Sender;
 use itertools::Itertools;
+use crate::background_process::background_process_handler::BackgroundProcessHandler;
+use crate::background_process::run_in_background::RunInBackground;
+use crate::background_process::signal::Signal;
+use crate::data_source::file_set;
+use crate::model::bgp_model::BGPModel;
 use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerManager, EscapeHandlerResult};
+use crate::model::file_preview::Preview;
 use crate::model::model::{ModelEvent, RootModel};
 use crate::shared::Shared;
 use crate::utils::event_emitter::EventEmitter;
 
 const GO_UP: &str = "..";
+const MAX_FOUND_LOGS: usize = 1000;
+const MAX_VISITED_FOLDERS: usize = 10000;
 
 pub struct OpenFileModel {
     sender: Sender<ModelEvent>,
+    runner: Shared<BGPModel>,
     escape_handler_manager: EscapeHandlerManager,
     is_open: bool,
     current_location: PathBuf,
-    files: Vec<DirEntry0>,
+    entries: Vec<ListedEntry>, // all entries of current location
+    files: Vec<DirEntry0>, // entries that are shown
     current_file: Option<String>,
     entry_info: Option<EntryInfo>,
+    recent_files: Vec<PathBuf>,
+    filter: String,
+    sort_order: SortOrder,
+    show_hidden: bool,
+    find_logs: bool,
+    find_logs_handler: Option<BackgroundProcessHandler>,
 }
 
 #[derive(Debug)]
 pub enum OpenFileModelEvent {
     LocationUpdated,
     FilesUpdated,
+    FilterUpdated,
     EntryInfoUpdated,
     Error(anyhow::Error),
 }
@@ -44,19 +61,51 @@ pub struct EntryInfo {
     pub size: u64,
     pub created_at: Option<SystemTime>,
     pub modified_at: Option<SystemTime>,
-    // todo other properties
+    pub permissions: String,
+    // regular files only
+    pub preview: Option<Preview>,
+}
+
+/// Order of files in the list. Folders always go before files.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum SortOrder {
+    #[default]
+    Name,
+    /// Largest first
+    Size,
+    /// Recently modified first
+    Modified,
+}
+
+#[derive(Clone)]
+struct ListedEntry {
+    entry: DirEntry0,
+    size: u64,
+    modified_at: Option<SystemTime>,
 }
 
 impl OpenFileModel {
-    pub fn new(sender: Sender<ModelEvent>, escape_handler: Shared<CompoundEscapeHandler>) -> Self {
+    pub fn new(
+        sender: Sender<ModelEvent>,
+        runner: Shared<BGPModel>,
+        escape_handler: Shared<CompoundEscapeHandler>,
+    ) -> Self {
         OpenFileModel {
             sender,
+            runner,
             escape_handler_manager: EscapeHandlerManager::new(escape_handler, Self::on_esc),
             is_open: false,
             current_location: PathBuf::new(),
+            entries: vec![],
             files: vec![],
             current_file: None,
             entry_info: None,
+            recent_files: vec![],
+            filter: String::new(),
+            sort_order: SortOrder::default(),
+            show_hidden: false,
+            find_logs: false,
+            find_logs_handler: None,
         }
     }
 
@@ -86,6 +135,83 @@ impl OpenFileModel {
         &self.files[..]
     }
 
+    pub fn get_filter(&self) -> &str {
+        self.filter.as_str()
+    }
+
+    /// Leaves only entries whose names contain characters of `filter` in the same order, ignoring
+    /// case, or match `filter` as a glob. Input that looks like a path does not filter the list,
+    /// it is opened on submit.
+    pub fn set_filter(&mut self, filter: &str) {
+        if self.filter != filter {
+            self.filter = filter.to_string();
+            self.update_files();
+        }
+    }
+
+    /// Opens file or folder typed as a path, absolute or relative to the current location.
+    /// Files matching a glob are opened together. Otherwise opens the first entry that matches
+    /// the filter.
+    pub fn submit_filter(&mut self) {
+        let is_glob = file_set::is_glob(&self.filter);
+        if is_path(&self.filter) || is_glob {
+            let target = self.current_location.join(expand_home(&self.filter));
+            if target.is_dir() {
+                self.set_current_location(target);
+            } else if target.is_file() || (is_glob && file_set::is_file_set(&target)) {
+                self.set_open(false);
+                self.sender.emit_event(ModelEvent::OpenFile(target.to_string_lossy().to_string()));
+            } else {
+                self.emit_event(OpenFileModelEvent::Error(anyhow!("{:?} is not found", target)));
+            }
+            return;
+        }
+        let first_match = self.files.iter()
+            .find(|entry| **entry != DirEntry0::Up)
+            .map(ToString::to_string);
+        if let Some(name) = first_match {
+            self.set_current_file(Some(&name));
+            self.submit_current_file();
+        }
+    }
+
+    pub fn get_sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        if self.sort_order != sort_order {
+            self.sort_order = sort_order;
+            self.update_files();
+        }
+    }
+
+    pub fn is_show_hidden(&self) -> bool {
+        self.show_hidden
+    }
+
+    /// Shows or hides files and folders whose names start with a dot
+    pub fn set_show_hidden(&mut self, show_hidden: bool) {
+        if self.show_hidden != show_hidden {
+            self.show_hidden = show_hidden;
+            self.reload_entries();
+        }
+    }
+
+    pub fn is_find_logs(&self) -> bool {
+        self.find_logs
+    }
+
+    /// Lists `*.log` files (including rotated ones like `*.log.1`) found in the current location
+    /// and its subfolders instead of the content of the current location. The search runs in
+    /// background, found files are listed when it is complete.
+    pub fn set_find_logs(&mut self, find_logs: bool) {
+        if self.find_logs != find_logs {
+            self.find_logs = find_logs;
+            self.reload_entries();
+        }
+    }
+
     pub fn get_current_file(&self) -> Option<&str> {
         self.current_file.as_deref()
     }
@@ -99,6 +225,30 @@ impl OpenFileModel {
         self.entry_info.as_ref()
     }
 
+    /// Files opened before, most recent first
+    pub fn get_recent_files(&self) -> &[PathBuf] {
+        &self.recent_files[..]
+    }
+
+    pub fn set_recent_files(&mut self, recent_files: Vec<PathBuf>) {
+        self.recent_files = recent_files;
+    }
+
+    /// Opens all files of the current location as one, see [file_set::list_files]
+    pub fn open_current_location(&mut self) {
+        self.set_open(false);
+        self.sender.emit_event(ModelEvent::OpenFile(self.current_location.to_string_lossy().to_string()));
+    }
+
+    pub fn open_recent_file(&mut self, path: &Path) {
+        if path.is_file() {
+            self.set_open(false);
+            self.sender.emit_event(ModelEvent::OpenFile(path.to_string_lossy().to_string()));
+        } else {
+            self.emit_event(OpenFileModelEvent::Error(anyhow!("{:?} does not exist anymore", path)));
+        }
+    }
+
     pub fn submit_current_file(&mut self) {
         let current_file = self.current_file.clone();
         match &current_file {
@@ -132,19 +282,89 @@ impl OpenFileModel {
         if self.current_location == current_location {
             return;
         }
-        match Self::list_files(&current_location) {
-            Ok(files) => {
+        match self.list_entries(&current_location) {
+            Ok(entries) => {
                 self.current_location = current_location;
                 self.set_current_file(None);
                 self.emit_event(OpenFileModelEvent::LocationUpdated);
-                self.files = files;
-                self.emit_event(OpenFileModelEvent::FilesUpdated);
+                self.entries = entries;
+                if !self.filter.is_empty() {
+                    self.filter.clear();
+                    self.emit_event(OpenFileModelEvent::FilterUpdated);
+                }
+                self.update_files();
+                self.start_finding_logs();
+            }
+            Err(err) =>
+                self.emit_event(OpenFileModelEvent::Error(anyhow!(err))),
+        }
+    }
+
+    fn reload_entries(&mut self) {
+        match self.list_entries(&self.current_location) {
+            Ok(entries) => {
+                self.entries = entries;
+                self.update_files();
+                self.start_finding_logs();
             }
             Err(err) =>
                 self.emit_event(OpenFileModelEvent::Error(anyhow!(err))),
         }
     }
 
+    /// Interrupts the search for logs in progress and, in "find logs" mode, starts a new one in
+    /// the current location. Found files are added to the entries when the search is complete.
+    fn start_finding_logs(&mut self) {
+        if let Some(handler) = self.find_logs_handler.take() {
+            handler.interrupt();
+        }
+        if !self.find_logs {
+            return;
+        }
+        let location = self.current_location.clone();
+        let show_hidden = self.show_hidden;
+        let description = format!("Find logs in {:?}", &location);
+        let runner = &mut *self.runner.get_mut_ref();
+        let handler = runner.background_process_builder::<(), _, io::Result<Vec<ListedEntry>>, _>()
+            .with_title("Find logs")
+            .with_description(description)
+            .with_task(move |ctx| find_logs(&location, show_hidden, || ctx.interrupted()))
+            .with_listener(|model, signal, id| {
+                let Signal::Complete(result) = signal else { return; };
+                let mut open_file_model = model.get_open_file_model();
+                if open_file_model.find_logs_handler.as_ref().map(|h| h.get_id()) != Some(id) {
+                    return;
+                }
+                open_file_model.find_logs_handler = None;
+                match result {
+                    Ok(logs) => {
+                        open_file_model.entries.extend(logs);
+                        open_file_model.update_files();
+                    }
+                    Err(err) =>
+                        open_file_model.emit_event(OpenFileModelEvent::Error(anyhow!(err))),
+                }
+            })
+            .run();
+        self.find_logs_handler = Some(handler);
+    }
+
+    /// Applies filter and sort order to entries of the current location
+    fn update_files(&mut self) {
+        let filter = Some(&self.filter).filter(|filter| !is_path(filter));
+        let mut entries: Vec<&ListedEntry> = self.entries.iter()
+            .filter(|e| e.entry == DirEntry0::Up
+                || filter.is_none_or(|filter| matches_filter(filter, &e.entry.to_string())))
+            .collect();
+        match self.sort_order {
+            SortOrder::Name => entries.sort_by(|a, b| a.entry.cmp(&b.entry)),
+            SortOrder::Size => entries.sort_by_key(|e| (e.entry.rank(), Reverse(e.size))),
+            SortOrder::Modified => entries.sort_by_key(|e| (e.entry.rank(), Reverse(e.modified_at))),
+        }
+        self.files = entries.into_iter().map(|e| e.entry.clone()).collect();
+        self.emit_event(OpenFileModelEvent::FilesUpdated);
+    }
+
     fn step_up(&mut self) {
         match self.current_location.parent() {
             Some(p) => {
@@ -158,9 +378,16 @@ impl OpenFileModel {
 
     fn update_entry_info(&mut self) {
         self.entry_info = self.path_to_file()
-            .map(std::fs::metadata)
-            .and_then(Result::ok)
-            .map(|metadata| EntryInfo::from(&metadata));
+            .and_then(|path| std::fs::metadata(&path).ok().map(|metadata| (path, metadata)))
+            .map(|(path, metadata)| {
+                let mut entry_info = EntryInfo::from(&metadata);
+                if metadata.is_file() {
+                    entry_info.preview = Preview::read(&path)
+                        .map_err(|err| log::warn!("Failed to read preview of {:?}: {}", path, err))
+                        .ok();
+                }
+                entry_info
+            });
         self.emit_event(OpenFileModelEvent::EntryInfoUpdated);
     }
 
@@ -178,20 +405,23 @@ impl OpenFileModel {
         self.sender.emit_event(ModelEvent::OpenFileModelEventWrapper(evt))
     }
 
-    fn list_files(location: &PathBuf) -> io::Result<Vec<DirEntry0>> {
-        std::fs::read_dir(location).map(|read_dir| {
-            let mut files: Vec<DirEntry0> = vec![];
-            if location.as_path().parent().is_some() {
-                files.push(DirEntry0::Up);
-            }
-            read_dir
-                .filter_map(Result::ok)
-                .filter_map(|e| DirEntry0::try_from(&e).ok())
-                .sorted()
-                .for_each(|item| files.push(item));
-
-            files
-        })
+    fn list_entries(&self, location: &Path) -> io::Result<Vec<ListedEntry>> {
+        let mut entries = vec![];
+        if location.parent().is_some() {
+            entries.push(ListedEntry { entry: DirEntry0::Up, size: 0, modified_at: None });
+        }
+        let read_dir = std::fs::read_dir(location)?;
+        if self.find_logs {
+            // logs are added by the search started in background
+            return Ok(entries);
+        }
+        read_dir
+            .filter_map(Result::ok)
+            .filter(|e| self.show_hidden || !is_hidden(&e.file_name().to_string_lossy()))
+            .filter_map(|e| ListedEntry::try_from(&e).ok())
+            .sorted_by(|a, b| a.entry.cmp(&b.entry))
+            .for_each(|e| entries.push(e));
+        Ok(entries)
     }
 
     fn on_esc(root_model: &mut RootModel) -> EscapeHandlerResult {
@@ -205,6 +435,114 @@ impl OpenFileModel {
     }
 }
 
+/// Walks `location` recursively until `interrupted` returns true. Paths of the found files are
+/// relative to `location`.
+fn find_logs(location: &Path, show_hidden: bool, interrupted: impl Fn() -> bool) -> io::Result<Vec<ListedEntry>> {
+    let mut result = vec![];
+    let mut folders = vec![PathBuf::new()];
+    let mut visited = 0;
+    while let Some(folder) = folders.pop() {
+        if interrupted() {
+            break;
+        }
+        visited += 1;
+        if visited > MAX_VISITED_FOLDERS || result.len() >= MAX_FOUND_LOGS {
+            log::warn!("Search for logs in {:?} is stopped after {} folders", location, visited - 1);
+            break;
+        }
+        let read_dir = match std::fs::read_dir(location.join(&folder)) {
+            Ok(read_dir) => read_dir,
+            // the location itself must be readable, subfolders may be not
+            Err(err) if folder.as_os_str().is_empty() => return Err(err),
+            Err(err) => {
+                log::info!("Skipping {:?}: {}", folder, err);
+                continue;
+            }
+        };
+        for e in read_dir.filter_map(Result::ok) {
+            let name = e.file_name().to_string_lossy().to_string();
+            if !show_hidden && is_hidden(&name) {
+                continue;
+            }
+            let Ok(file_type) = e.file_type() else { continue; };
+            if file_type.is_dir() {
+                folders.push(folder.join(&name));
+            } else if file_type.is_file() && is_log(&name) {
+                let metadata = e.metadata().ok();
+                result.push(ListedEntry {
+                    entry: DirEntry0::File(folder.join(&name).to_string_lossy().to_string()),
+                    size: metadata.as_ref().map(Metadata::len).unwrap_or_default(),
+                    modified_at: metadata.and_then(|m| m.modified().ok()),
+                });
+            }
+        }
+    }
+    result.sort_by(|a, b| a.entry.cmp(&b.entry));
+    Ok(result)
+}
+
+fn is_log(name: &str) -> bool {
+    name.ends_with(".log") || name.contains(".log.")
+}
+
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.') && name != GO_UP
+}
+
+fn is_path(input: &str) -> bool {
+    input.contains(MAIN_SEPARATOR) || input.contains('/') || input == "~" || input == GO_UP
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', MAIN_SEPARATOR]) =>
+            PathBuf::from(home).join(rest.trim_start_matches(['/', MAIN_SEPARATOR])),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Whether `name` matches `filter`: whole name against a glob with `*` or `?`, otherwise by
+/// [fuzzy_match]
+fn matches_filter(filter: &str, name: &str) -> bool {
+    if file_set::is_glob(filter) {
+        file_set::glob_match(filter, name)
+    } else {
+        fuzzy_match(filter, name)
+    }
+}
+
+/// Whether all characters of `pattern` occur in `text` in the same order, ignoring case
+fn fuzzy_match(pattern: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    pattern.chars()
+        .flat_map(char::to_lowercase)
+        .all(|p| chars.any(|c| c == p))
+}
+
+impl TryFrom<&DirEntry> for ListedEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &DirEntry) -> Result<Self, Self::Error> {
+        let entry = DirEntry0::try_from(value)?;
+        let metadata = value.metadata().ok();
+        Ok(ListedEntry {
+            entry,
+            size: metadata.as_ref().map(Metadata::len).unwrap_or_default(),
+            modified_at: metadata.and_then(|m| m.modified().ok()),
+        })
+    }
+}
+
+impl DirEntry0 {
+    fn rank(&self) -> u8 {
+        match self {
+            DirEntry0::Up => 0,
+            DirEntry0::Folder(_) => 1,
+            DirEntry0::File(_) => 2,
+        }
+    }
+}
+
 impl TryFrom<&DirEntry> for DirEntry0 {
     type Error = anyhow::Error;
 
@@ -262,12 +600,80 @@ impl Display for DirEntry0 {
     }
 }
 
+impl Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            SortOrder::Name => "name",
+            SortOrder::Size => "size",
+            SortOrder::Modified => "modified",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl SortOrder {
+    pub fn next(self) -> SortOrder {
+        match self {
+            SortOrder::Name => SortOrder::Size,
+            SortOrder::Size => SortOrder::Modified,
+            SortOrder::Modified => SortOrder::Name,
+        }
+    }
+}
+
 impl From<&Metadata> for EntryInfo {
     fn from(value: &Metadata) -> Self {
         Self {
             size: value.len(),
             created_at: value.created().ok(),
             modified_at: value.modified().ok(),
+            permissions: permissions(value),
+            preview: None,
         }
     }
+}
+
+/// `rwxr-xr-x` on Unix, read-only flag elsewhere
+#[cfg(unix)]
+fn permissions(metadata: &Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = metadata.permissions().mode();
+    let kind = if metadata.is_dir() { 'd' } else { '-' };
+    let flags = ['r', 'w', 'x'];
+    std::iter::once(kind)
+        .chain((0..9).map(|i| if mode & (0o400 >> i) != 0 { flags[i % 3] } else { '-' }))
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &Metadata) -> String {
+    String::from(if metadata.permissions().readonly() { "read-only" } else { "read-write" })
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::{fuzzy_match, is_hidden, is_log, is_path};
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_that!(fuzzy_match("", "app.log")).is_true();
+        assert_that!(fuzzy_match("alg", "app.log")).is_true();
+        assert_that!(fuzzy_match("APL", "app.log")).is_true();
+        assert_that!(fuzzy_match("lga", "app.log")).is_false();
+        assert_that!(fuzzy_match("apps", "app.log")).is_false();
+    }
+
+    #[test]
+    fn test_names() {
+        assert_that!(is_log("app.log")).is_true();
+        assert_that!(is_log("app.log.1")).is_true();
+        assert_that!(is_log("catalog")).is_false();
+        assert_that!(is_hidden(".git")).is_true();
+        assert_that!(is_hidden("..")).is_false();
+        assert_that!(is_path("/var/log")).is_true();
+        assert_that!(is_path("~")).is_true();
+        assert_that!(is_path("app")).is_false();
+    }
 }
\ No newline at end of file
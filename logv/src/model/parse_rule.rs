@@ -0,0 +1,83 @@
+use regex::Regex;
+
+/// Regular expression with named groups. Every group becomes a field (column).
+#[derive(Clone, Debug)]
+pub struct ParseRule {
+    regex: Regex,
+    columns: Vec<String>,
+}
+
+impl ParseRule {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        let regex = Regex::new(pattern)?;
+        let columns: Vec<String> = regex.capture_names()
+            .flatten()
+            .map(String::from)
+            .collect();
+        if columns.is_empty() {
+            return Err(regex::Error::Syntax(String::from("Parse rule must contain at least one named group, e.g. (?P<level>\\w+)")));
+        }
+        Ok(ParseRule { regex, columns })
+    }
+
+    pub fn get_pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn get_columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    /// Extracts values of fields in the order of [get_columns](ParseRule::get_columns). Returns
+    /// `None` if `line` does not match.
+    pub fn parse(&self, line: &str) -> Option<Vec<String>> {
+        self.regex.captures(line).map(|captures| {
+            self.columns.iter()
+                .map(|name| captures.name(name).map(|m| m.as_str().to_string()).unwrap_or_default())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::ParseRule;
+
+    const RULE: &str = r"(?P<ts>\S+ \S+) (?P<level>\w+) \[(?P<thread>[^\]]+)\] (?P<msg>.*)";
+
+    #[test]
+    fn test_columns() {
+        let rule = ParseRule::new(RULE).unwrap();
+        assert_that!(rule.get_columns()).is_equal_to(&vec![
+            String::from("ts"),
+            String::from("level"),
+            String::from("thread"),
+            String::from("msg"),
+        ]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let rule = ParseRule::new(RULE).unwrap();
+        let actual = rule.parse("2024-01-01 10:00:00 INFO [main] Started");
+        assert_that!(actual).is_some().is_equal_to(vec![
+            String::from("2024-01-01 10:00:00"),
+            String::from("INFO"),
+            String::from("main"),
+            String::from("Started"),
+        ]);
+    }
+
+    #[test]
+    fn test_no_match() {
+        let rule = ParseRule::new(RULE).unwrap();
+        assert_that!(rule.parse("garbage")).is_none();
+    }
+
+    #[test]
+    fn test_no_named_groups() {
+        assert_that!(ParseRule::new(r"(\w+)")).is_err();
+    }
+}
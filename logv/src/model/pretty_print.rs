@@ -0,0 +1,403 @@
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SyntaxKind {
+    Plain,
+    Key,
+    String,
+    Literal,
+    Tag,
+}
+
+/// Fragment of pretty-printed text
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub kind: SyntaxKind,
+}
+
+const INDENT: &str = "  ";
+const MAX_DEPTH: usize = 64;
+/// Every attempt to parse a fragment scans at most this many bytes, so that long lines with
+/// stray brackets stay linear. Longer fragments are kept as is.
+const MAX_FRAGMENT_LEN: usize = 64 * 1024;
+
+/// Reformats JSON and XML fragments embedded into `text`, the rest is kept as is.
+/// Fragments that can not be parsed are left intact.
+pub fn pretty_print(text: &str) -> Vec<Span> {
+    let mut printer = Printer::default();
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let fragment = &rest[..char_boundary_before(rest, MAX_FRAGMENT_LEN)];
+        let formatted = match rest.as_bytes()[0] {
+            b'{' => JsonFormatter::format(fragment),
+            // arrays of plain numbers or words are more likely to be e.g. thread ids
+            b'[' => JsonFormatter::format(fragment)
+                .filter(|(_, consumed)| fragment[..*consumed].contains(['{', '"'])),
+            b'<' => XmlFormatter::format(fragment),
+            _ => None,
+        };
+        match formatted {
+            Some((spans, consumed)) => {
+                spans.into_iter().for_each(|span| printer.push(&span.text, span.kind));
+                i += consumed;
+            },
+            None => {
+                let len = rest.chars().next().map(char::len_utf8).unwrap_or(1);
+                printer.push(&rest[..len], SyntaxKind::Plain);
+                i += len;
+            }
+        }
+    }
+    printer.spans
+}
+
+/// The largest char boundary of `s` not exceeding `pos`
+fn char_boundary_before(s: &str, pos: usize) -> usize {
+    let mut pos = pos.min(s.len());
+    while !s.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+#[derive(Default)]
+struct Printer {
+    spans: Vec<Span>,
+}
+
+impl Printer {
+    fn push(&mut self, text: &str, kind: SyntaxKind) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(last) = self.spans.last_mut() {
+            if last.kind == kind {
+                last.text.push_str(text);
+                return;
+            }
+        }
+        self.spans.push(Span { text: text.to_string(), kind });
+    }
+
+    fn new_line(&mut self, depth: usize) {
+        self.push("\n", SyntaxKind::Plain);
+        self.push(&INDENT.repeat(depth), SyntaxKind::Plain);
+    }
+}
+
+struct JsonFormatter<'a> {
+    s: &'a str,
+    pos: usize,
+    out: Printer,
+}
+
+impl<'a> JsonFormatter<'a> {
+    fn format(s: &'a str) -> Option<(Vec<Span>, usize)> {
+        let mut formatter = JsonFormatter { s, pos: 0, out: Printer::default() };
+        formatter.value(0)?;
+        Some((formatter.out.spans, formatter.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().filter(u8::is_ascii_whitespace).is_some() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, ch: u8) -> Option<()> {
+        self.skip_whitespace();
+        if self.peek()? == ch {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => self.object(depth),
+            b'[' => self.array(depth),
+            b'"' => {
+                let s = self.string()?;
+                self.out.push(s, SyntaxKind::String);
+                Some(())
+            },
+            b'-' | b'0'..=b'9' => {
+                let start = self.pos;
+                while self.peek().filter(|b| b.is_ascii_digit() || b"+-.eE".contains(b)).is_some() {
+                    self.pos += 1;
+                }
+                self.out.push(&self.s[start..self.pos], SyntaxKind::Literal);
+                Some(())
+            },
+            _ => {
+                let literal = ["true", "false", "null"].into_iter()
+                    .find(|literal| self.s[self.pos..].starts_with(literal))?;
+                self.pos += literal.len();
+                self.out.push(literal, SyntaxKind::Literal);
+                Some(())
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Option<()> {
+        self.expect(b'{')?;
+        self.out.push("{", SyntaxKind::Plain);
+        if self.expect(b'}').is_some() {
+            self.out.push("}", SyntaxKind::Plain);
+            return Some(());
+        }
+        loop {
+            self.out.new_line(depth + 1);
+            self.skip_whitespace();
+            if self.peek()? != b'"' {
+                return None;
+            }
+            let key = self.string()?;
+            self.out.push(key, SyntaxKind::Key);
+            self.expect(b':')?;
+            self.out.push(": ", SyntaxKind::Plain);
+            self.value(depth + 1)?;
+            if !self.separator(b'}', depth)? {
+                return Some(());
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Option<()> {
+        self.expect(b'[')?;
+        self.out.push("[", SyntaxKind::Plain);
+        if self.expect(b']').is_some() {
+            self.out.push("]", SyntaxKind::Plain);
+            return Some(());
+        }
+        loop {
+            self.out.new_line(depth + 1);
+            self.value(depth + 1)?;
+            if !self.separator(b']', depth)? {
+                return Some(());
+            }
+        }
+    }
+
+    /// Consumes either `,` (returns `true`) or `close` (returns `false`)
+    fn separator(&mut self, close: u8, depth: usize) -> Option<bool> {
+        self.skip_whitespace();
+        let ch = self.peek()?;
+        self.pos += 1;
+        if ch == b',' {
+            self.out.push(",", SyntaxKind::Plain);
+            Some(true)
+        } else if ch == close {
+            self.out.new_line(depth);
+            self.out.push(&self.s[self.pos - 1..self.pos], SyntaxKind::Plain);
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Returns string literal including quotes
+    fn string(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut escaped = false;
+        loop {
+            let ch = self.peek()?;
+            self.pos += 1;
+            match ch {
+                b'\\' if !escaped => escaped = true,
+                b'"' if !escaped => return Some(&self.s[start..self.pos]),
+                _ => escaped = false,
+            }
+        }
+    }
+}
+
+struct XmlFormatter<'a> {
+    s: &'a str,
+    pos: usize,
+    out: Printer,
+}
+
+impl<'a> XmlFormatter<'a> {
+    fn format(s: &'a str) -> Option<(Vec<Span>, usize)> {
+        let mut formatter = XmlFormatter { s, pos: 0, out: Printer::default() };
+        formatter.document()?;
+        Some((formatter.out.spans, formatter.pos))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.s[self.pos..]
+    }
+
+    fn document(&mut self) -> Option<()> {
+        let is_tag_start = |s: &str| s.starts_with("<?")
+            || s.starts_with("</")
+            || s.starts_with("<!--")
+            || (s.starts_with('<') && s[1..].starts_with(|c: char| c.is_alphabetic()));
+        if !is_tag_start(self.rest()) || self.rest().starts_with("</") {
+            return None;
+        }
+        let mut depth = 0usize;
+        let mut is_first = true;
+        let mut after_open = false;
+        let mut inline_text = false;
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("<?") || rest.starts_with("<!--") {
+                let end = if rest.starts_with("<?") { "?>" } else { "-->" };
+                let len = rest.find(end)? + end.len();
+                if !is_first {
+                    self.out.new_line(depth);
+                }
+                self.out.push(&rest[..len], SyntaxKind::Tag);
+                self.pos += len;
+                after_open = false;
+                inline_text = false;
+            } else if rest.starts_with("</") {
+                let len = rest.find('>')? + 1;
+                depth = depth.checked_sub(1)?;
+                if !inline_text {
+                    self.out.new_line(depth);
+                }
+                self.out.push(&rest[..len], SyntaxKind::Tag);
+                self.pos += len;
+                after_open = false;
+                inline_text = false;
+                if depth == 0 {
+                    return Some(());
+                }
+            } else if is_tag_start(rest) {
+                if depth > MAX_DEPTH {
+                    return None;
+                }
+                if !is_first {
+                    self.out.new_line(depth);
+                }
+                let self_closing = self.open_tag()?;
+                inline_text = false;
+                if self_closing {
+                    after_open = false;
+                    if depth == 0 {
+                        return Some(());
+                    }
+                } else {
+                    after_open = true;
+                    depth += 1;
+                }
+            } else {
+                let len = rest.find('<').filter(|len| *len > 0)?;
+                let text = rest[..len].trim();
+                if !text.is_empty() {
+                    if after_open {
+                        inline_text = true;
+                    } else {
+                        self.out.new_line(depth);
+                    }
+                    self.out.push(text, SyntaxKind::Plain);
+                }
+                self.pos += len;
+                after_open = false;
+            }
+            is_first = false;
+        }
+    }
+
+    /// Prints opening tag with attributes. Returns `true` if the tag is self-closing.
+    fn open_tag(&mut self) -> Option<bool> {
+        let rest = self.rest();
+        let len = rest.find('>')? + 1;
+        let self_closing = rest[..len].ends_with("/>");
+        let inner = &rest[1..len - if self_closing { 2 } else { 1 }];
+        let name_len = inner.find(char::is_whitespace).unwrap_or(inner.len());
+        self.out.push(&rest[..1 + name_len], SyntaxKind::Tag);
+        let mut attributes = &inner[name_len..];
+        while !attributes.trim().is_empty() {
+            attributes = attributes.trim_start();
+            let name_len = attributes.find(['=', ' ']).unwrap_or(attributes.len());
+            self.out.push(" ", SyntaxKind::Plain);
+            self.out.push(&attributes[..name_len], SyntaxKind::Key);
+            attributes = &attributes[name_len..];
+            if let Some(value) = attributes.strip_prefix('=') {
+                let quote = value.chars().next()?;
+                let value_len = if quote == '"' || quote == '\'' {
+                    value[1..].find(quote)? + 2
+                } else {
+                    value.find(' ').unwrap_or(value.len())
+                };
+                self.out.push("=", SyntaxKind::Plain);
+                self.out.push(&value[..value_len], SyntaxKind::String);
+                attributes = &value[value_len..];
+            }
+        }
+        self.out.push(if self_closing { "/>" } else { ">" }, SyntaxKind::Tag);
+        self.pos += len;
+        Some(self_closing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use super::{pretty_print, Span, SyntaxKind, MAX_FRAGMENT_LEN};
+
+    fn to_text(spans: &[Span]) -> String {
+        spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_json() {
+        let spans = pretty_print(r#"payload: {"a": 1, "b": [true, null], "c": {}}"#);
+        assert_that!(to_text(&spans)).is_equal_to(String::from(
+            "payload: {\n  \"a\": 1,\n  \"b\": [\n    true,\n    null\n  ],\n  \"c\": {}\n}"));
+        assert_that!(spans.iter().any(|s| s.kind == SyntaxKind::Key && s.text == "\"a\"")).is_true();
+    }
+
+    #[test]
+    fn test_broken_json_is_kept() {
+        let text = r#"values {"a": 1, oops"#;
+        assert_that!(to_text(&pretty_print(text))).is_equal_to(String::from(text));
+    }
+
+    #[test]
+    fn test_xml() {
+        let spans = pretty_print(r#"got <a x="1"><b>text</b><c/></a> done"#);
+        assert_that!(to_text(&spans)).is_equal_to(String::from(
+            "got <a x=\"1\">\n  <b>text</b>\n  <c/>\n</a> done"));
+        assert_that!(spans.iter().any(|s| s.kind == SyntaxKind::Key && s.text == "x")).is_true();
+    }
+
+    #[test]
+    fn test_stray_brackets() {
+        let text = r#"[42] [main] {"a": 1}"#;
+        assert_that!(to_text(&pretty_print(text))).is_equal_to(String::from("[42] [main] {\n  \"a\": 1\n}"));
+
+        let prefix = "<a [b {c ".repeat(10_000);
+        let text = format!(r#"{}{{"a": 1}}"#, prefix);
+        assert_that!(to_text(&pretty_print(&text))).is_equal_to(format!("{}{{\n  \"a\": 1\n}}", prefix));
+    }
+
+    #[test]
+    fn test_too_long_fragment_is_kept() {
+        let text = format!(r#"{{"a": "{}"}}"#, "é".repeat(MAX_FRAGMENT_LEN));
+        assert_that!(to_text(&pretty_print(&text))).is_equal_to(text);
+    }
+
+    #[test]
+    fn test_plain_text_is_kept() {
+        for text in ["a < b and c > d", "[42] [main] started"] {
+            assert_that!(to_text(&pretty_print(text))).is_equal_to(String::from(text));
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use crate::data_source::line_registry::LineRegistryError;
-use crate::data_source::{CustomHighlights, Data, Line, LineBuilder};
+use crate::data_source::{CustomHighlights, Data, Line, LineBuilder, INVALID_BYTES_CUSTOM_DATA_KEY};
+use crate::model::guess_date_format::{GuessContext, KnownDateFormat};
 use crate::utils::GraphemeRender;
+use cursive::utils::span::IndexedCow;
 use fluent_integer::Integer;
+use std::ops::Range;
 use thiserror::Error;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct LineRender {
@@ -16,7 +20,10 @@ pub struct LineRender {
 
 impl LineRender {
     pub fn new(line: Line) -> Self {
-        let render = GraphemeRender::from_string(&line.content);
+        let invalid: Vec<Range<usize>> = line.custom_highlights.get(INVALID_BYTES_CUSTOM_DATA_KEY)
+            .map(|items| items.iter().map(|item| item.start()..item.end()).collect())
+            .unwrap_or_default();
+        let render = GraphemeRender::from_string(&line.content, &invalid);
         LineRender {
             content: line.content,
             start: line.start,
@@ -58,6 +65,50 @@ impl LineRender {
         self.find_grapheme_by_offset(offset).map(|(pos, _)| pos)
     }
 
+    /// Number of screen rows the line occupies when wrapped at `width` graphemes
+    pub fn row_count(&self, width: usize) -> usize {
+        let width = width.max(1);
+        ((self.render.len() + width - 1) / width).max(1)
+    }
+
+    /// Copy of the line rendered with `replacement` in place of content between `start` and `end`
+    /// offsets. Graphemes of the replacement are mapped to the replaced ones in order, extra ones
+    /// to the last of them, so that cursor and selection stay within the original content.
+    pub fn with_replaced_render(&self, start: usize, end: usize, replacement: &str) -> LineRender {
+        let replaced_offsets: Vec<usize> = self.render.iter()
+            .filter(|g| g.is_first_in_original && g.original_offset >= start && g.original_offset < end)
+            .map(|g| g.original_offset)
+            .collect();
+        let mut render = Vec::with_capacity(self.render.len());
+        let mut render_offset = 0;
+        for g in self.render.iter() {
+            if g.original_offset < start || g.original_offset >= end {
+                render.push(GraphemeRender { render_offset, ..g.clone() });
+                render_offset += g.render.resolve(&self.content).len();
+            } else if g.original_offset == start && g.is_first_in_original {
+                let mut prev_offset = None;
+                for (i, r) in replacement.graphemes(true).enumerate() {
+                    let original_offset = replaced_offsets.get(i)
+                        .or(replaced_offsets.last())
+                        .copied()
+                        .unwrap_or(start);
+                    render.push(GraphemeRender {
+                        original_offset,
+                        render_offset,
+                        render: IndexedCow::Owned(r.to_string()),
+                        is_first_in_original: prev_offset != Some(original_offset),
+                    });
+                    prev_offset = Some(original_offset);
+                    render_offset += r.len();
+                }
+            }
+        }
+        LineRender {
+            render,
+            ..self.clone()
+        }
+    }
+
     pub fn to_builder(&self) -> LineRenderBuilder {
         LineRenderBuilder::default()
             .with_content(self.content.clone())
@@ -105,6 +156,31 @@ impl LineRenderBuilder {
     }
 }
 
+/// Creates renders of lines read from data source. Dates are rewritten into the display zone,
+/// so that wrapping, scrolling and cursor movement see lines as they are drawn.
+#[derive(Clone, Copy, Default)]
+pub struct LineRenderer {
+    date: Option<(&'static KnownDateFormat, GuessContext)>,
+}
+
+impl LineRenderer {
+    pub fn with_date_in_display_zone(date_format: &'static KnownDateFormat, context: GuessContext) -> Self {
+        LineRenderer {
+            date: Some((date_format, context)),
+        }
+    }
+
+    pub fn render(&self, line: Line) -> LineRender {
+        let line_render = LineRender::new(line);
+        let converted = self.date.and_then(|(date_format, context)|
+            date_format.render_in_display_zone(&line_render.content, &context));
+        match converted {
+            Some((render, m)) => line_render.with_replaced_render(m.start(), m.end(), &render),
+            None => line_render,
+        }
+    }
+}
+
 pub struct DataRender {
     pub lines: Vec<LineRender>,
     pub start: Option<Integer>,
@@ -112,9 +188,9 @@ pub struct DataRender {
 }
 
 impl DataRender {
-    pub fn new(raw_data: Data) -> Self {
+    pub fn new(raw_data: Data, line_renderer: &LineRenderer) -> Self {
         DataRender {
-            lines: raw_data.lines.into_iter().map(LineRender::new).collect(),
+            lines: raw_data.lines.into_iter().map(|line| line_renderer.render(line)).collect(),
             start: raw_data.start,
             end: raw_data.end,
         }
@@ -132,4 +208,25 @@ pub enum LineNumberMissingReason {
     MissingData,
 }
 
-pub type LineNumberResult = Result<u64, LineNumberMissingReason>;
\ No newline at end of file
+pub type LineNumberResult = Result<u64, LineNumberMissingReason>;
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use crate::model::rendered::LineRenderBuilder;
+
+    #[test]
+    fn test_with_replaced_render() {
+        let line = LineRenderBuilder::default().with_content("at 10:00 ok").build();
+        let replaced = line.with_replaced_render(3, 8, "08:00+00:00");
+        let rendered: String = replaced.render.iter()
+            .map(|g| g.render.resolve(&replaced.content))
+            .collect();
+        assert_that!(rendered.as_str()).is_equal_to("at 08:00+00:00 ok");
+        assert_that!(replaced.find_grapheme_index_by_offset(7.into())).is_equal_to(Some(7));
+        assert_that!(replaced.find_grapheme_index_by_offset(8.into())).is_equal_to(Some(14));
+        assert_that!(replaced.render[14].render_offset).is_equal_to(14);
+        assert_that!(replaced.render.iter().filter(|g| g.is_first_in_original).count()).is_equal_to(line.render.len());
+    }
+}
@@ -2,10 +2,12 @@ use fluent_integer::Integer;
 use std::fmt;
 use std::ops::Add;
 
+/// Offset of the first line in viewport. In soft wrap mode `row` is the number of rows of
+/// the first line that are scrolled out of view, otherwise it is always 0.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
 pub enum  ScrollPosition {
-    FromBeginning { shift: Integer },
-    FromEnd { shift: Integer },
+    FromBeginning { shift: Integer, row: usize },
+    FromEnd { shift: Integer, row: usize },
 }
 
 impl fmt::Display for ScrollPosition {
@@ -16,23 +18,37 @@ impl fmt::Display for ScrollPosition {
 
 impl ScrollPosition {
     pub fn from_beginning(shift: impl Into<Integer>) -> Self {
-        ScrollPosition::FromBeginning { shift: shift.into() }
+        ScrollPosition::FromBeginning { shift: shift.into(), row: 0 }
     }
 
     pub fn from_end(shift: impl Into<Integer>) -> Self {
-        ScrollPosition::FromEnd { shift: shift.into() }
+        ScrollPosition::FromEnd { shift: shift.into(), row: 0 }
+    }
+
+    pub fn row(&self) -> usize {
+        match self {
+            ScrollPosition::FromBeginning { row, .. } | ScrollPosition::FromEnd { row, .. } => *row,
+        }
+    }
+
+    pub fn with_row(self, row: usize) -> Self {
+        match self {
+            ScrollPosition::FromBeginning { shift, .. } => ScrollPosition::FromBeginning { shift, row },
+            ScrollPosition::FromEnd { shift, .. } => ScrollPosition::FromEnd { shift, row },
+        }
     }
 }
 
+/// Moves the position by a number of bytes. The result points to the first row of a line.
 impl Add<Integer> for &ScrollPosition {
     type Output = ScrollPosition;
 
     fn add(self, rhs: Integer) -> Self::Output {
         match self {
-            ScrollPosition::FromBeginning { shift } =>
-                ScrollPosition::FromBeginning { shift: *shift + rhs },
-            ScrollPosition::FromEnd { shift } =>
-                ScrollPosition::FromEnd { shift: *shift - rhs},
+            ScrollPosition::FromBeginning { shift, .. } =>
+                ScrollPosition::FromBeginning { shift: *shift + rhs, row: 0 },
+            ScrollPosition::FromEnd { shift, .. } =>
+                ScrollPosition::FromEnd { shift: *shift - rhs, row: 0 },
         }
     }
 }
@@ -40,8 +56,8 @@ impl Add<Integer> for &ScrollPosition {
 impl Into<Integer> for ScrollPosition {
     fn into(self) -> Integer {
         match self {
-            ScrollPosition::FromBeginning { shift } => shift,
-            ScrollPosition::FromEnd { shift } => - shift,
+            ScrollPosition::FromBeginning { shift, .. } => shift,
+            ScrollPosition::FromEnd { shift, .. } => - shift,
         }
     }
 }
@@ -49,15 +65,15 @@ impl Into<Integer> for ScrollPosition {
 impl From<Integer> for ScrollPosition {
     fn from(value: Integer) -> Self {
         if value >= 0 {
-            ScrollPosition::FromBeginning { shift: value }
+            ScrollPosition::from_beginning(value)
         } else {
-            ScrollPosition::FromEnd { shift: -value }
+            ScrollPosition::from_end(-value)
         }
     }
 }
 
 impl Default for ScrollPosition {
     fn default() -> Self {
-        ScrollPosition::FromBeginning { shift: 0.into() }
+        ScrollPosition::from_beginning(0)
     }
 }
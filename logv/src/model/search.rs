@@ -11,6 +11,7 @@ use crate::background_process::background_process_handler::BackgroundProcessHand
 use crate::background_process::run_in_background::RunInBackground;
 use crate::background_process::signal::Signal;
 use crate::background_process::task_context::TaskContext;
+use crate::data_source::transcoded::TranscodedBackend;
 use crate::data_source::Direction;
 use crate::immediate::Immediate;
 use crate::interval::Interval;
@@ -29,6 +30,8 @@ pub struct Search {
     last_request: Option<Interval<Integer>>,
     daemon_handler: BackgroundProcessHandler,
     search_request_sender: Sender<SearchRequest>,
+    // decoded file, if occurrences are found in the file rather than in decoded content
+    file_offsets: Option<TranscodedBackend>,
 }
 
 pub type CurrentOccurrenceResult = Result<(Rc<Vec<Occurrence>>, Option<usize>), SearchError>;
@@ -83,6 +86,24 @@ impl Search {
             last_request: None,
             daemon_handler,
             search_request_sender,
+            file_offsets: None,
+        }
+    }
+
+    /// Occurrences are found in offsets of `transcoded` file, so they are converted to offsets
+    /// of decoded content before the cursor is moved to them. They are not highlighted in text.
+    pub fn with_file_offsets(mut self, transcoded: TranscodedBackend) -> Self {
+        self.file_offsets = Some(transcoded);
+        self
+    }
+
+    fn to_decoded(&self, search_result: SearchResult) -> SearchResult {
+        match &self.file_offsets {
+            Some(transcoded) => search_result.map(|occurrence| Occurrence::new(
+                transcoded.to_decoded_offset(occurrence.start.as_u64()),
+                transcoded.to_decoded_offset(occurrence.end.as_u64()),
+            )),
+            None => search_result,
         }
     }
 
@@ -105,7 +126,7 @@ impl Search {
         if let Some(last_occurrence) = next_occurrence {
             self.last_occurrence = Some(last_occurrence);
             self.model_sender
-                .emit_event(ModelEvent::Search(Ok(last_occurrence)));
+                .emit_event(ModelEvent::Search(self.to_decoded(Ok(last_occurrence))));
             Ok(())
         } else {
             self.search_request_sender
@@ -118,6 +139,9 @@ impl Search {
         &mut self,
         viewport: Interval<Integer>,
     ) -> Immediate<CurrentOccurrenceResult> {
+        if self.file_offsets.is_some() {
+            return Immediate::Immediate(Err(SearchError::NotFound));
+        }
         if Some(viewport) == self.last_request {
             return match &self.occurrences {
                 Some(v) => {
@@ -164,7 +188,7 @@ impl Search {
                     self.last_occurrence = Some(*last_occurrence);
                 }
                 self.model_sender
-                    .emit_event(ModelEvent::Search(search_result));
+                    .emit_event(ModelEvent::Search(self.to_decoded(search_result)));
                 Ok(())
             }
             SearchResponse::FindAll(viewport, data) => match data {
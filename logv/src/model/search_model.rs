@@ -23,6 +23,7 @@ pub struct SearchModel<R: RunInBackground> {
     cursor_pos: Option<Integer>,
     is_backward: bool,
     is_regexp: bool,
+    is_hex: bool,
 }
 
 impl<R: RunInBackground + 'static> SearchModel<R> {
@@ -42,6 +43,7 @@ impl<R: RunInBackground + 'static> SearchModel<R> {
             cursor_pos: None,
             is_backward: false,
             is_regexp: false,
+            is_hex: false,
         }
     }
 
@@ -77,6 +79,7 @@ impl<R: RunInBackground + 'static> SearchModel<R> {
             .reader_factory(reader)
             .pattern(self.pattern.clone())
             .is_regexp(self.is_regexp)
+            .is_hex(self.is_hex)
             .initial_offset(self.cursor_pos.filter(|_| self.is_from_cursor))
             .is_backward(self.is_backward)
             .build()
@@ -91,6 +94,23 @@ impl<R: RunInBackground + 'static> SearchModel<R> {
         Ok(search)
     }
 
+    /// Creates search for the current pattern without looking for an occurrence, so that the
+    /// next one is found from `cursor` on demand
+    pub fn resume_search(&mut self, reader: Box<dyn ReaderFactory>, cursor: Integer) -> anyhow::Result<Search> {
+        let background_process_registry = self.runner.clone();
+        let background_process_registry = &mut *background_process_registry.get_mut_ref();
+        let constructor = NavigableSearcherConstructorBuilder::default()
+            .reader_factory(reader)
+            .pattern(self.pattern.clone())
+            .is_regexp(self.is_regexp)
+            .is_hex(self.is_hex)
+            .initial_offset(Some(cursor))
+            .is_backward(self.is_backward)
+            .build()
+            .map_err(|e| anyhow!(e.to_string()))?;
+        Ok(Search::new(self.model_sender.clone(), constructor, background_process_registry))
+    }
+
     pub fn is_from_cursor(&self) -> bool {
         self.is_from_cursor
     }
@@ -126,6 +146,15 @@ impl<R: RunInBackground + 'static> SearchModel<R> {
         self.is_regexp = is_regexp;
     }
 
+    pub fn is_hex(&self) -> bool {
+        self.is_hex
+    }
+
+    /// Pattern is searched for as a sequence of bytes written in hex
+    pub fn set_hex(&mut self, is_hex: bool) {
+        self.is_hex = is_hex;
+    }
+
     fn on_esc(root_model: &mut RootModel) -> EscapeHandlerResult {
         let mut search_model = root_model.get_search_model();
         if search_model.is_visible() {
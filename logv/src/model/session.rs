@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context};
+use fluent_integer::Integer;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::config::config_dir;
+use crate::data_source::line_index_cache::{content_checksum, ContentChecksum};
+use crate::model::scroll_position::ScrollPosition;
+
+const SESSIONS_FILE_NAME: &str = "sessions.yaml";
+const MAX_SESSIONS: usize = 100;
+pub const MAX_RECENT_FILES: usize = 10;
+
+/// Filter applied to a file, in a form it can be applied again
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FilterSpec {
+    Substring { pattern: String, neighbourhood: u8 },
+    Field { expression: String, neighbourhood: u8 },
+    Regex(String),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SearchSpec {
+    pub pattern: String,
+    pub is_regexp: bool,
+}
+
+/// State of a file view when it was left. Offsets are relative to the filtered content, if
+/// `filter` is set.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Session {
+    pub file_size: u64,
+    /// Seconds since epoch
+    pub modified: Option<u64>,
+    /// Checksum of the first `file_size` bytes, tells a grown file from a rewritten one
+    pub checksum: Option<ContentChecksum>,
+    /// Seconds since epoch
+    pub last_opened: u64,
+    pub cursor: Integer,
+    pub scroll_position: ScrollPosition,
+    pub horizontal_scroll: Integer,
+    pub search: Option<SearchSpec>,
+    pub filter: Option<FilterSpec>,
+}
+
+impl Session {
+    /// Offsets still point to the same content if the file is unchanged or has only been
+    /// appended to
+    pub fn is_valid_for(&self, path: &Path) -> bool {
+        let Some((file_size, modified)) = file_stamp(path) else { return false; };
+        if file_size == self.file_size && modified == self.modified {
+            return true;
+        }
+        // sessions stored without a checksum are kept for unchanged files only
+        let Some(checksum) = self.checksum else { return false; };
+        file_size > self.file_size && file_checksum(path, self.file_size) == Some(checksum)
+    }
+}
+
+/// Checksum of the first `size` bytes of a file, see [content_checksum]
+pub fn file_checksum(path: &Path, size: u64) -> Option<ContentChecksum> {
+    let mut file = std::fs::File::open(path).ok()?;
+    content_checksum(&mut file, size).ok()
+}
+
+/// Size and modification time (seconds since epoch) of a file
+pub fn file_stamp(path: &Path) -> Option<(u64, Option<u64>)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok().and_then(epoch_seconds);
+    Some((metadata.len(), modified))
+}
+
+pub fn now() -> u64 {
+    epoch_seconds(SystemTime::now()).unwrap_or_default()
+}
+
+fn epoch_seconds(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+/// Sessions of recently opened files, kept in `sessions.yaml` in [config_dir]
+#[derive(Debug, Default)]
+pub struct Sessions {
+    sessions: BTreeMap<String, Session>,
+}
+
+impl Sessions {
+    pub fn load() -> Self {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            return Sessions::default();
+        };
+        std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Self::parse(&content))
+            .unwrap_or_else(|err| {
+                log::warn!("Failed to load sessions from {:?}: {:?}", path, err);
+                Sessions::default()
+            })
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or(anyhow!("Config directory is unknown"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, self.to_yaml()?)
+            .with_context(|| format!("Failed to save sessions to {:?}", path))
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Session> {
+        self.sessions.get(path.to_string_lossy().as_ref())
+    }
+
+    /// Stores `session`, forgetting the least recently opened files when there are too many
+    pub fn set(&mut self, path: &Path, session: Session) {
+        self.sessions.insert(path.to_string_lossy().to_string(), session);
+        while self.sessions.len() > MAX_SESSIONS {
+            let oldest = self.sessions.iter()
+                .min_by_key(|(_, session)| session.last_opened)
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                self.sessions.remove(&oldest);
+            }
+        }
+    }
+
+    /// Most recently opened files first
+    pub fn recent_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<(&String, u64)> = self.sessions.iter()
+            .map(|(path, session)| (path, session.last_opened))
+            .collect();
+        files.sort_by(|a, b| b.1.cmp(&a.1));
+        files.into_iter()
+            .take(MAX_RECENT_FILES)
+            .map(|(path, _)| PathBuf::from(path))
+            .collect()
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        let docs = YamlLoader::load_from_str(content)?;
+        let sessions = docs.first()
+            .and_then(|doc| doc.as_hash())
+            .map(|hash| hash.iter()
+                .filter_map(|(path, session)| path.as_str().zip(parse_session(session)))
+                .map(|(path, session)| (path.to_string(), session))
+                .collect())
+            .unwrap_or_default();
+        Ok(Sessions {
+            sessions,
+        })
+    }
+
+    fn to_yaml(&self) -> anyhow::Result<String> {
+        let mut hash = yaml_rust2::yaml::Hash::new();
+        for (path, session) in &self.sessions {
+            hash.insert(Yaml::String(path.clone()), session_to_yaml(session));
+        }
+        let mut out = String::new();
+        YamlEmitter::new(&mut out).dump(&Yaml::Hash(hash))?;
+        Ok(out)
+    }
+
+    fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(SESSIONS_FILE_NAME))
+    }
+}
+
+fn parse_session(value: &Yaml) -> Option<Session> {
+    let integer = |value: &Yaml| value.as_i64().map(Integer::from);
+    let unsigned = |value: &Yaml| value.as_i64().and_then(|n| u64::try_from(n).ok());
+    // checksums take all 64 bits, so they are stored as is
+    let bits = |value: &Yaml| value.as_i64().map(|n| n as u64);
+    let neighbourhood = |value: &Yaml| value["neighbourhood"].as_i64()
+        .and_then(|n| u8::try_from(n).ok())
+        .unwrap_or_default();
+
+    let scroll = &value["scroll"];
+    let shift = integer(&scroll["shift"]).unwrap_or_default();
+    let row = scroll["row"].as_i64().and_then(|n| usize::try_from(n).ok()).unwrap_or_default();
+    let scroll_position = if scroll["from_end"].as_bool().unwrap_or_default() {
+        ScrollPosition::FromEnd { shift, row }
+    } else {
+        ScrollPosition::FromBeginning { shift, row }
+    };
+    let search = &value["search"];
+    let search = search["pattern"].as_str().map(|pattern| SearchSpec {
+        pattern: pattern.to_string(),
+        is_regexp: search["regexp"].as_bool().unwrap_or_default(),
+    });
+    let filter = &value["filter"];
+    let filter = if let Some(pattern) = filter["substring"].as_str() {
+        Some(FilterSpec::Substring { pattern: pattern.to_string(), neighbourhood: neighbourhood(filter) })
+    } else if let Some(expression) = filter["field"].as_str() {
+        Some(FilterSpec::Field { expression: expression.to_string(), neighbourhood: neighbourhood(filter) })
+    } else {
+        filter["regex"].as_str().map(|pattern| FilterSpec::Regex(pattern.to_string()))
+    };
+    Some(Session {
+        file_size: unsigned(&value["size"])?,
+        modified: unsigned(&value["modified"]),
+        checksum: bits(&value["head"]).zip(bits(&value["tail"]))
+            .map(|(head, tail)| ContentChecksum { head, tail }),
+        last_opened: unsigned(&value["opened"]).unwrap_or_default(),
+        cursor: integer(&value["cursor"]).unwrap_or_default(),
+        scroll_position,
+        horizontal_scroll: integer(&value["horizontal_scroll"]).unwrap_or_default(),
+        search,
+        filter,
+    })
+}
+
+fn session_to_yaml(session: &Session) -> Yaml {
+    let mut hash = yaml_rust2::yaml::Hash::new();
+    let insert = |hash: &mut yaml_rust2::yaml::Hash, key: &str, value: Yaml| {
+        hash.insert(Yaml::String(key.to_string()), value);
+    };
+    let integer = |n: Integer| Yaml::Integer(n.as_i64());
+
+    insert(&mut hash, "size", Yaml::Integer(session.file_size as i64));
+    if let Some(modified) = session.modified {
+        insert(&mut hash, "modified", Yaml::Integer(modified as i64));
+    }
+    if let Some(checksum) = session.checksum {
+        insert(&mut hash, "head", Yaml::Integer(checksum.head as i64));
+        insert(&mut hash, "tail", Yaml::Integer(checksum.tail as i64));
+    }
+    insert(&mut hash, "opened", Yaml::Integer(session.last_opened as i64));
+    insert(&mut hash, "cursor", integer(session.cursor));
+
+    let mut scroll = yaml_rust2::yaml::Hash::new();
+    let (shift, row, from_end) = match session.scroll_position {
+        ScrollPosition::FromBeginning { shift, row } => (shift, row, false),
+        ScrollPosition::FromEnd { shift, row } => (shift, row, true),
+    };
+    insert(&mut scroll, "shift", integer(shift));
+    insert(&mut scroll, "row", Yaml::Integer(row as i64));
+    insert(&mut scroll, "from_end", Yaml::Boolean(from_end));
+    insert(&mut hash, "scroll", Yaml::Hash(scroll));
+    insert(&mut hash, "horizontal_scroll", integer(session.horizontal_scroll));
+
+    if let Some(search) = &session.search {
+        let mut value = yaml_rust2::yaml::Hash::new();
+        insert(&mut value, "pattern", Yaml::String(search.pattern.clone()));
+        insert(&mut value, "regexp", Yaml::Boolean(search.is_regexp));
+        insert(&mut hash, "search", Yaml::Hash(value));
+    }
+    if let Some(filter) = &session.filter {
+        let mut value = yaml_rust2::yaml::Hash::new();
+        match filter {
+            FilterSpec::Substring { pattern, neighbourhood } => {
+                insert(&mut value, "substring", Yaml::String(pattern.clone()));
+                insert(&mut value, "neighbourhood", Yaml::Integer(*neighbourhood as i64));
+            }
+            FilterSpec::Field { expression, neighbourhood } => {
+                insert(&mut value, "field", Yaml::String(expression.clone()));
+                insert(&mut value, "neighbourhood", Yaml::Integer(*neighbourhood as i64));
+            }
+            FilterSpec::Regex(pattern) => insert(&mut value, "regex", Yaml::String(pattern.clone())),
+        }
+        insert(&mut hash, "filter", Yaml::Hash(value));
+    }
+    Yaml::Hash(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use spectral::prelude::*;
+
+    use crate::data_source::line_index_cache::ContentChecksum;
+    use crate::model::scroll_position::ScrollPosition;
+    use crate::test_extensions::TempFile;
+    use super::{file_checksum, file_stamp, FilterSpec, SearchSpec, Session, Sessions};
+
+    fn session(last_opened: u64) -> Session {
+        Session {
+            file_size: 4096,
+            modified: Some(1_700_000_000),
+            checksum: Some(ContentChecksum { head: 0xcbf29ce484222325, tail: 42 }),
+            last_opened,
+            cursor: 1234.into(),
+            scroll_position: ScrollPosition::FromEnd { shift: 512.into(), row: 2 },
+            horizontal_scroll: 8.into(),
+            search: Some(SearchSpec { pattern: String::from("ERROR"), is_regexp: false }),
+            filter: Some(FilterSpec::Field { expression: String::from("level=error"), neighbourhood: 2 }),
+        }
+    }
+
+    #[test]
+    fn test_sessions_round_trip() {
+        let mut sessions = Sessions::default();
+        sessions.set(Path::new("/var/log/app.log"), session(10));
+        sessions.set(Path::new("/var/log/other.log"), Session {
+            modified: None,
+            checksum: None,
+            scroll_position: ScrollPosition::from_beginning(100),
+            search: None,
+            filter: Some(FilterSpec::Regex(String::from("^\\d+"))),
+            ..session(20)
+        });
+
+        let parsed = Sessions::parse(&sessions.to_yaml().unwrap()).unwrap();
+        assert_that!(parsed.sessions).is_equal_to(sessions.sessions);
+        assert_that!(parsed.recent_files())
+            .is_equal_to(vec![PathBuf::from("/var/log/other.log"), PathBuf::from("/var/log/app.log")]);
+    }
+
+    #[test]
+    fn test_is_valid_for() {
+        let file = TempFile::new("session.log", "first\nsecond\n");
+        let path = file.path();
+        let (file_size, modified) = file_stamp(path).unwrap();
+        let session = Session {
+            file_size,
+            modified,
+            checksum: file_checksum(path, file_size),
+            ..session(10)
+        };
+        assert_that!(session.is_valid_for(path)).is_true();
+
+        std::fs::write(path, "first\nsecond\nthird\n").unwrap();
+        assert_that!(session.is_valid_for(path)).is_true();
+        assert_that!(Session { checksum: None, ..session.clone() }.is_valid_for(path)).is_false();
+
+        std::fs::write(path, "other\nsecond\nthird\n").unwrap();
+        assert_that!(session.is_valid_for(path)).is_false();
+
+        std::fs::write(path, "first\n").unwrap();
+        assert_that!(session.is_valid_for(path)).is_false();
+    }
+}
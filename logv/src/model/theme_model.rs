@@ -0,0 +1,67 @@
+use crossbeam_channel::Sender;
+
+use crate::app_theme::app_theme::AppThemeName;
+use crate::model::escape_handler::{CompoundEscapeHandler, EscapeHandlerManager, EscapeHandlerResult};
+use crate::model::model::{ModelEvent, RootModel};
+use crate::shared::Shared;
+use crate::utils::event_emitter::EventEmitter;
+
+#[derive(Debug)]
+pub enum ThemeModelEvent {
+    Open(bool),
+}
+
+/// Lists built-in themes and the ones found in themes directory, so that user can pick one
+pub struct ThemeModel {
+    model_sender: Sender<ModelEvent>,
+    is_open: bool,
+    escape_handler_manager: EscapeHandlerManager,
+    themes: Vec<AppThemeName>,
+}
+
+impl ThemeModel {
+    pub fn new(model_sender: Sender<ModelEvent>, escape_handler: Shared<CompoundEscapeHandler>) -> Self {
+        ThemeModel {
+            model_sender,
+            is_open: false,
+            escape_handler_manager: EscapeHandlerManager::new(escape_handler, Self::on_esc),
+            themes: vec![],
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    /// Opens or closes the list. Themes directory is scanned on every opening.
+    pub fn set_open(&mut self, is_open: bool) {
+        if self.is_open != is_open {
+            self.is_open = is_open;
+            if is_open {
+                self.themes = AppThemeName::available();
+            }
+            self.escape_handler_manager.toggle(is_open);
+            self.emit_event(ThemeModelEvent::Open(is_open));
+        }
+    }
+
+    pub fn get_themes(&self) -> &Vec<AppThemeName> {
+        &self.themes
+    }
+
+    fn on_esc(root_model: &mut RootModel) -> EscapeHandlerResult {
+        let me = &mut *root_model.get_theme_model();
+        if me.is_open() {
+            me.set_open(false);
+            EscapeHandlerResult::Dismiss
+        } else {
+            EscapeHandlerResult::Ignore
+        }
+    }
+}
+
+impl EventEmitter<ThemeModelEvent> for ThemeModel {
+    fn emit_event(&self, evt: ThemeModelEvent) {
+        self.model_sender.emit_event(ModelEvent::ThemeDialogEvent(evt));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::time::Duration as StdDuration;
+
+use anyhow::bail;
+use chrono::{Duration, NaiveDateTime};
+use fluent_integer::Integer;
+
+use crate::background_process::task_context::TaskContext;
+use crate::data_source::reader_factory::ReaderFactory;
+use crate::model::guess_date_format::{GuessContext, KnownDateFormat};
+use crate::utils;
+
+const INTERRUPT_CHECK_RATE: StdDuration = StdDuration::from_millis(100);
+
+/// Number of screen columns the delta is aligned to. Longer deltas take more space.
+pub const TIME_DELTA_WIDTH: usize = 8;
+
+/// Compact form of `delta` that fits [TIME_DELTA_WIDTH]: `+0.250s`, `+42s`, `+5m03s`, `+2h04m`,
+/// `+3d02h`
+pub fn format_delta(delta: Duration) -> String {
+    let sign = if delta < Duration::zero() { '-' } else { '+' };
+    let delta = delta.abs();
+    let s = delta.num_seconds();
+    if s < 10 {
+        format!("{}{}.{:03}s", sign, s, delta.num_milliseconds() % 1000)
+    } else if s < 60 {
+        format!("{}{}s", sign, s)
+    } else if s < 3600 {
+        format!("{}{}m{:02}s", sign, s / 60, s % 60)
+    } else if s < 86400 {
+        format!("{}{}h{:02}m", sign, s / 3600, s % 3600 / 60)
+    } else {
+        format!("{}{}d{:02}h", sign, s / 86400, s % 86400 / 3600)
+    }
+}
+
+/// Time elapsed by each line that has a date, by line start. The delta is counted from
+/// `reference`, if it is set, or from the previous line with a date otherwise. `previous` is
+/// the date of the last line before `lines` that has one.
+pub fn time_deltas<'a, I>(
+    lines: I,
+    date_format: &KnownDateFormat,
+    ctx: &GuessContext,
+    previous: Option<NaiveDateTime>,
+    reference: Option<NaiveDateTime>,
+) -> BTreeMap<Integer, Duration>
+    where I: IntoIterator<Item = (Integer, &'a str)>
+{
+    let mut previous = previous;
+    let mut result = BTreeMap::new();
+    for (start, content) in lines {
+        let Some(dt) = date_format.parse(content, ctx) else { continue; };
+        if let Some(base) = reference.or(previous) {
+            result.insert(start, dt - base);
+        }
+        previous = Some(dt);
+    }
+    result
+}
+
+/// Looks for the first line after `start` whose date is more than `threshold` later than the date
+/// of the previous line that has one. Returns start of that line.
+pub fn find_gap(
+    ctx: &mut TaskContext<(), anyhow::Result<Option<Integer>>>,
+    reader_factory: Box<dyn ReaderFactory>,
+    start: Integer,
+    date_format: &KnownDateFormat,
+    guess_context: &GuessContext,
+    threshold: Duration,
+) -> anyhow::Result<Option<Integer>> {
+    let mut reader = BufReader::new(reader_factory.new_reader()?);
+    reader.seek(SeekFrom::Start(start.as_u64()))?;
+    find_gap_in(reader, start, date_format, guess_context, threshold,
+                || ctx.interrupted_debounced(INTERRUPT_CHECK_RATE))
+}
+
+fn find_gap_in<R: BufRead>(
+    mut reader: R,
+    start: Integer,
+    date_format: &KnownDateFormat,
+    guess_context: &GuessContext,
+    threshold: Duration,
+    interrupted: impl Fn() -> bool,
+) -> anyhow::Result<Option<Integer>> {
+    let mut buf = vec![];
+    let mut offset = start;
+    let mut previous = None;
+    loop {
+        if interrupted() {
+            bail!("Search for time gap has been cancelled");
+        }
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        let mut line = String::from_utf8_lossy(&buf).to_string();
+        utils::trim_newline(&mut line);
+        if let Some(dt) = date_format.parse(&line, guess_context) {
+            if previous.is_some_and(|previous| dt - previous > threshold) {
+                return Ok(Some(offset));
+            }
+            previous = Some(dt);
+        }
+        offset = offset + n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use chrono::{Duration, NaiveDateTime};
+    use fluent_integer::Integer;
+    use spectral::prelude::*;
+
+    use crate::model::guess_date_format::{find_or_create_format, GuessContext};
+    use super::{find_gap_in, format_delta, time_deltas};
+
+    const LOG: &str = "2024-01-01 10:00:00 started\n\
+        2024-01-01 10:00:05 working\n\
+        continuation\n\
+        2024-01-01 10:03:00 stalled\n\
+        2024-01-01 10:03:01 done\n";
+
+    #[test]
+    fn test_format_delta() {
+        assert_that!(format_delta(Duration::milliseconds(250))).is_equal_to(String::from("+0.250s"));
+        assert_that!(format_delta(Duration::seconds(42))).is_equal_to(String::from("+42s"));
+        assert_that!(format_delta(Duration::seconds(303))).is_equal_to(String::from("+5m03s"));
+        assert_that!(format_delta(Duration::minutes(124))).is_equal_to(String::from("+2h04m"));
+        assert_that!(format_delta(Duration::hours(74))).is_equal_to(String::from("+3d02h"));
+        assert_that!(format_delta(Duration::seconds(-15))).is_equal_to(String::from("-15s"));
+    }
+
+    #[test]
+    fn test_time_deltas() {
+        let date_format = find_or_create_format("%Y-%m-%d %H:%M:%S").unwrap();
+        let ctx = GuessContext::with_year(2024);
+        let mut start = 0;
+        let lines: Vec<(Integer, &str)> = LOG.lines()
+            .map(|line| {
+                let result = (start.into(), line);
+                start += line.len() + 1;
+                result
+            })
+            .collect();
+
+        let deltas = time_deltas(lines.iter().copied(), date_format, &ctx, None, None);
+        assert_that!(deltas.values().copied().collect::<Vec<_>>())
+            .is_equal_to(vec![Duration::seconds(5), Duration::seconds(175), Duration::seconds(1)]);
+        assert_that!(deltas.contains_key(&lines[2].0)).is_false();
+
+        let reference = NaiveDateTime::parse_from_str("2024-01-01 10:00:00", "%Y-%m-%d %H:%M:%S").ok();
+        let deltas = time_deltas(lines.iter().copied(), date_format, &ctx, None, reference);
+        assert_that!(deltas.get(&lines[0].0)).is_equal_to(Some(&Duration::zero()));
+        assert_that!(deltas.get(&lines[4].0)).is_equal_to(Some(&Duration::seconds(181)));
+    }
+
+    #[test]
+    fn test_find_gap() {
+        let date_format = find_or_create_format("%Y-%m-%d %H:%M:%S").unwrap();
+        let ctx = GuessContext::with_year(2024);
+        let find = |start: usize, seconds: i64| find_gap_in(
+            Cursor::new(&LOG.as_bytes()[start..]), start.into(), date_format, &ctx,
+            Duration::seconds(seconds), || false).unwrap();
+
+        let stalled = LOG.find("2024-01-01 10:03:00").unwrap();
+        assert_that!(find(0, 60)).is_equal_to(Some(stalled.into()));
+        assert_that!(find(0, 3)).is_equal_to(Some(LOG.find("2024-01-01 10:00:05").unwrap().into()));
+        assert_that!(find(stalled, 0)).is_equal_to(Some(LOG.find("2024-01-01 10:03:01").unwrap().into()));
+        assert_that!(find(0, 600)).is_none();
+    }
+}
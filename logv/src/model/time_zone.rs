@@ -0,0 +1,206 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Offset, TimeZone, Utc};
+use lazy_static::lazy_static;
+use phf::phf_map;
+use regex::Regex;
+
+const HOUR: i32 = 3600;
+
+/// Offsets of common zone abbreviations. Abbreviations are ambiguous in general (e.g. IST), the
+/// most widespread meaning is used.
+const ZONE_ABBREVIATIONS: phf::Map<&str, i32> = phf_map! {
+    "UTC" => 0,
+    "GMT" => 0,
+    "Z" => 0,
+    "WET" => 0,
+    "WEST" => HOUR,
+    "BST" => HOUR,
+    "CET" => HOUR,
+    "CEST" => 2 * HOUR,
+    "EET" => 2 * HOUR,
+    "EEST" => 3 * HOUR,
+    "MSK" => 3 * HOUR,
+    "IST" => 5 * HOUR + HOUR / 2,
+    "CST" => -6 * HOUR,
+    "CDT" => -5 * HOUR,
+    "EST" => -5 * HOUR,
+    "EDT" => -4 * HOUR,
+    "MST" => -7 * HOUR,
+    "MDT" => -6 * HOUR,
+    "PST" => -8 * HOUR,
+    "PDT" => -7 * HOUR,
+    "AKST" => -9 * HOUR,
+    "AKDT" => -8 * HOUR,
+    "HST" => -10 * HOUR,
+    "JST" => 9 * HOUR,
+    "KST" => 9 * HOUR,
+    "AEST" => 10 * HOUR,
+    "AEDT" => 11 * HOUR,
+};
+
+lazy_static! {
+    static ref OFFSET_REGEX: Regex = Regex::new(r"^(?:UTC|GMT)?([+-])(\d{1,2})(?::?(\d{2}))?$").unwrap();
+    /// Zone right after a timestamp, e.g. `Z`, ` +02:00`, `+0200` or ` CEST`
+    static ref ZONE_SUFFIX_REGEX: Regex = Regex::new(r"^ ?(Z\b|[+-]\d{2}:?\d{2}\b|[A-Z]{1,4}\b)").unwrap();
+}
+
+/// Parses an offset (`+02:00`, `+0200`, `-05`, `UTC+3`) or a zone abbreviation (`CEST`)
+pub fn parse_offset(s: &str) -> Option<FixedOffset> {
+    if let Some(seconds) = ZONE_ABBREVIATIONS.get(s.to_uppercase().as_str()) {
+        return FixedOffset::east_opt(*seconds);
+    }
+    let captures = OFFSET_REGEX.captures(s)?;
+    let hours: i32 = captures[2].parse().ok()?;
+    let minutes: i32 = captures.get(3).map_or(Some(0), |m| m.as_str().parse().ok())?;
+    if minutes >= 60 {
+        return None;
+    }
+    let seconds = hours * HOUR + minutes * 60;
+    FixedOffset::east_opt(if &captures[1] == "-" { -seconds } else { seconds })
+}
+
+/// Finds a zone at the beginning of `s`, which is the text that follows a timestamp.
+/// Returns the offset and the length of the zone text, including the leading space.
+pub fn parse_zone_suffix(s: &str) -> Option<(FixedOffset, usize)> {
+    let captures = ZONE_SUFFIX_REGEX.captures(s)?;
+    let offset = parse_offset(&captures[1])?;
+    Some((offset, captures[0].len()))
+}
+
+/// Time zone of timestamps that have no zone in them
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum AssumedZone {
+    #[default]
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl AssumedZone {
+    /// Interprets `dt` as a wall time in this zone. Times skipped by DST transitions are not
+    /// resolved.
+    pub fn resolve(&self, dt: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            AssumedZone::Local => Local.from_local_datetime(dt).earliest().map(|dt| dt.fixed_offset()),
+            AssumedZone::Fixed(offset) => offset.from_local_datetime(dt).single(),
+        }
+    }
+
+    /// Wall time of the instant `dt` in this zone
+    pub fn wall_time(&self, dt: DateTime<FixedOffset>) -> NaiveDateTime {
+        match self {
+            AssumedZone::Local => dt.with_timezone(&Local).naive_local(),
+            AssumedZone::Fixed(offset) => dt.with_timezone(offset).naive_local(),
+        }
+    }
+}
+
+impl FromStr for AssumedZone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(AssumedZone::Local);
+        }
+        parse_offset(s)
+            .map(AssumedZone::Fixed)
+            .ok_or(format!("Unknown time zone: {}, expected local, UTC, an offset like +02:00 or an abbreviation like CET", s))
+    }
+}
+
+impl Display for AssumedZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssumedZone::Local => f.write_str("local"),
+            AssumedZone::Fixed(offset) if offset.local_minus_utc() == 0 => f.write_str("UTC"),
+            AssumedZone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+/// Time zone timestamps are shown in
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum DisplayZone {
+    /// Timestamps are shown as they are written
+    #[default]
+    Original,
+    Utc,
+    Local,
+}
+
+impl DisplayZone {
+    /// Zone that follows this one when switching them in turn
+    pub fn next(&self) -> DisplayZone {
+        match self {
+            DisplayZone::Original => DisplayZone::Utc,
+            DisplayZone::Utc => DisplayZone::Local,
+            DisplayZone::Local => DisplayZone::Original,
+        }
+    }
+
+    /// Converts a zoned time into this zone. Original zone keeps it as is.
+    pub fn convert(&self, dt: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            DisplayZone::Original => dt,
+            DisplayZone::Utc => dt.with_timezone(&Utc.fix()),
+            DisplayZone::Local => dt.with_timezone(&Local).fixed_offset(),
+        }
+    }
+}
+
+impl Display for DisplayZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayZone::Original => f.write_str("original time zone"),
+            DisplayZone::Utc => f.write_str("UTC"),
+            DisplayZone::Local => f.write_str("local time zone"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDateTime};
+    use spectral::prelude::*;
+
+    use super::{parse_offset, parse_zone_suffix, AssumedZone, DisplayZone};
+
+    fn offset(hours: i32, minutes: i32) -> Option<FixedOffset> {
+        FixedOffset::east_opt(hours * 3600 + minutes * 60)
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_that!(parse_offset("+02:00")).is_equal_to(offset(2, 0));
+        assert_that!(parse_offset("-0530")).is_equal_to(offset(-5, -30));
+        assert_that!(parse_offset("-05")).is_equal_to(offset(-5, 0));
+        assert_that!(parse_offset("UTC+3")).is_equal_to(offset(3, 0));
+        assert_that!(parse_offset("Z")).is_equal_to(offset(0, 0));
+        assert_that!(parse_offset("cest")).is_equal_to(offset(2, 0));
+        assert_that!(parse_offset("INFO")).is_none();
+        assert_that!(parse_offset("+02:75")).is_none();
+    }
+
+    #[test]
+    fn test_parse_zone_suffix() {
+        assert_that!(parse_zone_suffix("Z INFO")).is_equal_to(Some((offset(0, 0).unwrap(), 1)));
+        assert_that!(parse_zone_suffix(" +0200 INFO")).is_equal_to(Some((offset(2, 0).unwrap(), 6)));
+        assert_that!(parse_zone_suffix(" PST] INFO")).is_equal_to(Some((offset(-8, 0).unwrap(), 4)));
+        assert_that!(parse_zone_suffix(" INFO message")).is_none();
+        assert_that!(parse_zone_suffix(" 200 OK")).is_none();
+        assert_that!(parse_zone_suffix("")).is_none();
+    }
+
+    #[test]
+    fn test_convert() {
+        let dt = NaiveDateTime::parse_from_str("2024-01-31 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let zone: AssumedZone = "+02:00".parse().unwrap();
+        let zoned = zone.resolve(&dt).unwrap();
+        assert_that!(DisplayZone::Original.convert(zoned).naive_local()).is_equal_to(dt);
+        assert_that!(DisplayZone::Utc.convert(zoned).format("%H:%M%:z").to_string())
+            .is_equal_to(String::from("08:00+00:00"));
+        assert_that!("mars".parse::<AssumedZone>()).is_err();
+        assert_that!("utc".parse::<AssumedZone>().unwrap().to_string()).is_equal_to(String::from("UTC"));
+    }
+}
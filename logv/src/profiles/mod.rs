@@ -11,18 +11,21 @@ pub const DEFAULT_PROFILE_NAME: &str = "default";
 
 lazy_static! {
     pub static ref PROFILES: Vec<Profile> = load_builtin_profiles();
-    pub static ref OS_PROFILE: Profile = {
-        let default_profile = PROFILES.iter()
-            .find(|p| p.name() == DEFAULT_PROFILE_NAME)
-            .unwrap();
-        let os_profile = PROFILES.iter()
-            .find(|p| p.name() == std::env::consts::OS);
-        if let Some(os_profile) = os_profile {
-            default_profile.combine(os_profile)
-        } else {
-            default_profile.clone()
-        }
-    };
+    pub static ref OS_PROFILE: Profile = find_profile(std::env::consts::OS)
+        .unwrap_or_else(|| default_profile().clone());
+}
+
+/// Built-in profile `name` applied on top of the default one
+pub fn find_profile(name: &str) -> Option<Profile> {
+    PROFILES.iter()
+        .find(|p| p.name() == name)
+        .map(|profile| default_profile().combine(profile))
+}
+
+fn default_profile() -> &'static Profile {
+    PROFILES.iter()
+        .find(|p| p.name() == DEFAULT_PROFILE_NAME)
+        .unwrap()
 }
 
 fn load_builtin_profiles() -> Vec<Profile> {
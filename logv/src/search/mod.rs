@@ -2,5 +2,6 @@ pub mod searcher;
 pub mod navigable_searcher;
 mod searcher_impl;
 mod regex_searcher_impl;
+mod slice_searcher_impl;
 pub mod navigable_searcher_impl;
 mod search_utils;
\ No newline at end of file
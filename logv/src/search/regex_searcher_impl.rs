@@ -3,6 +3,8 @@ use crate::data_source::Direction;
 use crate::interval::Interval;
 use crate::search::search_utils::{calculate_offset_and_boundary, OffsetAndBoundary};
 use crate::search::searcher::{Occurrence, SearchError, SearchResult, Searcher};
+use crate::utils;
+use crate::utils::utf8::decode_substituting;
 use fluent_integer::Integer;
 use regex::Regex;
 use std::io::{BufReader, Read, Seek};
@@ -58,8 +60,8 @@ where
                 } else {
                     0
                 };
-                let content = String::from_utf8_lossy(&line);
-                if let Some(m) = self.regex.find_at(&content, at) {
+                let content = line_content(&line);
+                if let Some(m) = self.regex.find_at(&content, at.min(content.len())) {
                     // TODO: check that m.start() returns offset from string start
                     let occurrence = Occurrence::from_match(m) + start;
                     break Ok(occurrence)
@@ -85,8 +87,8 @@ where
                 } else {
                     line.len()
                 };
-                let content = String::from_utf8_lossy(&line);
-                if let Some(m) = self.regex.find_iter(&content[..at]).last() {
+                let content = line_content(&line);
+                if let Some(m) = self.regex.find_iter(&content[..at.min(content.len())]).last() {
                     let occurrence = Occurrence::from_match(m) + start;
                     break Ok(occurrence)
                 } else {
@@ -99,6 +101,13 @@ where
     }
 }
 
+/// Line content without the trailing line break, so that `\r` of CRLF line endings is not matched
+fn line_content(line: &[u8]) -> String {
+    let mut content = decode_substituting(line);
+    utils::trim_newline(&mut content);
+    content
+}
+
 // Tests are included according to http://xion.io/post/code/rust-unit-test-placement.html
 #[cfg(test)]
 #[path = "./regex_searcher_tests.rs"]
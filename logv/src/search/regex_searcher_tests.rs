@@ -90,6 +90,24 @@ fn test_regex_searcher_backward_not_found() {
     )
 }
 
+#[test]
+fn test_regex_searcher_crlf() {
+    test_regex(
+        "foo\r\nbar\r\n",
+        r"o$",
+        Direction::Forward,
+        Interval::all(),
+        Some(Occurrence::new(2, 3))
+    );
+    test_regex(
+        "foo\r\nbar\r\n",
+        r"r$",
+        Direction::Backward,
+        Interval::all(),
+        Some(Occurrence::new(7, 8))
+    )
+}
+
 fn test_regex(source: &str, pattern: &str, direction: Direction, range: Interval<Integer>, expected: Option<Occurrence>) {
     let regex = Regex::new(pattern).expect("Failed to parse regex");
     let mut searcher = RegexSearcherImpl::new(StrBackend::new(source).new_reader(), regex);
@@ -7,6 +7,8 @@ use crate::data_source::Direction;
 use crate::interval::Interval;
 use crate::search::regex_searcher_impl::RegexSearcherImpl;
 use crate::search::searcher_impl::SearcherImpl;
+use crate::search::slice_searcher_impl::SliceSearcherImpl;
+use crate::data_source::reader_factory::ReaderFactory;
 
 // closed segment
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
@@ -37,6 +39,37 @@ pub fn create_searcher<R: Read + Seek + 'static>(reader: BufReader<R>, pattern:
     }
 }
 
+/// Creates a searcher over a new reader of `reader_factory`. Content mapped into memory is
+/// searched for substring directly.
+pub fn create_searcher_for(reader_factory: &dyn ReaderFactory, pattern: String, is_regex: bool) -> std::io::Result<Box<dyn Searcher>> {
+    match reader_factory.mapped() {
+        Some(mapped) if !is_regex => Ok(Box::new(SliceSearcherImpl::new(mapped, pattern))),
+        _ => Ok(create_searcher(BufReader::new(reader_factory.new_reader()?), pattern, is_regex)),
+    }
+}
+
+/// Creates a searcher of a sequence of bytes over a new reader of `reader_factory`
+pub fn create_byte_searcher_for(reader_factory: &dyn ReaderFactory, pattern: Vec<u8>) -> std::io::Result<Box<dyn Searcher>> {
+    match reader_factory.mapped() {
+        Some(mapped) => Ok(Box::new(SliceSearcherImpl::new(mapped, pattern))),
+        None => Ok(Box::new(SearcherImpl::new(BufReader::new(reader_factory.new_reader()?), pattern))),
+    }
+}
+
+/// Parses bytes written in hex, e.g. `DE AD be ef` or `0xdeadbeef`. Whitespace is ignored.
+pub fn parse_hex_bytes(pattern: &str) -> Option<Vec<u8>> {
+    let digits: String = pattern.split_whitespace()
+        .map(|s| s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s))
+        .collect();
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.is_ascii() {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
 impl Occurrence {
     pub fn new<I, J>(start: I, end: J) -> Self
         where I: Into<Integer>, J: Into<Integer> {
@@ -13,17 +13,17 @@ pub struct SearcherImpl<R>
     where R: Read + Seek
 {
     f: BufReader<R>,
-    pattern: String,
+    pattern: Vec<u8>,
     buffer: LinkedList<u8>,
 }
 
 impl<R> SearcherImpl<R>
     where R: Read + Seek
 {
-    pub fn new(reader: BufReader<R>, pattern: String) -> SearcherImpl<R> {
+    pub fn new<P: Into<Vec<u8>>>(reader: BufReader<R>, pattern: P) -> SearcherImpl<R> {
         SearcherImpl {
             f: reader,
-            pattern,
+            pattern: pattern.into(),
             buffer: LinkedList::default(),
         }
     }
@@ -64,7 +64,7 @@ impl<R> SearcherImpl<R> where R: Read + Seek {
 
     fn fill_buffer(&mut self) -> Result<(), SearchError> {
         let m = self.buffer.len();
-        let n = self.pattern.as_bytes().len();
+        let n = self.pattern.as_slice().len();
         let delta = n.saturating_sub(m) as i128;
         if delta > 0 {
             self.f.read_fluently(delta, |chunk| {
@@ -78,7 +78,7 @@ impl<R> SearcherImpl<R> where R: Read + Seek {
     }
 
     fn compare(&self) -> bool {
-        let pattern = self.pattern.as_bytes();
+        let pattern = self.pattern.as_slice();
         self.buffer.len() == pattern.len() &&
             self.buffer.iter()
                 .zip(pattern.iter())
@@ -87,7 +87,7 @@ impl<R> SearcherImpl<R> where R: Read + Seek {
 
     fn scan_backward(&mut self, offset_boundary: Option<Integer>) -> SearchResult {
         let mut offset: Integer = self.f.stream_position().map_err(IO)?.into();
-        let pattern_len = self.pattern.as_bytes().len();
+        let pattern_len = self.pattern.as_slice().len();
         self.buffer.clear();
         loop {
             self.fill_buffer_backward()?;
@@ -106,7 +106,7 @@ impl<R> SearcherImpl<R> where R: Read + Seek {
 
     fn fill_buffer_backward(&mut self) -> Result<(), SearchError> {
         let m = self.buffer.len();
-        let n = self.pattern.as_bytes().len();
+        let n = self.pattern.as_slice().len();
         if m < n {
             self.f.read_fluently(-((n - m) as i64), |chunk| {
                 for ch in chunk {
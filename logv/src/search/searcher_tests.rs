@@ -1,5 +1,5 @@
 use crate::data_source::{Direction, LineSourceBackend, StrBackend};
-use crate::search::searcher::{Occurrence, Searcher};
+use crate::search::searcher::{parse_hex_bytes, Occurrence, Searcher};
 use spectral::prelude::*;
 use crate::interval::Interval;
 use crate::search::searcher_impl::SearcherImpl;
@@ -93,4 +93,13 @@ fn test_search(src: &'static str, pattern: &'static str, direction: Direction, n
                 .is_err();
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_parse_hex_bytes() {
+    assert_that!(parse_hex_bytes("DE AD be ef")).is_some().is_equal_to(vec![0xde, 0xad, 0xbe, 0xef]);
+    assert_that!(parse_hex_bytes("0x0a0D")).is_some().is_equal_to(vec![0x0a, 0x0d]);
+    assert_that!(parse_hex_bytes("abc")).is_none();
+    assert_that!(parse_hex_bytes("zz")).is_none();
+    assert_that!(parse_hex_bytes("  ")).is_none();
+}
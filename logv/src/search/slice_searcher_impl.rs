@@ -0,0 +1,66 @@
+use crate::data_source::mmap_backend::MappedFile;
+use crate::data_source::Direction;
+use crate::interval::Interval;
+use crate::search::search_utils::{calculate_offset_and_boundary, OffsetAndBoundary};
+use crate::search::searcher::{Occurrence, SearchError, SearchResult, Searcher};
+use fluent_integer::Integer;
+use memchr::memmem::{Finder, FinderRev};
+use std::io::{BufReader, Cursor};
+
+/// Substring searcher over content mapped into memory
+pub struct SliceSearcherImpl {
+    reader: BufReader<Cursor<MappedFile>>,
+    finder: Finder<'static>,
+    finder_rev: FinderRev<'static>,
+}
+
+impl SliceSearcherImpl {
+    pub fn new<P: AsRef<[u8]>>(mapped: MappedFile, pattern: P) -> SliceSearcherImpl {
+        SliceSearcherImpl {
+            reader: BufReader::new(Cursor::new(mapped)),
+            finder: Finder::new(pattern.as_ref()).into_owned(),
+            finder_rev: FinderRev::new(pattern.as_ref()).into_owned(),
+        }
+    }
+}
+
+impl Searcher for SliceSearcherImpl {
+    fn search(&mut self, direction: Direction, range: Interval<Integer>) -> SearchResult {
+        let OffsetAndBoundary {
+            offset,
+            offset_boundary
+        } = calculate_offset_and_boundary(&mut self.reader, direction, range)?;
+        let bytes = self.reader.get_ref().get_ref().as_ref();
+        let len = bytes.len();
+        let pattern_len = self.finder.needle().len();
+
+        match direction {
+            Direction::Forward => {
+                // occurrence must start not later than boundary
+                let start = offset.as_usize().min(len);
+                let end = offset_boundary
+                    .map(|b| (b + pattern_len).max(start.into()).as_usize().min(len))
+                    .unwrap_or(len);
+                self.finder.find(&bytes[start..end])
+                    .map(|p| Occurrence::with_len(start + p, pattern_len))
+                    .ok_or(SearchError::NotFound)
+            },
+            Direction::Backward => {
+                // occurrence must end not later than offset
+                let end = offset.as_usize().min(len);
+                let start = offset_boundary.unwrap_or_default()
+                    .max(0.into())
+                    .as_usize()
+                    .min(end);
+                self.finder_rev.rfind(&bytes[start..end])
+                    .map(|p| Occurrence::with_len(start + p, pattern_len))
+                    .ok_or(SearchError::NotFound)
+            },
+        }
+    }
+}
+
+// Tests are included according to http://xion.io/post/code/rust-unit-test-placement.html
+#[cfg(test)]
+#[path = "./slice_searcher_tests.rs"]
+mod slice_searcher_tests;
@@ -0,0 +1,69 @@
+use std::io::{BufReader, Cursor};
+
+use spectral::prelude::*;
+
+use crate::data_source::mmap_backend::MappedFile;
+use crate::data_source::Direction;
+use crate::interval::Interval;
+use crate::search::searcher::{Occurrence, Searcher};
+use crate::search::searcher_impl::SearcherImpl;
+use crate::search::slice_searcher_impl::SliceSearcherImpl;
+use crate::test_extensions::TempFile;
+
+const SRC: &str = "foo bar baz foo foo bar baz fffoo";
+
+fn range(direction: Direction, offset: usize) -> Interval<fluent_integer::Integer> {
+    match direction {
+        Direction::Forward => Interval::builder().left_bound_inclusive(offset.into()).right_unbounded().build(),
+        Direction::Backward => Interval::builder().left_unbounded().right_bound_inclusive(offset.into()).build(),
+    }
+}
+
+/// Compares results of [SliceSearcherImpl] with [SearcherImpl] reading by chunks of
+/// `capacity` bytes, from every offset of `src`
+fn assert_same_as_searcher_impl(name: &str, src: &str, pattern: &str, capacity: usize) {
+    let file = TempFile::new(&format!("slice-searcher-{}", name), src);
+    let mut slice_searcher = SliceSearcherImpl::new(MappedFile::open(file.path()).unwrap(), pattern.to_string());
+    let reader = BufReader::with_capacity(capacity, Cursor::new(src.as_bytes().to_vec()));
+    let mut searcher = SearcherImpl::new(reader, pattern.to_string());
+    for direction in [Direction::Forward, Direction::Backward] {
+        for offset in 0..=src.len() {
+            let expected = searcher.search(direction, range(direction, offset)).ok();
+            let actual = slice_searcher.search(direction, range(direction, offset)).ok();
+            let descr = format!("{:?} from {}", direction, offset);
+            asserting!(&descr)
+                .that(&actual)
+                .is_equal_to(expected);
+        }
+    }
+}
+
+#[test]
+fn test_same_as_searcher_impl() {
+    assert_same_as_searcher_impl("same", SRC, "foo", 8 * 1024);
+}
+
+#[test]
+fn test_same_as_searcher_impl_across_chunks() {
+    // reading from 0, "foo" at 12 crosses the boundary of chunks of 7 bytes
+    assert_same_as_searcher_impl("chunks", SRC, "foo", 7);
+    assert_same_as_searcher_impl("chunks-letter", SRC, "z", 7);
+}
+
+#[test]
+fn test_search_at_bounds() {
+    let file = TempFile::new("slice-searcher-bounds", SRC);
+    let mut searcher = SliceSearcherImpl::new(MappedFile::open(file.path()).unwrap(), "foo".to_string());
+    // at offset 0
+    assert_that!(searcher.search(Direction::Forward, range(Direction::Forward, 0)).ok())
+        .is_equal_to(Some(Occurrence::new(0, 3)));
+    assert_that!(searcher.search(Direction::Backward, range(Direction::Backward, 3)).ok())
+        .is_equal_to(Some(Occurrence::new(0, 3)));
+    assert_that!(searcher.search(Direction::Backward, range(Direction::Backward, 2)).ok()).is_none();
+    // at EOF
+    assert_that!(searcher.search(Direction::Backward, range(Direction::Backward, SRC.len())).ok())
+        .is_equal_to(Some(Occurrence::new(30, 33)));
+    assert_that!(searcher.search(Direction::Forward, range(Direction::Forward, 30)).ok())
+        .is_equal_to(Some(Occurrence::new(30, 33)));
+    assert_that!(searcher.search(Direction::Forward, range(Direction::Forward, 31)).ok()).is_none();
+}
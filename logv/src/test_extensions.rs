@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use spectral::{assert_that, Spec};
 use spectral::prelude::*;
 
@@ -20,4 +22,58 @@ impl <'s, T> UniqueElementAssertions<'s, T> for Spec<'s, Vec<T>> {
         assert_that(self.subject).has_length(1);
         assert_that(self.subject.first().unwrap())
     }
-}
\ No newline at end of file
+}
+
+/// File in the temporary directory, removed when dropped. `name` has to be unique among tests,
+/// as they run concurrently.
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    pub fn new(name: &str, content: impl AsRef<[u8]>) -> Self {
+        let path = temp_path(name);
+        std::fs::write(&path, content).unwrap();
+        TempFile { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Directory in the temporary directory, removed with its content when dropped
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = temp_path(name);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    /// Creates file `name` in the directory
+    pub fn file(&self, name: &str, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("logv-{}-{}", name, std::process::id()))
+}
@@ -8,9 +8,10 @@ use crate::app_theme::app_theme::AppThemeKey;
 use crate::highlight::highlighter_registry::cursive_highlighters;
 use crate::model::model::RootModel;
 use crate::shared::Shared;
-use crate::ui::line_drawer::LineDrawer;
+use crate::ui::hex_drawer::HexDrawer;
+use crate::ui::line_drawer::{text_width, LineDrawer};
 use crate::ui::ui_elements::UIElementName;
-use crate::utils::{NumberOfDecimalDigits, stat, stat_l};
+use crate::utils::{stat, stat_l};
 
 const METRIC_DRAW: &str = "draw";
 const METRIC_ACTION: &str = "action";
@@ -24,7 +25,7 @@ pub fn build_canvas(model: Shared<RootModel>) -> NamedView<Canvas<Shared<RootMod
             let mut state = state.get_mut_ref();
 
             let app_theme = &state.app_theme;
-            let highlighters = cursive_highlighters(app_theme);
+            let highlighters = cursive_highlighters(app_theme, state.get_highlight_rules());
             let regular_style = app_theme[AppThemeKey::Regular];
             let cursor_style = app_theme[AppThemeKey::Cursor];
             let selection_style = app_theme[AppThemeKey::Selection];
@@ -33,23 +34,29 @@ pub fn build_canvas(model: Shared<RootModel>) -> NamedView<Canvas<Shared<RootMod
             state.set_viewport_height(printer.size.y); // fetches data
 
             let mut max_line_number = None;
-            let mut effective_viewport_width = printer.size.x;
             if state.is_show_line_numbers() {
                 if let Some(data) = state.data() {
                     max_line_number = data.lines.iter()
                         .filter_map(|line| line.line_no.as_ref().ok())
                         .map(|i| *i + 1)
                         .last();
-                    if let Some(max_line_number) = max_line_number {
-                        let max_line_number_len = max_line_number.number_of_decimal_digits();
-                        effective_viewport_width -= max_line_number_len;
-                    }
                 }
             }
+            let effective_viewport_width = text_width(printer.size.x, max_line_number, state.get_time_deltas().is_some());
             state.set_viewport_width(effective_viewport_width);
 
-            if let Some(data) = state.data() {
-                let line_drawer = LineDrawer::new()
+            if let Some(hex_view) = state.get_hex_view() {
+                let hex_drawer = HexDrawer::new(&state);
+                printer.clear();
+                hex_view.rows()
+                    .take(printer.size.y)
+                    .map(|(offset, bytes)| hex_drawer.draw(offset, bytes))
+                    .enumerate()
+                    .for_each(|(i, ss)|
+                        printer.print_styled((0, i), SpannedString::from(&ss))
+                    );
+            } else if let Some(data) = state.data() {
+                let mut line_drawer = LineDrawer::new()
                     .with_state(&state)
                     .with_highlighters(&highlighters)
                     .with_width(printer.size.x)
@@ -59,9 +66,16 @@ pub fn build_canvas(model: Shared<RootModel>) -> NamedView<Canvas<Shared<RootMod
                     .with_line_number_style(line_number_style)
                     .with_show_line_numbers(state.is_show_line_numbers())
                     .with_max_line_number(max_line_number.unwrap_or(0));
-                data.lines.iter()
+                if let Some(time_deltas) = state.get_time_deltas() {
+                    line_drawer = line_drawer.with_time_deltas(time_deltas);
+                }
+                let file_boundaries = state.get_file_boundaries();
+                if !file_boundaries.is_empty() {
+                    line_drawer = line_drawer.with_file_boundaries(&file_boundaries);
+                }
+                state.get_visible_rows().into_iter()
                     .take(printer.size.y)
-                    .map(|line| line_drawer.draw(line))
+                    .map(|(i, row)| line_drawer.draw(&data.lines[i], row))
                     .enumerate()
                     .for_each(|(i, ss)|
                         printer.print_styled((0, i), SpannedStr::from(&ss))
@@ -0,0 +1,104 @@
+use std::cmp::Ordering;
+
+use cursive::Cursive;
+use cursive::traits::{Nameable, Resizable};
+use cursive::views::{Dialog, LinearLayout, TextView};
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::model::clustering_model::{ClusteringModel, ClusteringModelEvent};
+use crate::model::log_template::{TemplateStats, MAX_TEMPLATES};
+use crate::ui::ui_utils::PopLayerSafely;
+use crate::ui::view_with_callback::{ViewUpdateCallback, ViewWithCallback};
+use crate::ui::with_root_model::WithRootModel;
+
+const CLUSTERING_DIALOG: &str = "ClusteringDialog";
+const CLUSTERING_TABLE: &str = "ClusteringTable";
+const CLUSTERING_STATUS: &str = "ClusteringStatus";
+
+pub fn handle_clustering_model_event(model: &ClusteringModel, evt: ClusteringModelEvent) -> ViewUpdateCallback {
+    match evt {
+        ClusteringModelEvent::Open(true) => build_clustering_dialog(model).into(),
+        ClusteringModelEvent::Open(false) => Box::new(|app: &mut Cursive| app.pop_layer_safely(CLUSTERING_DIALOG)),
+        ClusteringModelEvent::Updated => {
+            let items = model.get_templates().cloned().unwrap_or_default();
+            let status = status_text(model);
+            Box::new(move |app: &mut Cursive| {
+                app.call_on_name(CLUSTERING_STATUS, |v: &mut TextView| v.set_content(status));
+                app.call_on_name(CLUSTERING_TABLE, |v: &mut TemplatesTable| v.set_items(items));
+            })
+        }
+    }
+}
+
+fn build_clustering_dialog(model: &ClusteringModel) -> ViewWithCallback {
+    let mut table = TemplatesTable::new()
+        .column(Column::Count, "#", |c| c.ordering(Ordering::Greater).width(10))
+        .column(Column::Template, "Template", |c| c.ordering(Ordering::Less))
+        .default_column(Column::Count);
+    table.set_items(model.get_templates().cloned().unwrap_or_default());
+    table.set_on_submit(|app: &mut Cursive, _row: usize, index: usize| {
+        let pattern = app.call_on_name(CLUSTERING_TABLE, |v: &mut TemplatesTable| {
+            v.borrow_item(index).map(|item| item.template.to_regex())
+        }).flatten();
+        let Some(pattern) = pattern else { return; };
+        let root_model = &mut *app.get_root_model();
+        root_model.get_clustering_model().set_open(false);
+        if let Err(err) = root_model.filter_by_regex(&pattern) {
+            root_model.set_error(Box::new(err));
+        }
+    });
+
+    let mut layout = LinearLayout::vertical();
+    layout.add_child(TextView::new(status_text(model)).with_name(CLUSTERING_STATUS));
+    layout.add_child(table.with_name(CLUSTERING_TABLE).min_size((100, 20)));
+
+    let dialog = Dialog::new()
+        .title("Message templates")
+        .content(layout)
+        .button("Close", |app| {
+            let root_model = app.get_root_model();
+            root_model.get_clustering_model().set_open(false);
+        })
+        .with_name(CLUSTERING_DIALOG);
+    ViewWithCallback::with_dummy_callback(dialog)
+}
+
+fn status_text(model: &ClusteringModel) -> String {
+    match model.get_templates() {
+        Some(templates) if model.get_unclustered() > 0 => format!(
+            "{} templates found, {} lines not clustered: the limit of {} templates is reached. \
+            Press Enter to filter lines by template",
+            templates.len(),
+            model.get_unclustered(),
+            MAX_TEMPLATES,
+        ),
+        Some(templates) => format!("{} templates found. Press Enter to filter lines by template", templates.len()),
+        None => String::from("Analyzing..."),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum Column {
+    Count,
+    Template,
+}
+
+impl TableViewItem<Column> for TemplateStats {
+    fn to_column(&self, column: Column) -> String {
+        match column {
+            Column::Count => self.count.to_string(),
+            Column::Template => self.template.to_string(),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: Column) -> Ordering
+        where
+            Self: Sized {
+        match column {
+            Column::Count => self.count.cmp(&other.count),
+            Column::Template => self.template.to_string().cmp(&other.template.to_string()),
+        }
+    }
+}
+
+type TemplatesTable = TableView<TemplateStats, Column>;
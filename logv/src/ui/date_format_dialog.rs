@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+
+use cursive::Cursive;
+use cursive::traits::{Nameable, Resizable};
+use cursive::views::{Dialog, EditView, LinearLayout, TextView};
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::model::date_format_model::{DateFormatModel, DateFormatModelEvent};
+use crate::model::guess_date_format::{find_or_create_format, FormatRating};
+use crate::model::model::RootModel;
+use crate::ui::ui_utils::PopLayerSafely;
+use crate::ui::view_with_callback::{ViewUpdateCallback, ViewWithCallback};
+use crate::ui::with_root_model::WithRootModel;
+
+const DATE_FORMAT_DIALOG: &str = "DateFormatDialog";
+const DATE_FORMAT_TABLE: &str = "DateFormatTable";
+const DATE_FORMAT_STATUS: &str = "DateFormatStatus";
+const CUSTOM_FORMAT_FIELD: &str = "CustomFormatField";
+
+pub fn handle_date_format_model_event(root_model: &RootModel, evt: DateFormatModelEvent) -> ViewUpdateCallback {
+    // sub-model is borrowed after reading the pinned format, which borrows it too
+    let current = current_format_text(root_model);
+    let model = root_model.get_date_format_model();
+    match evt {
+        DateFormatModelEvent::Open(true) => build_date_format_dialog(&current, &model).into(),
+        DateFormatModelEvent::Open(false) => Box::new(|app: &mut Cursive| app.pop_layer_safely(DATE_FORMAT_DIALOG)),
+        DateFormatModelEvent::Updated => {
+            let items = model.get_ratings().cloned().unwrap_or_default();
+            let status = status_text(&current, &model);
+            Box::new(move |app: &mut Cursive| {
+                app.call_on_name(DATE_FORMAT_STATUS, |v: &mut TextView| v.set_content(status));
+                app.call_on_name(DATE_FORMAT_TABLE, |v: &mut RatingsTable| v.set_items(items));
+            })
+        }
+    }
+}
+
+fn build_date_format_dialog(current: &str, model: &DateFormatModel) -> ViewWithCallback {
+    let mut table = RatingsTable::new()
+        .column(Column::Format, "Format", |c| c.ordering(Ordering::Less).width(30))
+        .column(Column::Rating, "Rating", |c| c.ordering(Ordering::Greater).width(10))
+        .column(Column::Lines, "Lines", |c| c.ordering(Ordering::Greater).width(10))
+        .column(Column::Example, "Example", |c| c.ordering(Ordering::Less))
+        .default_column(Column::Rating);
+    table.set_items(model.get_ratings().cloned().unwrap_or_default());
+    table.set_on_submit(|app: &mut Cursive, _row: usize, index: usize| {
+        let date_format = app.call_on_name(DATE_FORMAT_TABLE, |v: &mut RatingsTable| {
+            v.borrow_item(index).map(|item| item.format)
+        }).flatten();
+        let Some(date_format) = date_format else { return; };
+        let root_model = &mut *app.get_root_model();
+        root_model.get_date_format_model().set_open(false);
+        root_model.pin_date_format(Some(date_format));
+    });
+
+    let pin_custom = |app: &mut Cursive, definition: &str| {
+        let root_model = &mut *app.get_root_model();
+        match find_or_create_format(definition) {
+            Ok(date_format) => {
+                root_model.get_date_format_model().set_open(false);
+                root_model.pin_date_format(Some(date_format));
+            }
+            Err(err) => root_model.set_error(Box::new(err)),
+        }
+    };
+
+    let mut layout = LinearLayout::vertical();
+    layout.add_child(TextView::new(status_text(current, model)).with_name(DATE_FORMAT_STATUS));
+    layout.add_child(table.with_name(DATE_FORMAT_TABLE).min_size((100, 20)));
+    layout.add_child(TextView::new("Custom format (chrono syntax, e.g. %d/%m/%Y %H:%M:%S):"));
+    layout.add_child(EditView::new()
+        .on_submit(pin_custom)
+        .with_name(CUSTOM_FORMAT_FIELD));
+
+    let dialog = Dialog::new()
+        .title("Date format")
+        .content(layout)
+        .button("Pin custom", move |app| {
+            let definition = app.find_name::<EditView>(CUSTOM_FORMAT_FIELD)
+                .expect("Element not found")
+                .get_content();
+            pin_custom(app, definition.as_str());
+        })
+        .button("Guess", |app| {
+            let root_model = &mut *app.get_root_model();
+            root_model.get_date_format_model().set_open(false);
+            root_model.pin_date_format(None);
+        })
+        .button("Close", |app| {
+            let root_model = app.get_root_model();
+            root_model.get_date_format_model().set_open(false);
+        })
+        .with_name(DATE_FORMAT_DIALOG);
+    ViewWithCallback::with_dummy_callback(dialog)
+}
+
+fn current_format_text(root_model: &RootModel) -> String {
+    match (root_model.get_pinned_date_format(), root_model.get_date_format()) {
+        (Some(pinned), _) => format!("Pinned: {}", pinned.get_definition()),
+        (None, Some(guessed)) => format!("Guessed: {}", guessed.get_definition()),
+        (None, None) => String::from("Date format is not recognized"),
+    }
+}
+
+fn status_text(current: &str, model: &DateFormatModel) -> String {
+    match model.get_ratings() {
+        Some(ratings) => format!("{}\n{} formats match sampled lines. Press Enter to pin the format for this file", current, ratings.len()),
+        None => format!("{}\nAnalyzing...", current),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum Column {
+    Format,
+    Rating,
+    Lines,
+    Example,
+}
+
+impl TableViewItem<Column> for FormatRating {
+    fn to_column(&self, column: Column) -> String {
+        match column {
+            Column::Format => self.format.get_definition().to_string(),
+            Column::Rating => self.rating.to_string(),
+            Column::Lines => self.matched_lines.to_string(),
+            Column::Example => self.example.clone().unwrap_or_default(),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: Column) -> Ordering
+        where
+            Self: Sized {
+        match column {
+            Column::Format => self.format.get_definition().cmp(other.format.get_definition()),
+            Column::Rating => self.rating.cmp(&other.rating),
+            Column::Lines => self.matched_lines.cmp(&other.matched_lines),
+            Column::Example => self.example.cmp(&other.example),
+        }
+    }
+}
+
+type RatingsTable = TableView<FormatRating, Column>;
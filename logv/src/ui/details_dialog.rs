@@ -0,0 +1,68 @@
+use cursive::Cursive;
+use cursive::traits::{Nameable, Resizable, Scrollable};
+use cursive::utils::markup::StyledString;
+use cursive::views::{Dialog, TextView};
+
+use crate::app_theme::app_theme::{AppTheme, AppThemeKey};
+use crate::model::details_model::{DetailsModel, DetailsModelEvent};
+use crate::model::pretty_print::SyntaxKind;
+use crate::ui::ui_utils::PopLayerSafely;
+use crate::ui::view_with_callback::{ViewUpdateCallback, ViewWithCallback};
+use crate::ui::with_root_model::WithRootModel;
+
+const DETAILS_DIALOG: &str = "DetailsDialog";
+
+pub fn handle_details_model_event(model: &DetailsModel, app_theme: &AppTheme, evt: DetailsModelEvent) -> ViewUpdateCallback {
+    match evt {
+        DetailsModelEvent::Open(true) => build_details_dialog(model, app_theme).into(),
+        DetailsModelEvent::Open(false) => Box::new(|app: &mut Cursive| app.pop_layer_safely(DETAILS_DIALOG)),
+    }
+}
+
+fn build_details_dialog(model: &DetailsModel, app_theme: &AppTheme) -> ViewWithCallback {
+    let key_style = app_theme[AppThemeKey::LogfmtKey].get_style();
+    let width = model.get_fields().iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or_default();
+    let mut content = StyledString::new();
+    for (key, value) in model.get_fields() {
+        content.append_styled(format!("{:>width$}", key, width = width), key_style);
+        content.append_plain(": ");
+        // continuation lines of multi-line values are aligned with the first one
+        let indent = format!("\n{}", " ".repeat(width + 2));
+        content.append_plain(value.replace('\n', &indent));
+        content.append_plain("\n");
+    }
+    if !model.get_body().is_empty() {
+        if !model.get_fields().is_empty() {
+            content.append_plain("\n");
+        }
+        for span in model.get_body() {
+            match syntax_key(span.kind) {
+                Some(key) => content.append_styled(&span.text, app_theme[key].get_style()),
+                None => content.append_plain(&span.text),
+            }
+        }
+    }
+
+    let dialog = Dialog::new()
+        .title(model.get_title())
+        .content(TextView::new(content).scrollable().max_size((120, 30)))
+        .button("Close", |app| {
+            let root_model = app.get_root_model();
+            root_model.get_details_model().set_open(false);
+        })
+        .with_name(DETAILS_DIALOG);
+    ViewWithCallback::with_dummy_callback(dialog)
+}
+
+fn syntax_key(kind: SyntaxKind) -> Option<AppThemeKey> {
+    match kind {
+        SyntaxKind::Plain => None,
+        SyntaxKind::Key => Some(AppThemeKey::SyntaxKey),
+        SyntaxKind::String => Some(AppThemeKey::SyntaxString),
+        SyntaxKind::Literal => Some(AppThemeKey::SyntaxLiteral),
+        SyntaxKind::Tag => Some(AppThemeKey::SyntaxTag),
+    }
+}
@@ -0,0 +1,181 @@
+use std::cmp::Ordering;
+
+use cursive::Cursive;
+use cursive::traits::{Nameable, Resizable};
+use cursive::views::{Checkbox, Dialog, EditView, LinearLayout, TextView};
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::model::field_table_model::{FieldTableModel, FieldTableModelEvent, ParsedRow};
+use crate::ui::ui_utils::PopLayerSafely;
+use crate::ui::view_with_callback::{ViewUpdateCallback, ViewWithCallback};
+use crate::ui::with_root_model::WithRootModel;
+
+const FIELD_TABLE_DIALOG: &str = "FieldTableDialog";
+const FIELD_PANEL: &str = "FieldPanel";
+const FIELD_TABLE: &str = "FieldTable";
+const RULE_FIELD: &str = "RuleField";
+const COLUMN_WIDTH: usize = 20;
+const TABLE_SIZE: (usize, usize) = (120, 25);
+/// Position of the table in the field panel, after checkboxes of columns
+const TABLE_POSITION: usize = 1;
+
+pub fn handle_field_table_model_event(model: &FieldTableModel, evt: FieldTableModelEvent) -> ViewUpdateCallback {
+    match evt {
+        FieldTableModelEvent::Open(true) => build_field_table_dialog(model).into(),
+        FieldTableModelEvent::Open(false) => Box::new(|app: &mut Cursive| app.pop_layer_safely(FIELD_TABLE_DIALOG)),
+        FieldTableModelEvent::RuleUpdated => {
+            let callback = rebuild_field_panel(model);
+            Box::new(|app: &mut Cursive| {
+                app.call_on_name(FIELD_PANEL, move |panel: &mut LinearLayout| callback(panel));
+            })
+        },
+        FieldTableModelEvent::ColumnsUpdated => {
+            // columns of TableView are fixed, so the table is rebuilt
+            let mut table = build_table(model);
+            let visible_columns: Vec<usize> = model.get_rule()
+                .map(|rule| (0..rule.get_columns().len()).filter(|i| model.is_column_visible(*i)).collect())
+                .unwrap_or_default();
+            Box::new(move |app: &mut Cursive| {
+                // the new table keeps sort order and selection of the replaced one
+                let state = app.call_on_name(FIELD_TABLE, |table: &mut FieldTable| (table.order(), table.item()));
+                if let Some((order, item)) = state {
+                    if let Some((column, ordering)) = order.filter(|(column, _)| visible_columns.contains(column)) {
+                        table.sort_by(column, ordering);
+                    }
+                    if let Some(item) = item {
+                        table.set_selected_item(item);
+                    }
+                }
+                app.call_on_name(FIELD_PANEL, move |panel: &mut LinearLayout| {
+                    if panel.remove_child(TABLE_POSITION).is_some() {
+                        panel.insert_child(TABLE_POSITION, table.with_name(FIELD_TABLE).min_size(TABLE_SIZE));
+                    }
+                });
+            })
+        },
+        FieldTableModelEvent::RowsUpdated => {
+            let rows = model.get_rows().clone();
+            let (has_prev_page, has_next_page) = (model.has_prev_page(), model.has_next_page());
+            Box::new(move |app: &mut Cursive| {
+                app.call_on_name(FIELD_TABLE, |table: &mut FieldTable| table.set_items(rows));
+                app.call_on_name(FIELD_TABLE_DIALOG, |dialog: &mut Dialog|
+                    update_page_buttons(dialog, has_prev_page, has_next_page)
+                );
+            })
+        },
+    }
+}
+
+fn build_field_table_dialog(model: &FieldTableModel) -> ViewWithCallback {
+    let apply_rule = |app: &mut Cursive, rule: &str| {
+        let root_model = &mut *app.get_root_model();
+        if let Err(err) = root_model.set_parse_rule(rule) {
+            root_model.set_error(Box::new(err));
+        }
+    };
+
+    let mut layout = LinearLayout::vertical();
+    layout.add_child(TextView::new("Parse rule (regular expression with named groups):"));
+    layout.add_child(EditView::new()
+        .content(model.get_rule_text())
+        .on_submit(apply_rule)
+        .with_name(RULE_FIELD));
+    let mut field_panel = LinearLayout::vertical();
+    rebuild_field_panel(model)(&mut field_panel);
+    layout.add_child(field_panel.with_name(FIELD_PANEL));
+
+    let mut dialog = Dialog::new()
+        .title("Fields")
+        .content(layout)
+        .button("Apply", move |app| {
+            let rule = app.find_name::<EditView>(RULE_FIELD)
+                .expect("Element not found")
+                .get_content();
+            apply_rule(app, rule.as_str());
+        })
+        .button("Prev page", |app| app.get_root_model().field_table_prev_page())
+        .button("Next page", |app| app.get_root_model().field_table_next_page())
+        .button("Close", |app| {
+            let root_model = app.get_root_model();
+            root_model.get_field_table_model().set_open(false);
+        });
+    update_page_buttons(&mut dialog, model.has_prev_page(), model.has_next_page());
+    ViewWithCallback::with_dummy_callback(dialog.with_name(FIELD_TABLE_DIALOG))
+}
+
+/// Fills the panel with checkboxes of columns and the table, if the rule is set
+fn rebuild_field_panel(model: &FieldTableModel) -> Box<dyn FnOnce(&mut LinearLayout)> {
+    let columns: Vec<(String, bool)> = model.get_rule()
+        .map(|rule| rule.get_columns().iter().enumerate()
+            .map(|(i, name)| (name.clone(), model.is_column_visible(i)))
+            .collect())
+        .unwrap_or_default();
+    let table = model.get_rule().map(|_| build_table(model));
+    Box::new(move |panel| {
+        panel.clear();
+        let Some(table) = table else { return; };
+        let mut columns_panel = LinearLayout::horizontal();
+        columns_panel.add_child(TextView::new("Columns: "));
+        for (i, (name, is_visible)) in columns.into_iter().enumerate() {
+            columns_panel.add_child(Checkbox::new()
+                .with_checked(is_visible)
+                .on_change(move |app, is_checked| {
+                    let root_model = app.get_root_model();
+                    root_model.get_field_table_model().set_column_visible(i, is_checked);
+                }));
+            columns_panel.add_child(TextView::new(format!("{} ", name)));
+        }
+        panel.add_child(columns_panel);
+        panel.add_child(table.with_name(FIELD_TABLE).min_size(TABLE_SIZE));
+        panel.add_child(TextView::new("Sorting by a column applies to the current page only"));
+    })
+}
+
+fn build_table(model: &FieldTableModel) -> FieldTable {
+    let mut table = FieldTable::new();
+    if let Some(rule) = model.get_rule() {
+        for (i, name) in rule.get_columns().iter().enumerate() {
+            if model.is_column_visible(i) {
+                table.add_column(i, name.as_str(), |c| c.width(COLUMN_WIDTH));
+            }
+        }
+    }
+    table.set_items(model.get_rows().clone());
+    table.set_on_submit(|app: &mut Cursive, _row: usize, index: usize| {
+        let offset = app.call_on_name(FIELD_TABLE, |v: &mut FieldTable| {
+            v.borrow_item(index).map(|item| item.offset)
+        }).flatten();
+        let Some(offset) = offset else { return; };
+        let root_model = &mut *app.get_root_model();
+        root_model.get_field_table_model().set_open(false);
+        root_model.move_cursor_to_offset(offset, false);
+    });
+    table
+}
+
+/// Enables "Prev page" and "Next page" buttons, which follow "Apply"
+fn update_page_buttons(dialog: &mut Dialog, has_prev_page: bool, has_next_page: bool) {
+    for (button, is_enabled) in dialog.buttons_mut().skip(1).zip([has_prev_page, has_next_page]) {
+        button.set_enabled(is_enabled);
+    }
+}
+
+impl TableViewItem<usize> for ParsedRow {
+    fn to_column(&self, column: usize) -> String {
+        self.values.get(column).cloned().unwrap_or_default()
+    }
+
+    fn cmp(&self, other: &Self, column: usize) -> Ordering
+        where
+            Self: Sized {
+        let a = self.values.get(column);
+        let b = other.values.get(column);
+        let as_number = |v: Option<&String>| v.and_then(|s| s.parse::<f64>().ok());
+        match (as_number(a), as_number(b)) {
+            (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+            _ => a.cmp(&b),
+        }
+    }
+}
+
+type FieldTable = TableView<ParsedRow, usize>;
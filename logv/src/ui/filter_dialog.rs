@@ -1,6 +1,6 @@
 use cursive::Cursive;
 use cursive::traits::{Nameable, Resizable};
-use cursive::views::{Dialog, EditView, LinearLayout, TextView};
+use cursive::views::{Checkbox, Dialog, EditView, LinearLayout, TextView};
 use crate::model::filter_model::{FilterDialogModel, FilterDialogModelEvent};
 use crate::ui::ui_utils::PopLayerSafely;
 use crate::ui::view_with_callback::{ViewUpdateCallback, ViewWithCallback};
@@ -42,6 +42,15 @@ pub fn build_filter_dialog(model: &FilterDialogModel) -> ViewWithCallback {
         .on_submit(do_filter)
         .with_name(NEIGHBOURHOOD_FIELD);
     layout.add_child(neighbourhood_field);
+    let mut settings_panel = LinearLayout::horizontal();
+    settings_panel.add_child(Checkbox::new()
+        .with_checked(model.is_field_filter())
+        .on_change(|app, is_checked| {
+            let root_model = &mut *app.get_root_model();
+            root_model.get_filter_dialog_model().set_field_filter(is_checked);
+        }));
+    settings_panel.add_child(TextView::new("logfmt field (key=value or key~regex)"));
+    layout.add_child(settings_panel);
 
     let dialog = Dialog::new()
         .title("Filter")
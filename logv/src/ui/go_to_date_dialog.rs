@@ -3,6 +3,7 @@ use anyhow::anyhow;
 use cursive::{Cursive, View};
 use cursive::view::Nameable;
 use cursive::views::{Dialog, EditView, LinearLayout, TextView};
+use crate::model::date_input::DATE_INPUT_HINTS;
 use crate::model::model::RootModel;
 use crate::ui::ui_elements::UIElementName;
 use crate::ui::with_root_model::WithRootModel;
@@ -11,7 +12,7 @@ pub fn build_go_to_date_dialog(root_model: &mut RootModel) -> Box<dyn View> {
     let go_to_date_model = &mut *root_model.get_go_to_date_model();
 
     let mut layout = LinearLayout::vertical();
-    layout.add_child(TextView::new("Enter date (dd-MMM-yyyy HH:mm:ss):"));
+    layout.add_child(TextView::new("Enter date:"));
     layout.add_child(EditView::new()
         .content(go_to_date_model.get_value())
         .on_edit(|app, value, cursor| {
@@ -50,6 +51,10 @@ pub fn build_go_to_date_dialog(root_model: &mut RootModel) -> Box<dyn View> {
         .on_submit(|app, _value| submit(app))
         .with_name(UIElementName::GoToDateValue.to_string())
     );
+    layout.add_child(TextView::new(""));
+    for (example, description) in DATE_INPUT_HINTS {
+        layout.add_child(TextView::new(format!("{:<20} {}", example, description)));
+    }
 
     let d = Dialog::new()
         .title("Go to")
@@ -69,12 +74,11 @@ fn submit(app: &mut Cursive) {
 }
 
 fn try_submit(app: &mut Cursive) -> anyhow::Result<()> {
-    let file_name = {
+    let source = {
         let root_model = &mut *app.get_root_model();
         root_model
-            .file_name()
+            .get_line_source_factory()
             .ok_or(anyhow!("File is not set"))?
-            .to_string()
     };
     let content = {
         let value_field = app
@@ -86,9 +90,11 @@ fn try_submit(app: &mut Cursive) -> anyhow::Result<()> {
     let known_date_format = root_model.get_date_format()
         .ok_or(anyhow!("Date format is not recognized for file"))?;
     let guess_ctx = root_model.get_date_guess_context();
+    let reference = root_model.get_cursor_date();
+    let time_index = root_model.get_time_index();
     let go_to_date_model = &mut *root_model.get_go_to_date_model();
     go_to_date_model.set_value(&content);
-    go_to_date_model.submit(&file_name, known_date_format, guess_ctx)
+    go_to_date_model.submit(source, time_index, known_date_format, guess_ctx, reference)
 }
 
 fn cancel(app: &mut Cursive) {
@@ -15,7 +15,7 @@ pub fn build_go_to_dialog(root_model: &mut RootModel) -> Box<dyn View> {
 
     let mut layout = LinearLayout::vertical();
 
-    layout.add_child(TextView::new("Enter line number:"));
+    layout.add_child(TextView::new("Enter line number, byte offset (@123456, 0x1E240) or percentage (75%):"));
 
     let value_input = EditView::new()
         .content(go_to_model.get_value())
@@ -49,16 +49,16 @@ fn submit(app: &mut Cursive) {
 }
 
 fn try_submit(app: &mut Cursive) -> anyhow::Result<()> {
-    let (reader_factory, length) = {
+    let (reader_factory, length, transcoded) = {
         let root_model = &mut *app.get_root_model();
         let ds = &*root_model
             .get_datasource_ref()
             .ok_or(anyhow!("Data is not set"))?;
-        let length = match ds {
-            LineSourceHolder::Concrete(c) => Some(c.get_length()),
-            LineSourceHolder::Filtered(_) => None,
+        let (length, transcoded) = match ds {
+            LineSourceHolder::Concrete(c) => (Some(c.get_length()), root_model.get_transcoded()),
+            LineSourceHolder::Filtered(_) => (None, None),
         };
-        (ds.reader_factory(), length)
+        (ds.reader_factory(), length, transcoded)
     };
     let content = {
         app
@@ -69,6 +69,7 @@ fn try_submit(app: &mut Cursive) -> anyhow::Result<()> {
     let go_to_model = &mut *root_model.get_go_to_line_model();
     let line_registry = root_model.get_line_registry();
     go_to_model.set_line_registry(line_registry);
+    go_to_model.set_transcoded(transcoded);
     go_to_model.set_value(&content);
     go_to_model.submit(reader_factory, length)
 }
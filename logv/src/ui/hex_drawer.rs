@@ -0,0 +1,76 @@
+use cursive::theme::Style;
+use cursive::utils::span::SpannedString;
+use fluent_integer::Integer;
+
+use crate::app_theme::app_theme::AppThemeKey;
+use crate::highlight::style_with_priority::StyleWithPriority;
+use crate::interval::{Interval, PointLocationWithRespectToInterval};
+use crate::model::hex_view::{format_offset, to_ascii, BYTES_PER_ROW};
+use crate::model::model::RootModel;
+
+const OFFSET_DELIMITER: &str = " \u{2502} ";
+const ASCII_DELIMITER: &str = "\u{2502} ";
+
+/// Draws rows of hex view: offset, hex and ASCII columns
+pub struct HexDrawer {
+    regular_style: StyleWithPriority,
+    cursor_style: StyleWithPriority,
+    selection_style: StyleWithPriority,
+    offset_style: StyleWithPriority,
+    cursor: Integer,
+    selection: Interval<Integer>,
+}
+
+impl HexDrawer {
+    pub fn new(state: &RootModel) -> Self {
+        let app_theme = &state.app_theme;
+        HexDrawer {
+            regular_style: app_theme[AppThemeKey::Regular],
+            cursor_style: app_theme[AppThemeKey::Cursor],
+            selection_style: app_theme[AppThemeKey::Selection],
+            offset_style: app_theme[AppThemeKey::LineNumber],
+            cursor: state.get_hex_cursor(),
+            selection: state.get_hex_selection(),
+        }
+    }
+
+    /// Draws a row that starts at `offset`. The row is shorter than [BYTES_PER_ROW] only at the
+    /// end of the source.
+    pub fn draw(&self, offset: Integer, bytes: &[u8]) -> SpannedString<Style> {
+        let regular_style = self.regular_style.get_style();
+        let mut result = SpannedString::new();
+        result.append_styled(format_offset(offset), self.offset_style.get_style());
+        result.append_styled(OFFSET_DELIMITER, self.offset_style.get_style());
+
+        for i in 0..BYTES_PER_ROW {
+            if i == BYTES_PER_ROW / 2 {
+                result.append_styled(" ", regular_style);
+            }
+            match bytes.get(i) {
+                Some(b) => result.append_styled(format!("{:02x}", b), self.byte_style(offset + i)),
+                // cursor may be right after the last byte
+                None if self.cursor == offset + i =>
+                    result.append_styled("  ", (self.regular_style + self.cursor_style).get_style()),
+                None => result.append_styled("  ", regular_style),
+            }
+            result.append_styled(" ", regular_style);
+        }
+
+        result.append_styled(ASCII_DELIMITER, self.offset_style.get_style());
+        for (i, b) in bytes.iter().enumerate() {
+            result.append_styled(to_ascii(*b).to_string(), self.byte_style(offset + i));
+        }
+        result
+    }
+
+    fn byte_style(&self, offset: Integer) -> Style {
+        let mut style = self.regular_style;
+        if matches!(self.selection.point_location(&offset), PointLocationWithRespectToInterval::Belongs) {
+            style = style + self.selection_style;
+        }
+        if self.cursor == offset {
+            style = style + self.cursor_style;
+        }
+        style.get_style()
+    }
+}
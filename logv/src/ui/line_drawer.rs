@@ -1,8 +1,11 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write;
 use std::rc::Rc;
 
+use chrono::Duration;
 use cursive::theme::Style;
 use cursive::utils::span::{IndexedCow, IndexedSpan, SpannedString};
+use fluent_integer::Integer;
 
 use crate::data_source::line_registry::LineRegistryError;
 use crate::highlight::highlight::Highlighter;
@@ -10,11 +13,19 @@ use crate::highlight::style_with_priority::StyleWithPriority;
 use crate::interval::Interval;
 use crate::model::model::RootModel;
 use crate::model::rendered::{LineNumberMissingReason, LineRender};
+use crate::model::time_delta::{format_delta, TIME_DELTA_WIDTH};
 use crate::ui::span_producer::SpanProducer;
+use crate::utils::NumberOfDecimalDigits;
 
 const LINE_NO_DELIMITER: &str = " \u{2502}";
 const LINE_NO_DELIMITER_WIDTH: usize = LINE_NO_DELIMITER.len();
+/// Number of screen columns taken by [LINE_NO_DELIMITER]
+pub const LINE_NO_DELIMITER_COLUMNS: usize = 2;
 const LINE_NO_DELIMITER_BYTE_WIDTH: usize = LINE_NO_DELIMITER.as_bytes().len();
+/// Drawn instead of [LINE_NO_DELIMITER] next to the first line of a concatenated file
+const FILE_BOUNDARY_DELIMITER: &str = "\u{25B8}\u{2502}";
+/// Number of screen columns taken by time delta column
+pub const TIME_DELTA_COLUMNS: usize = TIME_DELTA_WIDTH + LINE_NO_DELIMITER_COLUMNS;
 // const LOADING_INDICATOR: &str = "⌛"; TODO for some reason printing line with this symbol drops one space
 const LOADING_INDICATOR: &str = "⧖";
 const ERROR_INDICATOR: &str = "⚠";
@@ -30,6 +41,8 @@ pub struct LineDrawer<'a> {
     line_number_style: Option<StyleWithPriority>,
     show_line_numbers: bool,
     max_line_number: u64,
+    time_deltas: Option<&'a BTreeMap<Integer, Duration>>,
+    file_boundaries: Option<&'a BTreeSet<Integer>>,
 }
 
 impl<'a> LineDrawer<'a> {
@@ -82,8 +95,22 @@ impl<'a> LineDrawer<'a> {
         self
     }
 
-    pub fn draw(&self, line: &LineRender) -> SpannedString<Style> {
-        let mut result = self.draw_line_number(line)
+    /// Time deltas are shown next to line numbers
+    pub fn with_time_deltas(mut self, time_deltas: &'a BTreeMap<Integer, Duration>) -> Self {
+        self.time_deltas.replace(time_deltas);
+        self
+    }
+
+    /// Lines starting at `file_boundaries` are marked next to line numbers
+    pub fn with_file_boundaries(mut self, file_boundaries: &'a BTreeSet<Integer>) -> Self {
+        self.file_boundaries.replace(file_boundaries);
+        self
+    }
+
+    /// Draws `row`-th screen row of the line. Rows other than the first one exist only if soft
+    /// wrap is on.
+    pub fn draw(&self, line: &LineRender, row: usize) -> SpannedString<Style> {
+        let mut result = self.draw_line_number(line, row == 0)
             .unwrap_or_else(|err| {
                 log::warn!("Failed to draw line number: {:?}", err);
                 SpannedString::new()
@@ -92,12 +119,16 @@ impl<'a> LineDrawer<'a> {
         let state = self.state.unwrap();
         let highlighters = self.highlighters.unwrap();
         let line_span_width = result.source().len();
-        let width = self.width.map(|w| w.saturating_sub(line_span_width)).unwrap();
+        let wrap_width = state.get_wrap_width();
+        let width = wrap_width.unwrap_or_else(|| self.width.map(|w| w.saturating_sub(line_span_width)).unwrap());
         let regular_style = self.regular_style.unwrap();
         let cursor_style = self.cursor_style.unwrap();
         let selection_style = self.selection_style.unwrap();
 
-        let horizontal_scroll = state.get_horizontal_scroll().as_usize();
+        let horizontal_scroll = match wrap_width {
+            Some(wrap_width) => row * wrap_width,
+            None => state.get_horizontal_scroll().as_usize(),
+        };
         let cursor = state.get_cursor();
 
         let get_visible_graphemes = || line.render.iter()
@@ -172,7 +203,7 @@ impl<'a> LineDrawer<'a> {
         } else {
             let mut spans = vec![];
             let mut s = String::with_capacity(1);
-            if cursor >= line.start && cursor <= line.end {
+            if cursor >= line.start && cursor <= line.end && row == 0 {
                 spans.push(indexed_span(0, 1, 1, cursor_style.get_style()));
                 s.push(' ');
             }
@@ -183,14 +214,26 @@ impl<'a> LineDrawer<'a> {
         result
     }
 
-    fn draw_line_number(&self, line: &LineRender) -> Result<SpannedString<Style>, std::fmt::Error> {
-        if !self.show_line_numbers {
-            return Ok(SpannedString::new());
+    /// Draws line number and time delta, or blank space of the same width for continuation rows
+    /// of a wrapped line
+    fn draw_line_number(&self, line: &LineRender, is_first_row: bool) -> Result<SpannedString<Style>, std::fmt::Error> {
+        let mut result = SpannedString::new();
+        if self.show_line_numbers {
+            result.append(self.draw_number(line, is_first_row)?);
+        }
+        if let Some(time_deltas) = self.time_deltas {
+            result.append(self.draw_time_delta(time_deltas.get(&line.start).filter(|_| is_first_row))?);
         }
+        Ok(result)
+    }
 
+    fn draw_number(&self, line: &LineRender, is_first_row: bool) -> Result<SpannedString<Style>, std::fmt::Error> {
         let line_number_width = self.max_line_number.checked_ilog10().unwrap_or(0) as usize + 1;
         let mut prefix = String::new();
         match &line.line_no {
+            _ if !is_first_row => {
+                write!(&mut prefix, "{:width$}", "", width = line_number_width)?;
+            },
             Ok(line_no) => {
                 write!(
                     &mut prefix,
@@ -217,15 +260,43 @@ impl<'a> LineDrawer<'a> {
             }
         }
         let line_number_offset = prefix.as_bytes().len();
-        prefix += LINE_NO_DELIMITER;
+        let is_file_boundary = is_first_row && self.file_boundaries.is_some_and(|b| b.contains(&line.start));
+        let delimiter = if is_file_boundary { FILE_BOUNDARY_DELIMITER } else { LINE_NO_DELIMITER };
+        prefix += delimiter;
         let spans = vec![
             indexed_span(0, line_number_offset, line_number_width, self.line_number_style.unwrap().get_style()),
-            indexed_span(line_number_offset, line_number_offset + LINE_NO_DELIMITER_BYTE_WIDTH, LINE_NO_DELIMITER_WIDTH, self.line_number_style.unwrap().get_style())
+            indexed_span(line_number_offset, line_number_offset + delimiter.len(), delimiter.len(), self.line_number_style.unwrap().get_style())
+        ];
+        Ok(SpannedString::with_spans(prefix, spans))
+    }
+
+    /// Draws time delta of the line, or blank space for lines without date
+    fn draw_time_delta(&self, delta: Option<&Duration>) -> Result<SpannedString<Style>, std::fmt::Error> {
+        let mut prefix = String::new();
+        let text = delta.map(|delta| format_delta(*delta)).unwrap_or_default();
+        write!(&mut prefix, "{:>width$}", text, width = TIME_DELTA_WIDTH)?;
+        let delta_offset = prefix.as_bytes().len();
+        let delta_width = prefix.chars().count();
+        prefix += LINE_NO_DELIMITER;
+        let style = self.line_number_style.unwrap().get_style();
+        let spans = vec![
+            indexed_span(0, delta_offset, delta_width, style),
+            indexed_span(delta_offset, delta_offset + LINE_NO_DELIMITER_BYTE_WIDTH, LINE_NO_DELIMITER_WIDTH, style)
         ];
         Ok(SpannedString::with_spans(prefix, spans))
     }
 }
 
+/// Number of columns left for line content in a viewport of `viewport_width` columns, after
+/// line numbers up to `max_line_number` and time deltas, if they are shown
+pub fn text_width(viewport_width: usize, max_line_number: Option<u64>, show_time_deltas: bool) -> usize {
+    let line_numbers_width = max_line_number
+        .map(|n| n.number_of_decimal_digits() + LINE_NO_DELIMITER_COLUMNS)
+        .unwrap_or(0);
+    let time_deltas_width = if show_time_deltas { TIME_DELTA_COLUMNS } else { 0 };
+    viewport_width.saturating_sub(line_numbers_width + time_deltas_width)
+}
+
 fn indexed_span<T, I1, I2>(start: I1, end: I2, width: usize, attr: T) -> IndexedSpan<T>
     where I1: TryInto<usize>, I2: TryInto<usize>
 {
@@ -239,3 +310,22 @@ fn indexed_span<T, I1, I2>(start: I1, end: I2, width: usize, attr: T) -> Indexed
         width
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use crate::model::time_delta::TIME_DELTA_WIDTH;
+    use super::text_width;
+
+    #[test]
+    fn test_text_width() {
+        assert_that!(text_width(80, None, false)).is_equal_to(80);
+        // digits of the line number and the delimiter
+        assert_that!(text_width(80, Some(9), false)).is_equal_to(77);
+        assert_that!(text_width(80, Some(1000), false)).is_equal_to(74);
+        assert_that!(text_width(80, None, true)).is_equal_to(80 - TIME_DELTA_WIDTH - 2);
+        assert_that!(text_width(80, Some(10), true)).is_equal_to(76 - TIME_DELTA_WIDTH - 2);
+        assert_that!(text_width(3, Some(1000), true)).is_equal_to(0);
+    }
+}
@@ -4,6 +4,7 @@ use cursive::views::LinearLayout;
 use crate::model::model::RootModel;
 use crate::shared::Shared;
 use crate::ui::canvas::build_canvas;
+use crate::ui::minimap::build_minimap;
 use crate::ui::status_panel::build_status_panel;
 use crate::ui::ui_elements::UIElementName;
 
@@ -40,7 +41,10 @@ pub fn build_ui(model: Shared<RootModel>) -> Box<dyn View> {
 
     let mut layout = LinearLayout::vertical();
     // layout.add_child(menu);
-    layout.add_child(build_canvas(model).full_height());
+    let mut content = LinearLayout::horizontal();
+    content.add_child(build_canvas(model.clone()).full_width());
+    content.add_child(build_minimap(model));
+    layout.add_child(content.full_height());
     layout.add_child(build_status_panel().view);
 
     layout.focus_view(&Selector::Name(UIElementName::MainContent.to_string().as_str()))
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use cursive::view::Nameable;
+use cursive::views::{Canvas, NamedView};
+use fluent_integer::Integer;
+
+use crate::app_theme::app_theme::AppThemeKey;
+use crate::model::minimap_model::HitKind;
+use crate::model::model::RootModel;
+use crate::shared::Shared;
+use crate::ui::ui_elements::UIElementName;
+
+pub const MINIMAP_WIDTH: usize = 1;
+
+const SYMBOL_BOOKMARK: &str = "●";
+const SYMBOL_SEARCH: &str = "■";
+const SYMBOL_FILTER: &str = "▪";
+const SYMBOL_EMPTY: &str = "│";
+const SYMBOL_VIEWPORT: &str = " ";
+
+pub fn build_minimap(model: Shared<RootModel>) -> NamedView<Canvas<Shared<RootModel>>> {
+    Canvas::new(model)
+        .with_draw(|state, printer| {
+            let state = state.get_ref();
+            let rows = printer.size.y;
+            if rows == 0 {
+                return;
+            }
+            let app_theme = &state.app_theme;
+            let minimap = state.get_minimap_model();
+            let file_length = minimap.get_file_length();
+            let row_of = |offset: Integer| -> usize {
+                if file_length <= 0 {
+                    0
+                } else {
+                    (offset * rows / file_length).as_usize().min(rows - 1)
+                }
+            };
+
+            let viewport = state.data()
+                .and_then(|data| data.start.zip(data.end))
+                .and_then(|(s, e)| {
+                    let ds = state.get_datasource_ref()?;
+                    let s = ds.to_original_offset(s)?;
+                    let e = ds.to_original_offset(e).unwrap_or(s);
+                    Some((row_of(s), row_of(e)))
+                });
+            let bookmarks: HashSet<usize> = state.get_bookmarks().iter()
+                .map(|offset| row_of(*offset))
+                .collect();
+
+            for row in 0..rows {
+                let mut style = app_theme[AppThemeKey::Regular];
+                let mut symbol = SYMBOL_EMPTY;
+                if viewport.is_some_and(|(s, e)| s <= row && row <= e) {
+                    style = style + app_theme[AppThemeKey::MinimapViewport];
+                    symbol = SYMBOL_VIEWPORT;
+                }
+                if bookmarks.contains(&row) {
+                    style = style + app_theme[AppThemeKey::MinimapBookmark];
+                    symbol = SYMBOL_BOOKMARK;
+                } else if minimap.get_hits(HitKind::Search).count(row, rows) > 0 {
+                    style = style + app_theme[AppThemeKey::MinimapSearch];
+                    symbol = SYMBOL_SEARCH;
+                } else if minimap.get_hits(HitKind::Filter).count(row, rows) > 0 {
+                    style = style + app_theme[AppThemeKey::MinimapFilter];
+                    symbol = SYMBOL_FILTER;
+                }
+                printer.with_style(style.get_style(), |printer| printer.print((0, row), symbol));
+            }
+        })
+        .with_required_size(|_, constraint| (MINIMAP_WIDTH, constraint.y).into())
+        .with_name(UIElementName::Minimap)
+}
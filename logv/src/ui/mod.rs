@@ -12,10 +12,17 @@ pub mod progress_dialog;
 pub mod ui_utils;
 pub mod bgp_status;
 pub mod filter_dialog;
+pub mod clustering_dialog;
+pub mod date_format_dialog;
+pub mod field_table_dialog;
+pub mod details_dialog;
+pub mod theme_dialog;
 pub mod palette_utils;
 mod span_producer;
 mod line_drawer;
+mod hex_drawer;
 mod canvas;
+mod minimap;
 mod status_panel;
 mod view_with_callback;
 
@@ -5,20 +5,30 @@ use cursive::{Cursive, View};
 use cursive::theme::{PaletteColor, Style};
 use cursive::traits::{Nameable, Resizable, Scrollable};
 use cursive::view::IntoBoxedView;
-use cursive::views::{Button, Dialog, LinearLayout, NamedView, PaddedView, ScrollView, SelectView, TextContent, TextView};
+use cursive::views::{Button, Checkbox, Dialog, EditView, LinearLayout, NamedView, PaddedView, ScrollView, SelectView, TextContent, TextView};
 use human_bytes::human_bytes;
-use crate::model::open_file_model::{DirEntry0, OpenFileModel, OpenFileModelEvent};
+use crate::model::file_preview::PREVIEW_LINES;
+use crate::model::open_file_model::{DirEntry0, OpenFileModel, OpenFileModelEvent, SortOrder};
 use crate::ui::view_with_callback::{ViewUpdateCallback, ViewWithCallback};
 use crate::ui::with_root_model::WithRootModel;
 
 const BREADCRUMBS_PANEL: &str = "BreadcrumbsPanel";
 const FILE_LIST: &str = "FileList";
 const FILE_LIST_SCROLL: &str = "FileListScroll";
-const FILE_LIST_SIZE: (usize, usize) = (40, 20);
+const FILE_LIST_SIZE: (usize, usize) = (40, 14);
+const FILTER_FIELD: &str = "FileFilter";
 const FILE_INFO_SIZE: &str = "FileInfoSize";
 const FILE_INFO_CREATED_AT: &str = "FileInfoCreatedAt";
 const FILE_INFO_MODIFIED_AT: &str = "FileInfoModifiedAt";
-const FILE_INFO_PANEL_SIZE: (usize, usize) = (19, 10);
+const FILE_INFO_PERMISSIONS: &str = "FileInfoPermissions";
+const FILE_INFO_TYPE: &str = "FileInfoType";
+const FILE_PREVIEW: &str = "FilePreview";
+const FILE_INFO_ELEMENTS: [&str; 6] = [
+    FILE_INFO_SIZE, FILE_INFO_CREATED_AT, FILE_INFO_MODIFIED_AT, FILE_INFO_PERMISSIONS, FILE_INFO_TYPE, FILE_PREVIEW,
+];
+const SORT_BUTTON: &str = "SortButton";
+const FILE_INFO_PANEL_SIZE: (usize, usize) = (19, 14);
+const RECENT_FILES_HEIGHT: usize = 5;
 const PLACEHOLDER: &str = "-";
 const PADDING: usize = 1;
 const DIALOG_WIDTH: usize = PADDING + FILE_LIST_SIZE.0 + PADDING + FILE_INFO_PANEL_SIZE.0 + PADDING;
@@ -30,17 +40,26 @@ type FileListScroll = ScrollView<NamedView<SelectView>>;
 pub fn build_open_file_dialog(model: &mut OpenFileModel) -> ViewWithCallback {
     let mut content = LinearLayout::vertical();
     content.add_child(build_breadcrumbs(model));
+    content.add_child(build_filter(model));
+    let file_info = FILE_INFO_ELEMENTS.map(|_| TextContent::new(PLACEHOLDER));
+    update_file_info_panel(model)(file_info.clone());
     {
         let mut panel = LinearLayout::horizontal();
         panel.add_child(build_file_selector(model));
-        panel.add_child(build_file_info_panel(model));
+        panel.add_child(build_file_info_panel(&file_info));
         content.add_child(panel);
     }
+    content.add_child(build_preview(&file_info[5]));
+    content.add_child(build_list_options(model));
+    if !model.get_recent_files().is_empty() {
+        content.add_child(build_recent_files(model));
+    }
     let dialog = Dialog::new()
         .title("Open file")
         .padding_lrtb(1, 1, 1, 1)
         .content(content)
         .button("Open", on_open)
+        .button("Open folder", on_open_folder)
         .button("Cancel", close)
         .into_boxed_view();
     let callback = Box::new(focus_file_list);
@@ -68,10 +87,19 @@ pub fn handle_open_file_model_event(model: &mut OpenFileModel, evt: OpenFileMode
                 });
             })
         },
+        OpenFileModelEvent::FilterUpdated => {
+            let filter = model.get_filter().to_string();
+            Box::new(move |app| {
+                app.call_on_name(FILTER_FIELD, |edit: &mut EditView| {
+                    // the callback would set the same filter again
+                    let _ = edit.set_content(filter);
+                });
+            })
+        },
         OpenFileModelEvent::EntryInfoUpdated => {
             let callback = update_file_info_panel(model);
             Box::new(|app| {
-                let elements = [FILE_INFO_SIZE, FILE_INFO_CREATED_AT, FILE_INFO_MODIFIED_AT]
+                let elements = FILE_INFO_ELEMENTS
                     .map(|id| app.find_name::<TextView>(id).unwrap().get_shared_content());
                 callback(elements);
             })
@@ -120,6 +148,64 @@ fn rebuild_breadcrumbs(model: &OpenFileModel) -> Box<dyn FnOnce(&mut LinearLayou
     })
 }
 
+fn build_filter(model: &OpenFileModel) -> Box<dyn View> {
+    let edit = EditView::new()
+        .content(model.get_filter())
+        .on_edit(|app, text, _| {
+            let root_model = app.get_root_model();
+            root_model.get_open_file_model().set_filter(text);
+        })
+        .on_submit(|app, text| {
+            let root_model = app.get_root_model();
+            let open_file_model = &mut *root_model.get_open_file_model();
+            open_file_model.set_filter(text);
+            open_file_model.submit_filter();
+        })
+        .with_name(FILTER_FIELD);
+    let panel = LinearLayout::horizontal()
+        .child(TextView::new("Filter or path: "))
+        .child(edit.full_width());
+    PaddedView::lrtb(1, 1, 0, 1, panel.max_width(DIALOG_WIDTH - 2 * PADDING))
+        .into_boxed_view()
+}
+
+fn build_list_options(model: &OpenFileModel) -> Box<dyn View> {
+    let show_hidden = Checkbox::new()
+        .with_checked(model.is_show_hidden())
+        .on_change(|app, checked| {
+            let root_model = app.get_root_model();
+            root_model.get_open_file_model().set_show_hidden(checked);
+        });
+    let find_logs = Checkbox::new()
+        .with_checked(model.is_find_logs())
+        .on_change(|app, checked| {
+            let root_model = app.get_root_model();
+            root_model.get_open_file_model().set_find_logs(checked);
+        });
+    let sort_order = Button::new(sort_label(model.get_sort_order()), |app| {
+        let sort_order = {
+            let root_model = app.get_root_model();
+            let open_file_model = &mut *root_model.get_open_file_model();
+            let sort_order = open_file_model.get_sort_order().next();
+            open_file_model.set_sort_order(sort_order);
+            sort_order
+        };
+        app.call_on_name(SORT_BUTTON, |button: &mut Button| button.set_label(sort_label(sort_order)));
+    });
+    let panel = LinearLayout::horizontal()
+        .child(show_hidden)
+        .child(TextView::new(" Hidden  "))
+        .child(find_logs)
+        .child(TextView::new(" *.log in subfolders  "))
+        .child(sort_order.with_name(SORT_BUTTON));
+    PaddedView::lrtb(1, 1, 1, 0, panel)
+        .into_boxed_view()
+}
+
+fn sort_label(sort_order: SortOrder) -> String {
+    format!("Sort by {}", sort_order)
+}
+
 fn build_file_selector(model: &mut OpenFileModel) -> Box<dyn View> {
     let mut scroll_view = SelectView::<String>::new()
         .on_select(|app, item| {
@@ -181,31 +267,52 @@ fn on_open(app: &mut Cursive) {
     }
 }
 
-fn build_file_info_panel(model: &OpenFileModel) -> Box<dyn View> {
-    let mut file_size = TextView::new(PLACEHOLDER);
-    let mut created_at = TextView::new(PLACEHOLDER);
-    let mut modified_at = TextView::new(PLACEHOLDER);
+fn on_open_folder(app: &mut Cursive) {
+    let root_model = &mut *app.get_root_model();
+    let open_file_model = &mut *root_model.get_open_file_model();
+    open_file_model.open_current_location();
+}
 
-    let elements = [
-        file_size.get_shared_content(),
-        created_at.get_shared_content(),
-        modified_at.get_shared_content(),
-    ];
-    update_file_info_panel(model)(elements);
+fn build_recent_files(model: &OpenFileModel) -> Box<dyn View> {
+    let mut list = SelectView::<PathBuf>::new()
+        .on_submit(|app, path: &PathBuf| {
+            let root_model = app.get_root_model();
+            let open_file_model = &mut *root_model.get_open_file_model();
+            open_file_model.open_recent_file(path);
+        });
+    for path in model.get_recent_files() {
+        list.add_item(path.display().to_string(), path.clone());
+    }
+    let panel = LinearLayout::vertical()
+        .child(TextView::new("Recent files:").style(Style::from(PaletteColor::TitleSecondary)))
+        .child(list.scrollable().max_height(RECENT_FILES_HEIGHT));
+    PaddedView::lrtb(1, 1, 1, 0, panel.max_width(DIALOG_WIDTH - 2 * PADDING))
+        .into_boxed_view()
+}
+
+fn build_file_info_panel(file_info: &[TextContent]) -> Box<dyn View> {
+    let labels = ["Size:", "Created At:", "Modified At:", "Permissions:", "Type:"];
+    let mut panel = LinearLayout::vertical();
+    for (i, label) in labels.into_iter().enumerate() {
+        panel.add_child(TextView::new(label).style(Style::from(PaletteColor::TitleSecondary)));
+        panel.add_child(TextView::new_with_content(file_info[i].clone()).with_name(FILE_INFO_ELEMENTS[i]));
+    }
+    PaddedView::lrtb(1, 1, 0, 0, panel.fixed_size(FILE_INFO_PANEL_SIZE))
+        .into_boxed_view()
+}
 
+fn build_preview(preview: &TextContent) -> Box<dyn View> {
     let panel = LinearLayout::vertical()
-        .child(TextView::new("Size:").style(Style::from(PaletteColor::TitleSecondary)))
-        .child(file_size.with_name(FILE_INFO_SIZE))
-        .child(TextView::new("Created At:").style(Style::from(PaletteColor::TitleSecondary)))
-        .child(created_at.with_name(FILE_INFO_CREATED_AT))
-        .child(TextView::new("Modified At:").style(Style::from(PaletteColor::TitleSecondary)))
-        .child(modified_at.with_name(FILE_INFO_MODIFIED_AT))
-        .fixed_size(FILE_INFO_PANEL_SIZE);
-    PaddedView::lrtb(1, 1, 0, 0, panel)
+        .child(TextView::new("Preview:").style(Style::from(PaletteColor::TitleSecondary)))
+        .child(TextView::new_with_content(preview.clone())
+            .no_wrap()
+            .with_name(FILE_PREVIEW)
+            .fixed_height(PREVIEW_LINES));
+    PaddedView::lrtb(1, 1, 1, 0, panel.fixed_width(DIALOG_WIDTH - 2 * PADDING))
         .into_boxed_view()
 }
 
-fn update_file_info_panel(model: &OpenFileModel) -> Box<dyn FnOnce([TextContent; 3])> {
+fn update_file_info_panel(model: &OpenFileModel) -> Box<dyn FnOnce([TextContent; 6])> {
     let entry_info = model.get_entry_info().map(Clone::clone);
     Box::new(move |text_views| {
         let size = entry_info.as_ref()
@@ -225,6 +332,21 @@ fn update_file_info_panel(model: &OpenFileModel) -> Box<dyn FnOnce([TextContent;
             .map(print_date)
             .unwrap_or(String::from(PLACEHOLDER));
         text_views[2].set_content(modified_at);
+
+        let permissions = entry_info.as_ref()
+            .map(|e| e.permissions.clone())
+            .unwrap_or(String::from(PLACEHOLDER));
+        text_views[3].set_content(permissions);
+
+        let preview = entry_info.as_ref().and_then(|e| e.preview.as_ref());
+        let kind = preview
+            .map(|p| p.kind.to_string())
+            .unwrap_or(String::from(PLACEHOLDER));
+        text_views[4].set_content(kind);
+        let lines = preview
+            .map(|p| p.lines.join("\n"))
+            .unwrap_or_default();
+        text_views[5].set_content(lines);
     })
 }
 
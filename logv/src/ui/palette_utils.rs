@@ -1,4 +1,4 @@
-use cursive::theme::{Color, Palette};
+use cursive::theme::{BaseColor, Color, Palette};
 
 #[allow(dead_code)]
 pub trait PaletteAdditions {
@@ -21,38 +21,40 @@ pub trait PaletteAdditions {
 
 impl PaletteAdditions for Palette {
     fn yellow(&self) -> Color {
-        custom_color(self, "yellow")
+        custom_color(self, "yellow", Color::Dark(BaseColor::Yellow))
     }
 
     fn orange(&self) -> Color {
-        custom_color(self, "orange")
+        custom_color(self, "orange", Color::Light(BaseColor::Red))
     }
 
     fn red(&self) -> Color {
-        custom_color(self, "red")
+        custom_color(self, "red", Color::Dark(BaseColor::Red))
     }
 
     fn magenta(&self) -> Color {
-        custom_color(self, "magenta")
+        custom_color(self, "magenta", Color::Dark(BaseColor::Magenta))
     }
 
     fn violet(&self) -> Color {
-        custom_color(self, "violet")
+        custom_color(self, "violet", Color::Light(BaseColor::Blue))
     }
 
     fn blue(&self) -> Color {
-        custom_color(self, "blue")
+        custom_color(self, "blue", Color::Dark(BaseColor::Blue))
     }
 
     fn cyan(&self) -> Color {
-        custom_color(self, "cyan")
+        custom_color(self, "cyan", Color::Dark(BaseColor::Cyan))
     }
 
     fn green(&self) -> Color {
-        custom_color(self, "green")
+        custom_color(self, "green", Color::Dark(BaseColor::Green))
     }
 }
 
-fn custom_color(palette: &Palette, key: &str) -> Color {
-    *palette.custom(key).unwrap()
+/// Custom color of the palette. Themes made by users may lack it, so the closest base color is
+/// used then.
+fn custom_color(palette: &Palette, key: &str, fallback: Color) -> Color {
+    palette.custom(key).copied().unwrap_or(fallback)
 }
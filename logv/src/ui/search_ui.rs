@@ -2,7 +2,6 @@ use anyhow::anyhow;
 use cursive::{Cursive, View};
 use cursive::view::{Nameable, Resizable};
 use cursive::views::{Checkbox, Dialog, EditView, LinearLayout, TextView};
-use crate::data_source::reader_factory::HasReaderFactory;
 use crate::model::model::RootModel;
 use crate::shared::Shared;
 use crate::ui::ui_elements::UIElementName;
@@ -12,14 +11,17 @@ pub fn build_search_ui(state: Shared<RootModel>) -> Box<dyn View> {
     let do_search = error_boundary(|app: &mut Cursive, search_str: &str| {
         let mut root_model = app.get_root_model();
         let mut search_model = root_model.get_search_model();
+        let is_hex = search_model.is_hex();
         if search_model.is_from_cursor() {
-            search_model.set_cursor(root_model.get_cursor());
+            search_model.set_cursor(root_model.get_search_cursor(is_hex));
         }
         search_model.set_pattern(search_str);
-        let reader_factory = root_model.get_datasource_ref()
-            .ok_or(anyhow!(""))?
-            .reader_factory();
-        let search = search_model.start_search(reader_factory)?;
+        let reader_factory = root_model.get_search_reader_factory(is_hex)
+            .ok_or(anyhow!(""))?;
+        let mut search = search_model.start_search(reader_factory)?;
+        if let Some(transcoded) = root_model.get_hex_search_file(is_hex) {
+            search = search.with_file_offsets(transcoded);
+        }
         search_model.set_visible(false);
         drop(search_model);
         root_model.set_current_search(Some(search));
@@ -63,6 +65,15 @@ pub fn build_search_ui(state: Shared<RootModel>) -> Box<dyn View> {
         .with_name(UIElementName::SearchRegexp.to_string())
     );
     search_settings_panel.add_child(TextView::new("Regexp"));
+    search_settings_panel.add_child(Checkbox::new()
+        .with_checked(search_model.is_hex())
+        .on_change(|app, is_checked| {
+            let model = app.get_root_model();
+            model.get_search_model().set_hex(is_checked);
+        })
+        .with_name(UIElementName::SearchHex.to_string())
+    );
+    search_settings_panel.add_child(TextView::new("Hex bytes"));
     layout.add_child(search_settings_panel);
 
     let dialog = Dialog::new()
@@ -0,0 +1,43 @@
+use cursive::Cursive;
+use cursive::traits::{Nameable, Resizable};
+use cursive::views::{Dialog, SelectView};
+
+use crate::app_theme::app_theme::AppThemeName;
+use crate::model::theme_model::{ThemeModel, ThemeModelEvent};
+use crate::ui::ui_utils::PopLayerSafely;
+use crate::ui::view_with_callback::{ViewUpdateCallback, ViewWithCallback};
+use crate::ui::with_root_model::WithRootModel;
+
+const THEME_DIALOG: &str = "ThemeDialog";
+
+pub fn handle_theme_model_event(model: &ThemeModel, current: &AppThemeName, evt: ThemeModelEvent) -> ViewUpdateCallback {
+    match evt {
+        ThemeModelEvent::Open(true) => build_theme_dialog(model, current).into(),
+        ThemeModelEvent::Open(false) => Box::new(|app: &mut Cursive| app.pop_layer_safely(THEME_DIALOG)),
+    }
+}
+
+fn build_theme_dialog(model: &ThemeModel, current: &AppThemeName) -> ViewWithCallback {
+    let mut select = SelectView::new();
+    for name in model.get_themes() {
+        select.add_item(name.to_string(), name.clone());
+    }
+    if let Some(i) = model.get_themes().iter().position(|name| name == current) {
+        // no callback is set yet
+        let _ = select.set_selection(i);
+    }
+    select.set_on_submit(|app: &mut Cursive, name: &AppThemeName| {
+        let root_model = app.get_root_model();
+        root_model.get_theme_model().set_open(false);
+        root_model.set_theme(name.clone());
+    });
+
+    let dialog = Dialog::new()
+        .title("Theme")
+        .content(select.min_width(30))
+        .button("Close", |app| {
+            app.get_root_model().get_theme_model().set_open(false);
+        })
+        .with_name(THEME_DIALOG);
+    ViewWithCallback::with_dummy_callback(dialog)
+}
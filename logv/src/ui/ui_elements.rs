@@ -1,5 +1,6 @@
 pub enum UIElementName {
     MainContent,
+    Minimap,
     StatusFile,
     StatusPosition,
     StatusHint,
@@ -8,6 +9,7 @@ pub enum UIElementName {
     SearchFromCursor,
     SearchBackward,
     SearchRegexp,
+    SearchHex,
     GoToValue,
     GoToDateValue,
     HelpTable,
@@ -17,6 +19,7 @@ impl ToString for UIElementName {
     fn to_string(&self) -> String {
         let str = match self {
             UIElementName::MainContent => "main_content",
+            UIElementName::Minimap => "minimap",
             UIElementName::StatusFile => "status_file",
             UIElementName::StatusPosition => "status_position",
             UIElementName::StatusHint => "status_hint",
@@ -25,6 +28,7 @@ impl ToString for UIElementName {
             UIElementName::SearchFromCursor => "search_from_cursor",
             UIElementName::SearchBackward => "search_backward",
             UIElementName::SearchRegexp => "search_regexp",
+            UIElementName::SearchHex => "search_hex",
             UIElementName::GoToValue => "go_to_value",
             UIElementName::GoToDateValue => "go_to_date_value",
             UIElementName::HelpTable => "help_table",
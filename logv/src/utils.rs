@@ -1,5 +1,7 @@
 use std::borrow::Cow;
 use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use cursive::utils::span::IndexedCow;
 use log::Level;
@@ -8,6 +10,9 @@ use stopwatch::Stopwatch;
 use unicode_segmentation::UnicodeSegmentation;
 use fluent_integer::Integer;
 use paste::paste;
+use crate::config::DEFAULT_TAB_WIDTH;
+
+static TAB_WIDTH: AtomicUsize = AtomicUsize::new(DEFAULT_TAB_WIDTH);
 
 pub fn sign(n: Integer) -> (Integer, i8) {
     if n >= 0 {
@@ -150,7 +155,13 @@ macro_rules! number_of_decimal_digits_impl_unsigned {
 number_of_decimal_digits_impl_unsigned!(u64 u32 u16 u8);
 
 pub mod utf8 {
-    use std::io::{BufReader, ErrorKind, Read, Seek};
+    use std::io::{BufReader, Read, Seek};
+    use std::ops::Range;
+
+    /// Stands for a byte that is not a part of valid UTF-8 sequence. Being a single-byte char, it
+    /// keeps offsets in decoded text equal to offsets in the source. As the same char may occur in
+    /// the source, positions of substituted bytes are reported separately.
+    pub const SUBSTITUTE: char = '\u{1A}';
 
     pub enum UnicodeByteType {
         Single,
@@ -179,27 +190,10 @@ pub mod utf8 {
     pub struct UtfChar {
         ch: char,
         offset: u64,
+        is_valid: bool,
     }
 
     impl UtfChar {
-        fn from_u8(b: u8, offset: u64) -> Self {
-            UtfChar {
-                ch: char::from(b),
-                offset,
-            }
-        }
-
-        fn from_little_endian(bytes: [u8; 4], offset: u64) -> std::io::Result<Self> {
-            let s = std::str::from_utf8(&bytes)
-                .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
-            match s.chars().next() {
-                Some(ch) => Ok(UtfChar {
-                    ch, offset
-                }),
-                None => panic!("Invalid UTF-8 sequence at offset {}: {:?}", offset, bytes) // TODO proper Err
-            }
-        }
-
         pub fn get_char(&self) -> char {
             self.ch
         }
@@ -211,56 +205,135 @@ pub mod utf8 {
         pub fn get_end(&self) -> u64 {
             self.offset + (self.ch.len_utf8() as u64)
         }
+
+        /// Whether the char has been decoded, rather than substituted for a malformed byte
+        pub fn is_valid(&self) -> bool {
+            self.is_valid
+        }
     }
 
-    // TODO: big endian support
+    /// Reads char that contains current position of `reader`, which may point to the middle of
+    /// a multibyte char. Reader is left at the end of the char.
+    ///
+    /// Each byte of a malformed sequence is read as [SUBSTITUTE].
     pub fn read_utf_char<R>(reader: &mut BufReader<R>) -> std::io::Result<Option<UtfChar>>
         where R: Read + Seek
     {
         let offset = reader.stream_position()?;
-        read_utf_char_internal(reader, offset)
+        // a char starts at most 3 bytes before `offset` and is at most 4 bytes long
+        let back = offset.min(3);
+        reader.seek_relative(-(back as i64))?;
+        let mut window = [0_u8; 7];
+        let mut len = 0;
+        while len < window.len() {
+            match reader.read(&mut window[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        let window_start = offset - back;
+        if len as u64 <= back {
+            reader.seek_relative(back as i64 - len as i64)?;
+            return Ok(None);
+        }
+
+        let window = &window[..len];
+        let back = back as usize;
+        let (start, ch, is_valid) = (0..=back)
+            .find_map(|i| decode_first(&window[back - i..])
+                .filter(|ch| ch.len_utf8() > i)
+                .map(|ch| (offset - i as u64, ch, true)))
+            .unwrap_or((offset, SUBSTITUTE, false));
+        let end = start + ch.len_utf8() as u64;
+        reader.seek_relative(end as i64 - (window_start + len as u64) as i64)?;
+        Ok(Some(UtfChar { ch, offset: start, is_valid }))
     }
 
-    // TODO: big endian support
-    fn read_utf_char_internal<R>(reader: &mut BufReader<R>, offset: u64) -> std::io::Result<Option<UtfChar>>
-    where R: Read + Seek
-    {
-        let mut buf = [0_u8; 1];
-        let bytes_read = reader.read(&mut buf)?;
-        if bytes_read == 1 {
-            let b = buf[0];
-            match utf_byte_type(b) {
-                Ok(UnicodeByteType::Single) => Ok(Some(UtfChar::from_u8(b, offset))),
-                Ok(UnicodeByteType::FirstOf2) => match reader.read(&mut buf)? {
-                    0 => panic!("Invalid UTF-8 sequence at {}", offset), // TODO: Err()
-                    1 => Ok(Some(UtfChar::from_little_endian([b, buf[0], 0, 0], offset)?)),
-                    _ => panic!("Impossible") // TODO err
-                },
-                Ok(UnicodeByteType::FirstOf3) => {
-                    let mut buf2 = [0_u8; 2];
-                    match reader.read(&mut buf2)? {
-                        2 => Ok(Some(UtfChar::from_little_endian([b, buf2[0], buf2[1], 0], offset)?)),
-                        _ => panic!("Invalid UTF-8 sequence at {}", offset), // TODO: Err()
-                    }
-                },
-                Ok(UnicodeByteType::FirstOf4) => {
-                    let mut buf2 = [0_u8; 3];
-                    match reader.read(&mut buf2)? {
-                        3 => Ok(Some(UtfChar::from_little_endian([b, buf2[0], buf2[1], buf2[2]], offset)?)),
-                        _ => panic!("Invalid UTF-8 sequence at {}", offset), // TODO: Err()
-                    }
-                },
-                Ok(UnicodeByteType::Continuation) => {
-                    if offset == 0 {
-                        panic!("") // todo proper Err
-                    }
-                    reader.seek_relative(-2)?;
-                    read_utf_char_internal(reader, offset - 1)
+    /// Decodes char that starts at the beginning of `bytes`
+    fn decode_first(bytes: &[u8]) -> Option<char> {
+        let len = match utf_byte_type(*bytes.first()?) {
+            Ok(UnicodeByteType::Single) => 1,
+            Ok(UnicodeByteType::FirstOf2) => 2,
+            Ok(UnicodeByteType::FirstOf3) => 3,
+            Ok(UnicodeByteType::FirstOf4) => 4,
+            Ok(UnicodeByteType::Continuation) | Err(_) => return None,
+        };
+        std::str::from_utf8(bytes.get(..len)?).ok()?.chars().next()
+    }
+
+    /// Same as [String::from_utf8_lossy], but replaces every byte of malformed sequences with
+    /// [SUBSTITUTE], so that the result has the same length as `bytes`.
+    pub fn decode_substituting(bytes: &[u8]) -> String {
+        decode_marking_invalid(bytes).0
+    }
+
+    /// Same as [decode_substituting], but also returns ranges of substituted bytes
+    pub fn decode_marking_invalid(mut bytes: &[u8]) -> (String, Vec<Range<usize>>) {
+        let mut result = String::with_capacity(bytes.len());
+        let mut invalid = vec![];
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(s) => {
+                    result.push_str(s);
+                    return (result, invalid);
                 },
-                Err(_) => panic!("Failed to recognize byte type at offset {}, value: {}", offset, b) // TODO proper Err
+                Err(e) => {
+                    let (valid, rest) = bytes.split_at(e.valid_up_to());
+                    result.push_str(std::str::from_utf8(valid).unwrap());
+                    let invalid_len = e.error_len().unwrap_or(rest.len());
+                    invalid.push(result.len()..result.len() + invalid_len);
+                    result.extend(std::iter::repeat(SUBSTITUTE).take(invalid_len));
+                    bytes = &rest[invalid_len..];
+                }
             }
-        } else {
-            Ok(None)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::io::{BufReader, Cursor, Seek, SeekFrom};
+        use spectral::prelude::*;
+
+        use super::{decode_marking_invalid, decode_substituting, read_utf_char, SUBSTITUTE};
+
+        fn read_all(bytes: &[u8], from: u64) -> Vec<(char, u64)> {
+            let mut reader = BufReader::new(Cursor::new(bytes));
+            reader.seek(SeekFrom::Start(from)).unwrap();
+            let mut result = vec![];
+            while let Some(ch) = read_utf_char(&mut reader).unwrap() {
+                result.push((ch.get_char(), ch.get_offset()));
+            }
+            result
+        }
+
+        #[test]
+        fn test_read_utf_char() {
+            let bytes = "a€b".as_bytes();
+            assert_that!(read_all(bytes, 0)).is_equal_to(vec![('a', 0), ('€', 1), ('b', 4)]);
+            // from the middle of a char
+            assert_that!(read_all(bytes, 2)).is_equal_to(vec![('€', 1), ('b', 4)]);
+        }
+
+        #[test]
+        fn test_read_invalid_sequences() {
+            let bytes = b"a\xe2\x82b\xfc\x80";
+            let expected = vec![('a', 0), (SUBSTITUTE, 1), (SUBSTITUTE, 2), ('b', 3), (SUBSTITUTE, 4), (SUBSTITUTE, 5)];
+            assert_that!(read_all(bytes, 0)).is_equal_to(expected);
+            assert_that!(read_all(bytes, 2)).is_equal_to(vec![(SUBSTITUTE, 2), ('b', 3), (SUBSTITUTE, 4), (SUBSTITUTE, 5)]);
+        }
+
+        #[test]
+        fn test_decode_substituting() {
+            let decoded = decode_substituting(b"a\xe2\x82b\xfc\x80\xe2\x82\xac");
+            assert_that!(decoded.as_str()).is_equal_to("a\u{1A}\u{1A}b\u{1A}\u{1A}€");
+            assert_that!(decode_substituting(b"ab\xe2").len()).is_equal_to(3);
+        }
+
+        #[test]
+        fn test_decode_marking_invalid() {
+            let (decoded, invalid) = decode_marking_invalid(b"\x1a\xe2\x82b\xfc\x1a");
+            assert_that!(decoded.as_str()).is_equal_to("\u{1A}\u{1A}\u{1A}b\u{1A}\u{1A}");
+            assert_that!(invalid).is_equal_to(vec![1..3, 4..5]);
         }
     }
 }
@@ -275,11 +348,16 @@ pub struct GraphemeRender {
 
 impl GraphemeRender {
 
-    pub fn from_string(string: &String) -> Vec<GraphemeRender> {
+    /// `invalid` are ranges of [utf8::SUBSTITUTE] chars that stand for malformed bytes
+    pub fn from_string(string: &String, invalid: &[Range<usize>]) -> Vec<GraphemeRender> {
         let mut result = vec![];
         let mut render_offset = 0_usize;
         for (original_offset, original_grapheme) in string.grapheme_indices(true) {
-            let render = GraphemeRender::render(original_grapheme);
+            let render = if invalid.iter().any(|range| range.contains(&original_offset)) {
+                Cow::Owned(String::from('\u{FFFD}'))
+            } else {
+                GraphemeRender::render(original_grapheme)
+            };
             match render {
                 Cow::Borrowed(_) => {
                     let render_len = render.len();
@@ -310,15 +388,33 @@ impl GraphemeRender {
         result
     }
 
+    /// Sets number of spaces tabs are rendered as
+    pub fn set_tab_width(tab_width: usize) {
+        TAB_WIDTH.store(tab_width, Ordering::Relaxed);
+    }
+
     fn render(grapheme: &str) -> Cow<str> {
         if grapheme.eq("\t") {
-            Cow::Owned(String::from("    "))
+            Cow::Owned(" ".repeat(TAB_WIDTH.load(Ordering::Relaxed)))
         } else if grapheme.eq("\u{FEFF}") {
             Cow::Owned(String::with_capacity(0))
+        } else if grapheme.chars().any(|ch| ch.is_control()) {
+            Cow::Owned(grapheme.chars().map(GraphemeRender::escape).collect())
         } else {
             Cow::Borrowed(grapheme)
         }
     }
+
+    /// Replaces chars that would break the layout with visible glyphs: control chars with
+    /// their Unicode control pictures, C1 controls with replacement char
+    fn escape(ch: char) -> char {
+        match ch {
+            '\u{0}'..='\u{1F}' => char::from_u32(0x2400 + ch as u32).unwrap(),
+            '\u{7F}' => '\u{2421}',
+            '\u{80}'..='\u{9F}' => '\u{FFFD}',
+            _ => ch,
+        }
+    }
 }
 
 pub mod event_emitter {
@@ -363,3 +459,30 @@ macro_rules! tout {
         crate::utils::print_debug(|| format!($fmt, $($args)*));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use spectral::prelude::*;
+
+    use crate::utils::GraphemeRender;
+
+    #[test]
+    fn test_control_chars_are_escaped() {
+        assert_that!(render("a\u{1B}[0m\tb\u{1A}", &[])).is_equal_to(String::from("a\u{241B}[0m    b\u{241A}"));
+    }
+
+    #[test]
+    fn test_invalid_bytes_are_replaced() {
+        assert_that!(render("a\u{1A}\u{1A}b\u{1A}", &[1..3])).is_equal_to(String::from("a\u{FFFD}\u{FFFD}b\u{241A}"));
+    }
+
+    fn render(s: &str, invalid: &[std::ops::Range<usize>]) -> String {
+        GraphemeRender::from_string(&String::from(s), invalid)
+            .into_iter()
+            .map(|g| match g.render {
+                cursive::utils::span::IndexedCow::Owned(s) => s,
+                cursive::utils::span::IndexedCow::Borrowed { start, end } => s[start..end].to_string(),
+            })
+            .collect()
+    }
+}